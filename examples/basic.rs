@@ -1,7 +1,7 @@
 extern crate rags_rs as rags;
 use rags::argparse;
 
-static LONG_DESC: &'static str =
+static LONG_DESC: &str =
 "This example aims to show beginner to intermediate options on the parser
 as well as good practices.
 
@@ -77,10 +77,10 @@ fn main() {
     match handle_args(&mut parser, &mut opts) {
         Ok(_) => {}
         Err(e) => {
-            println!("");
-            println!("ERROR: {}", e);
-            println!("");
-            parser.print_help();
+            eprintln!();
+            eprintln!("ERROR: {}", e);
+            eprintln!();
+            parser.print_help_err();
             std::process::exit(1);
         }
     }
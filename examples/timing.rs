@@ -0,0 +1,93 @@
+extern crate rags_rs as rags;
+
+use std::time::{Duration, Instant};
+
+// Times parsing of command lines shaped like those of a larger tool, for comparing
+// changes to how args are matched. Run in release mode for meaningful numbers:
+//
+//     cargo run --release --example timing
+
+static LONGS: [&str; 40] = [
+    "config", "output", "input", "format", "level", "threads", "jobs", "target",
+    "profile", "features", "manifest", "package", "workspace", "exclude", "include", "color",
+    "message", "timings", "offline", "frozen", "locked", "registry", "index", "token",
+    "proxy", "timeout", "retries", "cache", "prefix", "root", "bin", "example",
+    "test", "bench", "lib", "edition", "toolchain", "linker", "runner", "env",
+];
+
+const ROUNDS: usize = 10;
+
+// best of the rounds, each parsing `iterations` times
+fn best_of<F: Fn()>(iterations: usize, parse: F) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..iterations {
+                parse();
+            }
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+// every long option given a value, a run of short flags, and trailing positionals
+fn options() -> Duration {
+    let mut args = vec!("app".to_string(), "-abc".to_string());
+    for long in LONGS.iter() {
+        args.push(format!("--{}", long));
+        args.push(format!("{}-value", long));
+    }
+    for i in 0..20 {
+        args.push(format!("file{}.txt", i));
+    }
+
+    best_of(20000, || {
+        let mut values: Vec<String> = vec!(String::new(); LONGS.len());
+        let mut flags = [false; 3];
+        let mut files: Vec<String> = vec!();
+
+        let mut parser = rags::Parser::from_strings(args.clone());
+        for (long, value) in LONGS.iter().zip(values.iter_mut()) {
+            parser.long_arg(long, "an option", value, None, false).expect("bad arg");
+        }
+        for (short, flag) in ['a', 'b', 'c'].iter().zip(flags.iter_mut()) {
+            parser.flag(*short, "", "a flag", flag, false).expect("bad flag");
+        }
+        parser.positional_list("files", "input files", &mut files, false)
+            .expect("bad positional list");
+        assert_eq!(files.len(), 20);
+    })
+}
+
+// a tool with many subcommands, one of which is given along with many inputs sharing a
+// prefix with the subcommand names
+fn subcommands() -> Duration {
+    let names: Vec<&'static str> = (0..200)
+        .map(|i| &*Box::leak(format!("command{}", i).into_boxed_str()))
+        .collect();
+    let mut args = vec!("app".to_string(), "command150".to_string());
+    for i in 0..300 {
+        args.push(format!("command-input{}", i));
+    }
+
+    best_of(5000, || {
+        let mut subs: Vec<String> = vec!();
+        let mut inputs: Vec<String> = vec!();
+
+        let mut parser = rags::Parser::from_strings(args.clone());
+        for name in names.iter() {
+            parser.subcommand(name, "a subcommand", &mut subs, None).expect("bad subcommand")
+                .positional_list("inputs", "inputs", &mut inputs, false)
+                    .expect("bad positional list")
+                .done().expect("failed to close subcommand");
+        }
+        assert_eq!(subs, vec!("command150"));
+        assert_eq!(inputs.len(), 300);
+    })
+}
+
+fn main() {
+    println!("options: {:?}", options());
+    println!("subcommands: {:?}", subcommands());
+}
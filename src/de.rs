@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+
+use serde::de::{self, Deserializer, IntoDeserializer, Visitor};
+use serde::de::value::{Error, MapDeserializer, SeqDeserializer};
+use serde::forward_to_deserialize_any;
+
+/// Deserializes the target from the options matched by a parse, keyed as in
+/// [Parser::matched_options](../struct.Parser.html#method.matched_options).
+pub(crate) fn from_matched<T: de::DeserializeOwned>(matched: BTreeMap<String, Vec<String>>)
+    -> Result<T, Error>
+{
+    let map = MapDeserializer::new(matched.into_iter().map(|(k, v)| (k, Values(v))));
+    T::deserialize(map)
+}
+
+// all raw values captured for a single option
+struct Values(Vec<String>);
+
+impl Values {
+    // the value an option deserializes to when a single value is wanted (last given wins)
+    fn last(self) -> Result<Raw, Error> {
+        match self.0.into_iter().last() {
+            Some(v) => { Ok(Raw(v)) }
+            None => { Err(de::Error::custom("flag given where a value was expected")) }
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Values {
+    type Deserializer = Self;
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! delegate_last {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                self.last()?.$method(visitor)
+            }
+        )*
+    }
+}
+
+impl<'de> Deserializer<'de> for Values {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0.len() {
+            0 => { visitor.visit_bool(true) }
+            1 => { self.last()?.deserialize_any(visitor) }
+            _ => { self.deserialize_seq(visitor) }
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        // flags are matched without a value
+        if self.0.is_empty() {
+            return visitor.visit_bool(true);
+        }
+        self.last()?.deserialize_bool(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(SeqDeserializer::new(self.0.into_iter().map(Raw)))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _: &'static str, visitor: V)
+        -> Result<V::Value, Error>
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self,
+        name: &'static str, variants: &'static [&'static str], visitor: V
+    ) -> Result<V::Value, Error> {
+        self.last()?.deserialize_enum(name, variants, visitor)
+    }
+
+    delegate_last!{
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64 deserialize_char deserialize_str deserialize_string
+    }
+
+    forward_to_deserialize_any!{
+        bytes byte_buf unit unit_struct tuple_struct map struct identifier ignored_any
+    }
+}
+
+// a single raw value, parsed into whatever type the target asks for
+struct Raw(String);
+
+impl<'de> IntoDeserializer<'de, Error> for Raw {
+    type Deserializer = Self;
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! parse_raw {
+    ($($method:ident => $visit:ident),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.0.parse() {
+                    Ok(v) => { visitor.$visit(v) }
+                    Err(e) => {
+                        Err(de::Error::custom(format!("invalid value '{}': {}", self.0, e)))
+                    }
+                }
+            }
+        )*
+    }
+}
+
+impl<'de> Deserializer<'de> for Raw {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.0)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _: &'static str, visitor: V)
+        -> Result<V::Value, Error>
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self,
+        _: &'static str, _: &'static [&'static str], visitor: V
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    parse_raw!{
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8, deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32, deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8, deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32, deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32, deserialize_f64 => visit_f64,
+        deserialize_char => visit_char
+    }
+
+    forward_to_deserialize_any!{
+        str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
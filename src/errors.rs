@@ -1,27 +1,171 @@
-use std::error::Error as ErrorImpl;
-
 use crate::printer::arg_string;
 
+/// Boxed error kept as the `source` of a failed construction by the typed builders
+/// (e.g. [Parser::arg_typed](struct.Parser.html#method.arg_typed)), as produced by the
+/// `FromStr` implementation of the target type.
+pub type BoxedError = Box<dyn std::error::Error + Send + Sync>;
+
+// why a value failed to construct: only a message, or the typed error kept as a source
+pub(crate) enum Failure {
+    Message(String),
+    Typed(BoxedError),
+}
+
+impl Failure {
+    pub(crate) fn message<E: std::fmt::Display>(e: E) -> Failure {
+        Failure::Message(e.to_string())
+    }
+
+    pub(crate) fn typed<E: Into<BoxedError>>(e: E) -> Failure {
+        Failure::Typed(e.into())
+    }
+
+    // builds the plain error from the message, wrapping it along with the source if typed
+    fn wrap<F: FnOnce(String) -> Error>(self, plain: F) -> Error {
+        match self {
+            Failure::Message(msg) => { plain(msg) }
+            Failure::Typed(e) => {
+                Error::WithSource(Box::new(plain(e.to_string())), e)
+            }
+        }
+    }
+}
+
 pub enum Error {
     InvalidState(&'static str),
     InvalidInput(char, &'static str, &'static str),
     MissingArgValue(char, &'static str),
-    ConstructionError(char, &'static str, String), // TODO: would be nice to keep the typed-error
-    PositionalConstructionError(&'static str, String), // TODO: would be nice to keep the original
-    SubConstructionError(&'static str, String), // TODO: would be nice to keep the typed-error
+    ConstructionError(char, &'static str, String), // short, long, message
+    PositionalConstructionError(&'static str, String), // name, message
+    InvalidPositionalChoice(&'static str, String, &'static [&'static str]), // name, given, choices
+    InvalidChoice(char, &'static str, String, &'static [&'static str]), // short, long, given, choices
+    SubConstructionError(&'static str, String), // name, message
     ValuedArgInRun(char, String), // offending short, run it was contained in
+    WrongArity(&'static str, usize, usize), // arg, expected count, given count
+    ValidationFailed(char, &'static str, String, String), // short, long, value, reason
+    OutOfRange(char, &'static str, String), // short, long, description
 
     NestedGroup(&'static str, &'static str), // existing, attempted
     PrinterMissingGroup(&'static str),
 
     MissingArgument(String),
     MissingPositional(String),
+    MissingArguments(Vec<String>),
+    MissingSubcommand(Vec<&'static str>), // available subcommands
+    IncompleteGroup(Vec<String>), // members not given
+    MutuallyExclusive(Vec<&'static str>), // members given
+    MissingOneOf(Vec<&'static str>), // members, of which none were given
+    UnexpectedArgument(String, Option<String>), // given, closest declared
+    AmbiguousLong(String, Vec<&'static str>), // given, longs it abbreviates
+    RepeatedValue(String, &'static str), // option given, repeated subcommand it was given within
+    PathNotFound(String),
+    ConfigFile(String, String), // path, reason
+    ResponseFile(String, String), // path, reason
+    Deserialize(String), // reason
     MultipleVariadic(&'static str),
     UnorderedPositionals(&'static str),
+    InvalidTree(Vec<String>), // every structural problem found
+
+    WithSource(Box<Error>, BoxedError), // construction error, the typed error causing it
 }
 
 impl std::error::Error for Error {
     fn description(&self) -> &str {
+        self.summary()
+    }
+
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::WithSource(_, e) => {
+                Some(e.as_ref())
+            }
+            _ => { None }
+        }
+    }
+}
+
+impl Error {
+    // construction failure for an arg, wrapped with its source when it is typed
+    pub(crate) fn construction(short: char, long: &'static str, f: Failure) -> Error {
+        f.wrap(|msg| Error::ConstructionError(short, long, msg))
+    }
+
+    // construction failure for a positional, wrapped with its source when it is typed
+    pub(crate) fn positional_construction(name: &'static str, f: Failure) -> Error {
+        f.wrap(|msg| Error::PositionalConstructionError(name, msg))
+    }
+
+    // construction failure for a subcommand, wrapped with its source when it is typed
+    pub(crate) fn sub_construction(name: &'static str, f: Failure) -> Error {
+        f.wrap(|msg| Error::SubConstructionError(name, msg))
+    }
+
+    /// The error a [Error::WithSource](#variant.WithSource) wraps, or the error itself
+    /// otherwise. This allows matching on the kind of error regardless of whether the
+    /// typed source was kept.
+    pub fn inner(&self) -> &Error {
+        match self {
+            Error::WithSource(e, _) => { e.inner() }
+            _ => { self }
+        }
+    }
+
+    /// Suggested process exit code for the error, allowing `main` to consistently
+    /// `std::process::exit(e.exit_code())`. The mapping is:
+    ///
+    /// * `2` for usage errors, where the arguments given do not fit the declared
+    ///   interface (e.g. a missing required arg, a malformed value, or an invalid choice),
+    ///   following the convention of `getopt`-based tools.
+    /// * `1` for all other errors, such as a value which could not be constructed or a
+    ///   path which does not exist, as well as errors in how the parser was set up.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::InvalidInput(_, _, _) |
+            Error::MissingArgValue(_, _) |
+            Error::InvalidPositionalChoice(_, _, _) |
+            Error::InvalidChoice(_, _, _, _) |
+            Error::ValuedArgInRun(_, _) |
+            Error::WrongArity(_, _, _) |
+            Error::ValidationFailed(_, _, _, _) |
+            Error::OutOfRange(_, _, _) |
+            Error::MissingArgument(_) |
+            Error::MissingPositional(_) |
+            Error::MissingArguments(_) |
+            Error::MissingSubcommand(_) |
+            Error::IncompleteGroup(_) |
+            Error::MutuallyExclusive(_) |
+            Error::MissingOneOf(_) |
+            Error::UnexpectedArgument(_, _) |
+            Error::AmbiguousLong(_, _) |
+            Error::RepeatedValue(_, _) => {
+                2
+            }
+
+            Error::InvalidState(_) |
+            Error::ConstructionError(_, _, _) |
+            Error::PositionalConstructionError(_, _) |
+            Error::SubConstructionError(_, _) |
+            Error::NestedGroup(_, _) |
+            Error::PrinterMissingGroup(_) |
+            Error::PathNotFound(_) |
+            Error::ConfigFile(_, _) |
+            Error::ResponseFile(_, _) |
+            Error::Deserialize(_) |
+            Error::MultipleVariadic(_) |
+            Error::UnorderedPositionals(_) |
+            Error::InvalidTree(_) => {
+                1
+            }
+
+            Error::WithSource(e, _) => {
+                e.exit_code()
+            }
+        }
+    }
+
+    /// Short, static summary of the error kind. Used as the prefix of the
+    /// `Display` output.
+    fn summary(&self) -> &'static str {
         match self {
             Error::InvalidState(_) => {
                 "invalid parser state"
@@ -38,12 +182,27 @@ impl std::error::Error for Error {
             Error::PositionalConstructionError(_, _) => {
                 "failed to construct positional target from string"
             }
+            Error::InvalidPositionalChoice(_, _, _) => {
+                "positional value is not one of the allowed choices"
+            }
+            Error::InvalidChoice(_, _, _, _) => {
+                "value is not one of the allowed choices"
+            }
             Error::SubConstructionError(_, _) => {
                 "failed to construct subcommand from string"
             }
             Error::ValuedArgInRun(_, _) => {
                 "short-code runs only support valued-args as the last character in the run"
             }
+            Error::WrongArity(_, _, _) => {
+                "wrong number of values"
+            }
+            Error::ValidationFailed(_, _, _, _) => {
+                "value failed validation"
+            }
+            Error::OutOfRange(_, _, _) => {
+                "value is out of range"
+            }
 
             Error::NestedGroup(_, _) => {
                 "groups cannot be nested"
@@ -59,12 +218,55 @@ impl std::error::Error for Error {
             Error::MissingPositional(_) => {
                 "required positional was not given"
             }
+            Error::MissingArguments(_) => {
+                "required arguments were not given"
+            }
+            Error::MissingSubcommand(_) => {
+                "a subcommand is required"
+            }
+            Error::IncompleteGroup(_) => {
+                "options must be given together"
+            }
+            Error::MutuallyExclusive(_) => {
+                "options cannot be given together"
+            }
+            Error::MissingOneOf(_) => {
+                "one of the options is required"
+            }
+            Error::UnexpectedArgument(_, _) => {
+                "unexpected argument"
+            }
+            Error::AmbiguousLong(_, _) => {
+                "ambiguous abbreviation"
+            }
+            Error::RepeatedValue(_, _) => {
+                "option takes a single value across repeated subcommands"
+            }
+            Error::PathNotFound(_) => {
+                "path does not exist"
+            }
+            Error::ConfigFile(_, _) => {
+                "could not load config file"
+            }
+            Error::ResponseFile(_, _) => {
+                "could not expand response file"
+            }
+            Error::Deserialize(_) => {
+                "could not deserialize arguments"
+            }
             Error::MultipleVariadic(_) => {
                 "second declared variadic positional has no effect"
             }
             Error::UnorderedPositionals(_) => {
                 "declaring a positional after a variadic positional has no effect"
             }
+            Error::InvalidTree(_) => {
+                "invalid declarations"
+            }
+
+            Error::WithSource(e, _) => {
+                e.summary()
+            }
         }
     }
 }
@@ -73,47 +275,114 @@ impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Error::InvalidState(desc) => {
-                write!(f, "{}: {}", self.description(), desc)
+                write!(f, "{}: {}", self.summary(), desc)
             }
             Error::InvalidInput(short, long, desc) => {
-                write!(f, "{}: {} {}", self.description(), arg_string(*short, long, false), desc)
+                write!(f, "{}: {} {}", self.summary(), arg_string(*short, long, false), desc)
             }
             Error::MissingArgValue(short, long) => {
-                write!(f, "{} for {}", self.description(), arg_string(*short, long, false))
+                write!(f, "{} for {}", self.summary(), arg_string(*short, long, false))
             }
             Error::ConstructionError(short, long, err) => {
-                write!(f, "{} for {}: {}", self.description(),
+                write!(f, "{} for {}: {}", self.summary(),
                     arg_string(*short, long, false), err)
             }
             Error::PositionalConstructionError(name, err) => {
-                write!(f, "{} for {}: {}", self.description(), name, err)
+                write!(f, "{} for {}: {}", self.summary(), name, err)
+            }
+            Error::InvalidPositionalChoice(name, given, choices) => {
+                write!(f, "{} for {}: got '{}', expected one of {}", self.summary(),
+                    name, given, choices.join("|"))
+            }
+            Error::InvalidChoice(short, long, given, choices) => {
+                write!(f, "{} for {}: got '{}', expected one of {}", self.summary(),
+                    arg_string(*short, long, false), given, choices.join("|"))
             }
             Error::SubConstructionError(name, err) => {
-                write!(f, "{} for {}: {}", self.description(), name, err)
+                write!(f, "{} for {}: {}", self.summary(), name, err)
             }
             Error::ValuedArgInRun(short, run) => {
-                write!(f, "{}: {} is within {}", self.description(), short, run)
+                write!(f, "{}: {} is within {}", self.summary(), short, run)
+            }
+            Error::WrongArity(name, expected, given) => {
+                write!(f, "{} for {}: expected {}, got {}", self.summary(), name, expected, given)
+            }
+            Error::ValidationFailed(short, long, value, reason) => {
+                write!(f, "{} for {}: '{}' {}", self.summary(),
+                    arg_string(*short, long, false), value, reason)
+            }
+            Error::OutOfRange(short, long, desc) => {
+                write!(f, "{} for {}: {}", self.summary(), arg_string(*short, long, false), desc)
             }
 
 
             Error::NestedGroup(orig, attempt) => {
-                write!(f, "{} ({} within {})", self.description(), attempt, orig)
+                write!(f, "{} ({} within {})", self.summary(), attempt, orig)
             }
             Error::PrinterMissingGroup(name) => {
-                write!(f, "{}: {}", self.description(), name)
+                write!(f, "{}: {}", self.summary(), name)
             }
 
             Error::MissingArgument(a) => {
-                write!(f, "{}: {}", self.description(), a)
+                write!(f, "{}: {}", self.summary(), a)
             }
             Error::MissingPositional(a) => {
-                write!(f, "{}: {}", self.description(), a)
+                write!(f, "{}: {}", self.summary(), a)
+            }
+            Error::MissingArguments(names) => {
+                write!(f, "{}: {}", self.summary(), names.join(", "))
+            }
+            Error::MissingSubcommand(subs) => {
+                write!(f, "{}: expected one of {}", self.summary(), subs.join(", "))
+            }
+            Error::IncompleteGroup(missing) => {
+                write!(f, "{}, missing: {}", self.summary(), missing.join(", "))
+            }
+            Error::MutuallyExclusive(given) => {
+                write!(f, "{}: {}", self.summary(),
+                    given.iter().map(|g| format!("--{}", g)).collect::<Vec<String>>().join(", "))
+            }
+            Error::MissingOneOf(members) => {
+                write!(f, "{}: expected one of {}", self.summary(),
+                    members.iter().map(|m| format!("--{}", m)).collect::<Vec<String>>().join(", "))
+            }
+            Error::UnexpectedArgument(arg, None) => {
+                write!(f, "{}: {}", self.summary(), arg)
+            }
+            Error::UnexpectedArgument(arg, Some(suggestion)) => {
+                write!(f, "{}: {}; did you mean {}?", self.summary(), arg, suggestion)
+            }
+            Error::AmbiguousLong(token, longs) => {
+                let longs: Vec<String> = longs.iter().map(|l| format!("--{}", l)).collect();
+                write!(f, "{}: --{} could be {}", self.summary(), token, longs.join(", "))
+            }
+            Error::RepeatedValue(arg, sub) => {
+                write!(f, "{}: {} was given more than once within {}", self.summary(), arg, sub)
+            }
+            Error::PathNotFound(p) => {
+                write!(f, "{}: {}", self.summary(), p)
+            }
+            Error::ConfigFile(path, reason) => {
+                write!(f, "{} {}: {}", self.summary(), path, reason)
+            }
+            Error::ResponseFile(path, reason) => {
+                write!(f, "{} @{}: {}", self.summary(), path, reason)
+            }
+            Error::Deserialize(reason) => {
+                write!(f, "{}: {}", self.summary(), reason)
             }
             Error::MultipleVariadic(p) => {
-                write!(f, "{}: {}", self.description(), p)
+                write!(f, "{}: {}", self.summary(), p)
             }
             Error::UnorderedPositionals(p) => {
-                write!(f, "{}: {}", self.description(), p)
+                write!(f, "{}: {}", self.summary(), p)
+            }
+            Error::InvalidTree(problems) => {
+                write!(f, "{}: {}", self.summary(), problems.join("; "))
+            }
+
+            Error::WithSource(e, _) => {
+                write!(f, "{}", e)
             }
         }
     }
@@ -25,6 +25,20 @@
 //! 2. positional arguments should be defined within a subcommand scope even if shared
 //!    betwwen subcommands
 //!
+//! Matching is deterministic. Each declaration consumes the left-most unconsumed arg
+//! it matches, and declarations are matched in the order they are made, so an arg
+//! string is always claimed by the first declaration in scope that matches it. When
+//! deciding whether a declaration matches, exact long names (`--verbose`) and exact
+//! short codes (`-v`, or a `v` within a run such as `-xvz`) are the only forms
+//! considered; a long name never matches a longer or shorter arg string
+//! (`--verb` does not match `--verbose`, nor does `--verbose-level`) unless
+//! abbreviations are enabled with
+//! [Parser::allow_abbreviations](struct.Parser.html#method.allow_abbreviations).
+//!
+//! Within a run of short codes, only the last code may take a value. The value is
+//! either the next arg (`-xvf foo.txt`) or a number ending the run, which always
+//! belongs to the last short code in the run (`-vvn5` gives `n` the value `5`).
+//!
 //!
 //!
 //! # Example Usage
@@ -175,10 +189,12 @@
 //!
 //! ```
 
+use std::borrow::Cow;
 use std::env;
+use std::io::{IsTerminal, Write};
 use std::str::FromStr;
 use std::string::ToString;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 extern crate bit_set;
 
@@ -188,12 +204,27 @@ pub use errors::*;
 mod printer;
 use printer::arg_string;
 
+#[cfg(feature = "serde")]
+mod de;
+pub use printer::OptionSort;
+pub use printer::Completion;
+pub use printer::ColorMode;
+pub use printer::Accessory;
+pub use printer::RequiredMarker;
+pub use printer::{HelpModel, HelpOption, HelpGroup, HelpPositional};
+
 type MatchResult = Result<Option<FoundMatch>, Error>;
+type EnvSource = Box<dyn Fn(&str) -> Option<String>>;
+type UnknownHandler = Box<dyn FnMut(&Unused) -> UnknownAction>;
+type HelpRenderer = Box<dyn Fn(&HelpModel)>;
 
 #[cfg(test)] mod test_args;
+#[cfg(test)] mod test_errors;
 #[cfg(test)] mod test_flags;
+#[cfg(test)] mod test_help;
 #[cfg(test)] mod test_count;
 #[cfg(test)] mod test_lists;
+#[cfg(test)] mod test_matched;
 #[cfg(test)] mod test_positionals;
 #[cfg(test)] mod test_subcmds;
 #[cfg(test)] mod test_unused;
@@ -240,6 +271,7 @@ enum ItemType {
 enum ValueLocation {
     Unknown,
     HasEqual(usize),
+    Attached(usize), // offset the value starts at (e.g. `-ffoo`)
     TakesNext,
 }
 
@@ -260,6 +292,339 @@ impl FoundMatch {
     }
 }
 
+/// MatchRecord retains the raw values captured for a matched option so the
+/// result of a parse can be inspected after the fact.
+struct MatchRecord<'args> {
+    short: char,
+    long: &'static str,
+    values: Vec<Cow<'args, str>>,
+}
+impl MatchRecord<'_> {
+    fn key(&self) -> String {
+        if self.long.is_empty() {
+            self.short.to_string()
+        } else {
+            self.long.to_string()
+        }
+    }
+
+    // whether the record is of the option with the given codes. as with the key, options
+    // are told apart by long name when they have one, as the short code may have been
+    // assigned automatically or differ between scopes declaring the same option
+    fn is(&self, short: char, long: &'static str) -> bool {
+        if long.is_empty() {
+            self.long.is_empty() && (self.short == short)
+        } else {
+            self.long == long
+        }
+    }
+}
+
+/// Declared is an option, positional, or subcommand as seen while walking the
+/// declarations, kept regardless of whether the scope it was declared in is parsed.
+enum Declared {
+    Option(usize, char, &'static str, &'static str, Takes, bool), // scope, short, long, desc, value, hidden
+    Positional(usize, &'static str, bool, bool), // scope, name, required, variadic
+    Subcommand(usize, usize, &'static str, &'static str), // parent scope, scope, name, desc
+}
+
+/// Takes describes what, if anything, a declared option expects as its value.
+#[derive(Clone, Copy)]
+enum Takes {
+    Nothing,
+    Value,
+    Completed(Completion),
+}
+
+/// Tree records the shape of the declarations (scopes and what was declared in each)
+/// so structural mistakes can be reported by
+/// [Parser::validate_tree](struct.Parser.html#method.validate_tree).
+struct Tree {
+    declared: Vec<Declared>,
+    scopes: Vec<String>, // name of each scope, indexed by scope id
+    open: Vec<usize>, // stack of scope ids currently being walked
+    builtins: usize, // leading declarations made by the parser itself (help, version)
+}
+impl Tree {
+    fn new() -> Tree {
+        Tree{
+            declared: vec!(),
+            scopes: vec!("top-level".to_string()),
+            open: vec!(0),
+            builtins: 0,
+        }
+    }
+
+    // whether anything beyond the built-in flags has been declared
+    fn has_user_declarations(&self) -> bool {
+        self.declared.len() > self.builtins
+    }
+
+    // whether the built-in flags are still being declared
+    fn declaring_builtins(&self) -> bool {
+        self.builtins == usize::MAX
+    }
+
+    fn current(&self) -> usize {
+        *self.open.last().unwrap_or(&0)
+    }
+
+    fn enter(&mut self, name: &'static str, desc: &'static str) {
+        let parent = self.current();
+        let path = match parent {
+            0 => { name.to_string() }
+            parent => { format!("{} {}", self.scopes[parent], name) }
+        };
+        self.scopes.push(path);
+        self.open.push(self.scopes.len() - 1);
+        self.declared.push(Declared::Subcommand(parent, self.current(), name, desc));
+    }
+
+    fn leave(&mut self) {
+        if self.open.len() > 1 {
+            self.open.pop();
+        }
+    }
+
+    fn scope_name(&self) -> &str {
+        &self.scopes[self.current()]
+    }
+
+    // the top-level option sharing a code with the given one, when declared in a subcommand
+    fn shadowed(&self, short: char, long: &'static str) -> Option<(char, &'static str)> {
+        if self.current() == 0 {
+            return None;
+        }
+        self.declared.iter().find_map(|d| match d {
+            Declared::Option(0, s, l, _, _, _)
+                if ((short != '\0') && (*s == short)) || (!long.is_empty() && (*l == long)) => {
+                Some((*s, *l))
+            }
+            _ => { None }
+        })
+    }
+
+    // the scope reached by following the given subcommand names down from the top-level
+    fn scope_of(&self, path: &[&'static str]) -> Option<usize> {
+        path.iter().try_fold(0, |scope, name| {
+            self.declared.iter().find_map(|d| match d {
+                Declared::Subcommand(parent, child, n, _) if (*parent == scope) && (n == name) => {
+                    Some(*child)
+                }
+                _ => { None }
+            })
+        })
+    }
+
+    // names of the subcommands declared directly within the scope
+    fn subcommands(&self, scope: usize) -> Vec<&'static str> {
+        self.declared.iter()
+            .filter_map(|d| match d {
+                Declared::Subcommand(parent, _, name, _) if *parent == scope => { Some(*name) }
+                _ => { None }
+            })
+            .collect()
+    }
+
+    fn option(&mut self, short: char, long: &'static str, desc: &'static str, takes: Takes) {
+        let scope = self.current();
+        self.declared.push(Declared::Option(scope, short, long, desc, takes, false));
+    }
+
+    // marks the option declared last as hidden. returns false, changing nothing, when
+    // the last declaration was not an option
+    fn hide_last(&mut self) -> bool {
+        match self.declared.last_mut() {
+            Some(Declared::Option(_, _, _, _, _, hidden)) => {
+                *hidden = true;
+                true
+            }
+            _ => { false }
+        }
+    }
+
+    // the long names declared so far which begin with the given token, in declaration
+    // order, considering only the scopes currently being walked. the built-in flags are
+    // matched before abbreviations apply, so they never make a prefix ambiguous
+    fn longs_starting_with(&self, token: &str) -> Vec<&'static str> {
+        let mut result: Vec<&'static str> = vec!();
+        for decl in self.declared.iter().skip(self.builtins) {
+            if let Declared::Option(scope, _, long, _, _, hidden) = decl {
+                if !hidden && self.open.contains(scope) && long.starts_with(token)
+                    && !result.contains(long) {
+                    result.push(*long);
+                }
+            }
+        }
+        result
+    }
+
+    fn positional(&mut self, name: &'static str, required: bool, variadic: bool) {
+        let scope = self.current();
+        self.declared.push(Declared::Positional(scope, name, required, variadic));
+    }
+
+    // describes every structural problem found, in declaration order
+    fn problems(&self) -> Vec<String> {
+        let mut result = vec!();
+        for (scope, scope_name) in self.scopes.iter().enumerate() {
+            let mut shorts = BTreeSet::new();
+            let mut longs = BTreeSet::new();
+            let mut variadic: Option<&'static str> = None;
+            let mut optional: Option<&'static str> = None;
+
+            for decl in self.declared.iter() {
+                match decl {
+                    Declared::Option(s, short, long, _, _, _) if *s == scope => {
+                        if (*short != '\0') && !shorts.insert(*short) {
+                            result.push(format!("{}: duplicate short code -{}",
+                                scope_name, short));
+                        }
+                        if !long.is_empty() && !longs.insert(*long) {
+                            result.push(format!("{}: duplicate long code --{}",
+                                scope_name, long));
+                        }
+                    }
+                    Declared::Positional(s, name, required, is_variadic) if *s == scope => {
+                        if let Some(v) = variadic {
+                            result.push(format!("{}: positional {} declared after variadic {}",
+                                scope_name, name, v));
+                        }
+                        if let (true, Some(o)) = (*required, optional) {
+                            result.push(format!(
+                                "{}: required positional {} declared after optional {}",
+                                scope_name, name, o));
+                        }
+                        if *is_variadic {
+                            variadic = variadic.or(Some(*name));
+                        }
+                        if !*required {
+                            optional = optional.or(Some(*name));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        for scope in self.open.iter().skip(1) {
+            result.push(format!("{}: subcommand scope not closed with done()",
+                self.scopes[*scope]));
+        }
+        result
+    }
+
+    // renders the zsh completion function `func` for the scope, followed by the
+    // functions of every subcommand declared within it
+    fn zsh_function(&self, scope: usize, func: &str, out: &mut String) {
+        let mut specs = vec!();
+        let mut positionals = vec!();
+        let mut subs = vec!();
+        for decl in self.declared.iter() {
+            match decl {
+                Declared::Option(s, short, long, desc, takes, false) if *s == scope => {
+                    specs.extend(zsh_option(*short, long, desc, *takes));
+                }
+                Declared::Positional(s, name, required, variadic) if *s == scope => {
+                    let kind = match (*variadic, *required) {
+                        (true, _) => { "*" }
+                        (false, true) => { "" }
+                        (false, false) => { ":" }
+                    };
+                    positionals.push(zsh_quote(
+                        &format!("{}:{}:_default", kind, name.replace(':', "\\:"))));
+                }
+                Declared::Subcommand(parent, child, name, desc) if *parent == scope => {
+                    subs.push((*child, *name, *desc));
+                }
+                _ => {}
+            }
+        }
+
+        out.push_str(&format!("{}() {{\n", func));
+        if subs.is_empty() {
+            specs.extend(positionals);
+            out.push_str("    _arguments -s -S");
+        } else {
+            specs.push(zsh_quote("1: :->command"));
+            specs.push(zsh_quote("*:: :->args"));
+            out.push_str("    local curcontext=\"$curcontext\" state line\n");
+            out.push_str("    typeset -A opt_args\n\n");
+            out.push_str("    _arguments -C -s -S");
+        }
+        for spec in specs.iter() {
+            out.push_str(&format!(" \\\n        {}", spec));
+        }
+        out.push('\n');
+
+        if !subs.is_empty() {
+            out.push_str("\n    case $state in\n");
+            out.push_str("        command)\n");
+            out.push_str("            local -a commands\n");
+            out.push_str("            commands=(\n");
+            for (_, name, desc) in subs.iter() {
+                out.push_str(&format!("                {}\n",
+                    zsh_quote(&format!("{}:{}", name.replace(':', "\\:"), desc))));
+            }
+            out.push_str("            )\n");
+            out.push_str("            _describe -t commands 'command' commands\n");
+            out.push_str("            ;;\n");
+            out.push_str("        args)\n");
+            out.push_str("            case $line[1] in\n");
+            for (_, name, _) in subs.iter() {
+                out.push_str(&format!("                {}) {}_{} ;;\n",
+                    zsh_quote(name), func, zsh_ident(name)));
+            }
+            out.push_str("            esac\n");
+            out.push_str("            ;;\n");
+            out.push_str("    esac\n");
+        }
+        out.push_str("}\n");
+
+        for (child, name, _) in subs.iter() {
+            out.push('\n');
+            self.zsh_function(*child, &format!("{}_{}", func, zsh_ident(name)), out);
+        }
+    }
+}
+
+// single-quotes a word for zsh, escaping any quotes within it
+fn zsh_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+// replaces anything that cannot be part of a zsh function name
+fn zsh_ident(s: &str) -> String {
+    s.chars().map(|c| if c.is_ascii_alphanumeric() || (c == '-') { c } else { '_' }).collect()
+}
+
+// the `_arguments` specs for an option, e.g. `'(-f --file)'{-f,--file=}'[a file]:file:_files'`,
+// where options taking a value prompt for it
+fn zsh_option(short: char, long: &str, desc: &str, takes: Takes) -> Option<String> {
+    let message = if long.is_empty() { "value" } else { long };
+    let value = match takes {
+        Takes::Nothing => { "".to_string() }
+        Takes::Value => { format!(":{}: ", message) }
+        Takes::Completed(c) => { format!(":{}:{}", message, c.zsh_action()) }
+    };
+    let long_code = match takes {
+        Takes::Nothing => { format!("--{}", long) }
+        _ => { format!("--{}=", long) }
+    };
+    let tail = zsh_quote(&format!("[{}]{}",
+        desc.replace('\\', "\\\\").replace('[', "\\[").replace(']', "\\]"), value));
+
+    match (short != '\0', !long.is_empty()) {
+        (true, true) => {
+            Some(format!("{}{{-{},{}}}{}",
+                zsh_quote(&format!("(-{} --{})", short, long)), short, long_code, tail))
+        }
+        (true, false) => { Some(format!("-{}{}", short, tail)) }
+        (false, true) => { Some(format!("{}{}", long_code, tail)) }
+        (false, false) => { None }
+    }
+}
+
 
 /// Defines the types of arguments we can handle, and when matched, our best
 /// guess as to what kind of arg that is until we can verify with more context.
@@ -269,6 +634,25 @@ pub enum LooksLike {
     LongArg,
     Positional,
 }
+impl LooksLike {
+    // classifies an arg by its leading dashes alone
+    fn of(arg: &str) -> LooksLike {
+        let mut chars = arg.chars();
+        let arg_0 = chars.next().unwrap_or('\0');
+        let arg_1 = chars.next().unwrap_or('\0');
+
+        // by convention a lone dash means stdin, so treat it as a positional
+        if arg == "-" {
+            LooksLike::Positional
+        } else if (arg_0 == '-') && (arg_1 == '-') {
+            LooksLike::LongArg
+        } else if (arg_0 == '-') && (arg_1 != '-') {
+            LooksLike::ShortArg
+        } else {
+            LooksLike::Positional
+        }
+    }
+}
 impl std::fmt::Display for LooksLike {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
@@ -285,6 +669,54 @@ impl std::fmt::Display for LooksLike {
     }
 }
 
+/// UnknownAction is returned by the handler given to
+/// [Parser::on_unknown](struct.Parser.html#method.on_unknown) to decide what becomes
+/// of an argument which was not matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownAction {
+    /// Leave the argument as is, so it is still reported by
+    /// [Parser::unused](struct.Parser.html#method.unused).
+    Ignore,
+    /// Fail with [Error::UnexpectedArgument](enum.Error.html#variant.UnexpectedArgument).
+    Error,
+    /// Mark the argument as used, so it is no longer reported as unused.
+    Consume,
+}
+
+/// ConfigFormat is the format of a config file loaded by
+/// [Parser::config_from_env](struct.Parser.html#method.config_from_env).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// One `long-name = value` pair per line. Blank lines and lines beginning with `#`
+    /// are ignored, and values may be wrapped in double quotes.
+    KeyValue,
+}
+
+// parses the contents of a config file into values keyed by long name
+fn parse_config(path: &str, contents: &str, format: ConfigFormat)
+    -> Result<BTreeMap<String, String>, Error>
+{
+    let mut result = BTreeMap::new();
+    match format {
+        ConfigFormat::KeyValue => {
+            for (i, line) in contents.lines().enumerate() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+
+                let (key, val) = line.split_once('=').ok_or_else(|| Error::ConfigFile(
+                    path.to_string(), format!("line {}: expected KEY = VALUE", i + 1)
+                ))?;
+                let val = val.trim();
+                let val = val.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(val);
+                result.insert(key.trim().to_string(), val.to_string());
+            }
+        }
+    }
+    Ok(result)
+}
+
 /// Unused carries information about arguments which go unmatched.
 /// Used both in delineating short-code runs as well as passing back
 /// all unmatched arguments to the user (when requested via
@@ -295,20 +727,9 @@ pub struct Unused {
 }
 impl Unused {
     pub fn new(value: String) -> Unused {
-        let arg_0 = value.chars().nth(0).or(Some('\0')).unwrap();
-        let arg_1 = value.chars().nth(1).or(Some('\0')).unwrap();
-
-        let looks_like = if (arg_0 == '-') && (arg_1 == '-') {
-            LooksLike::LongArg
-        } else if (arg_0 == '-') && (arg_1 != '-') {
-            LooksLike::ShortArg
-        } else {
-            LooksLike::Positional
-        };
-
         Unused {
+            looks_like: LooksLike::of(&value),
             arg: value,
-            looks_like: looks_like,
         }
     }
 }
@@ -325,6 +746,207 @@ impl std::fmt::Display for Unused {
     }
 }
 
+/// ConsumedMask is a snapshot of which args have been consumed by the parser,
+/// as returned by [Parser::consumption](struct.Parser.html#method.consumption).
+/// Indices refer to positions in the full argument vector (including `argv[0]`).
+///
+/// The program name (index `0`), the arg-stop sentinel, and any args following it
+/// are never candidates for parsing and are therefore reported as consumed.
+/// An arg containing a run of short codes (e.g. `-abc`) is only consumed once every
+/// code in the run has been matched.
+pub struct ConsumedMask {
+    unconsumed: bit_set::BitSet,
+    len: usize,
+}
+impl ConsumedMask {
+    /// Returns whether the arg at the given index has been consumed.
+    /// Out of range indices are never consumed.
+    pub fn contains(&self, index: usize) -> bool {
+        (index < self.len) && !self.unconsumed.contains(index)
+    }
+
+    /// Iterates the indices of all args which have not been consumed, in order.
+    pub fn iter_unconsumed(&self) -> impl Iterator<Item = usize> + '_ {
+        self.unconsumed.iter()
+    }
+}
+
+// keeps the program name, dropping the `n` args which follow it
+fn skip_leading(mut args: Vec<String>, n: usize) -> Vec<String> {
+    let end = std::cmp::min(args.len(), n.saturating_add(1));
+    if end > 1 {
+        args.drain(1..end);
+    }
+    args
+}
+
+// replaces each `@file` arg with the whitespace-separated args within the file, expanding
+// nested references. args following the arg-stop sentinel are kept as given. `within`
+// holds the files currently being expanded, guarding against a file referencing itself
+fn expand_response_files(args: Vec<String>, within: &mut Vec<std::path::PathBuf>)
+    -> Result<Vec<String>, Error>
+{
+    let mut result = vec!();
+    let mut stopped = false;
+    for arg in args {
+        let path = match arg.strip_prefix('@') {
+            Some(p) if !stopped && !p.is_empty() => { p.to_string() }
+            _ => {
+                stopped = stopped || (arg == "--");
+                result.push(arg);
+                continue;
+            }
+        };
+
+        let canonical = std::fs::canonicalize(&path)
+            .map_err(|e| Error::ResponseFile(path.clone(), format!("{}", e)))?;
+        if within.contains(&canonical) {
+            return Err(Error::ResponseFile(path, "file references itself".to_string()));
+        }
+        let contents = std::fs::read_to_string(&canonical)
+            .map_err(|e| Error::ResponseFile(path.clone(), format!("{}", e)))?;
+
+        within.push(canonical);
+        let nested = expand_response_files(
+            contents.split_whitespace().map(|a| a.to_string()).collect(), within)?;
+        within.pop();
+
+        stopped = nested.iter().any(|a| a == "--");
+        result.extend(nested);
+    }
+    Ok(result)
+}
+
+// the number of single character insertions, deletions, or substitutions needed to
+// turn one string into the other (Levenshtein distance)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut prev = (0..=b.len()).collect::<Vec<usize>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec!(i + 1);
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            curr.push(std::cmp::min(
+                std::cmp::min(prev[j + 1] + 1, curr[j] + 1),
+                prev[j] + cost
+            ));
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+/// Expands the flat `dotted.key=value` pairs collected by
+/// [Parser::dotted_set_arg](struct.Parser.html#method.dotted_set_arg) into nested JSON
+/// objects, splitting each key on `.` (e.g. `server.port=8080` is
+/// `{"server": {"port": "8080"}}`). All values are strings.
+///
+/// When a key is both given a value and used as the prefix of another key (e.g. `server`
+/// and `server.port`), the nested object replaces the value.
+///
+/// Requires the `serde` feature.
+#[cfg(feature = "serde")]
+pub fn expand_dotted(pairs: &BTreeMap<String, String>) -> serde_json::Value {
+    let mut root = serde_json::Map::new();
+    for (key, value) in pairs.iter() {
+        let mut parts = key.split('.').collect::<Vec<&str>>();
+        let leaf = parts.pop().unwrap_or("");
+
+        let mut curr = &mut root;
+        for p in parts {
+            let entry = curr.entry(p.to_string())
+                .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+            if !entry.is_object() {
+                *entry = serde_json::Value::Object(serde_json::Map::new());
+            }
+            curr = match entry {
+                serde_json::Value::Object(m) => { m }
+                _ => { unreachable!("entry was just made an object") }
+            };
+        }
+
+        // an object here means a longer key has already nested under this one
+        if !curr.get(leaf).is_some_and(|v| v.is_object()) {
+            curr.insert(leaf.to_string(), serde_json::Value::String(value.clone()));
+        }
+    }
+    serde_json::Value::Object(root)
+}
+
+// constructs the target with `FromStr`, keeping only the message of a failure
+fn construct<T: FromStr>(value: &str) -> Result<T, Failure>
+    where <T as FromStr>::Err: std::fmt::Display
+{
+    T::from_str(value).map_err(Failure::message)
+}
+
+// constructs the target with `FromStr`, keeping the error of a failure as its source
+fn construct_typed<T: FromStr>(value: &str) -> Result<T, Failure>
+    where <T as FromStr>::Err: Into<BoxedError>
+{
+    T::from_str(value).map_err(Failure::typed)
+}
+
+// whether a raw value exactly matches one of the allowed choices
+fn is_choice(value: &str, choices: &[&str]) -> bool {
+    choices.contains(&value)
+}
+
+// a human readable form of a chrono format string (e.g. `%Y-%m-%d` is `YYYY-MM-DD`)
+#[cfg(feature = "chrono")]
+fn format_hint(format: &str) -> String {
+    format
+        .replace("%Y", "YYYY")
+        .replace("%m", "MM")
+        .replace("%d", "DD")
+        .replace("%H", "hh")
+        .replace("%M", "mm")
+        .replace("%S", "ss")
+}
+
+/// ParseOutcome is a canonical snapshot of a parse, as returned by
+/// [Parser::outcome](struct.Parser.html#method.outcome). It is intended for golden
+/// tests of a command line interface, where the outcome of a parse can be compared
+/// directly with `assert_eq!`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ParseOutcome {
+    /// Matched options, as given by
+    /// [Parser::matched_options](struct.Parser.html#method.matched_options).
+    pub matched: BTreeMap<String, Vec<String>>,
+    /// Names of the matched subcommands, outermost first.
+    pub subcommands: Vec<String>,
+    /// Args left unused, as given by [Parser::unused](struct.Parser.html#method.unused).
+    pub unused: Vec<String>,
+    /// Whether help was requested.
+    pub help: bool,
+    /// Whether the version was requested.
+    pub version: bool,
+}
+
+impl ParseOutcome {
+    /// Reports the options whose matched values differ between this outcome and `other`,
+    /// as `(long, before, after)` ordered by long name. Values of an option are joined
+    /// with `,`, so a matched flag is `Some("")`, while an option which was not matched
+    /// is `None`.
+    ///
+    /// This is intended for testing precedence between sources (e.g. a parse with only
+    /// a config file against one that also has command line args).
+    pub fn diff(&self, other: &ParseOutcome) -> Vec<(String, Option<String>, Option<String>)> {
+        let names = self.matched.keys().chain(other.matched.keys())
+            .collect::<BTreeSet<&String>>();
+
+        names.into_iter()
+            .map(|name| (
+                name.clone(),
+                self.matched.get(name).map(|v| v.join(",")),
+                other.matched.get(name).map(|v| v.join(",")),
+            ))
+            .filter(|(_, before, after)| before != after)
+            .collect()
+    }
+}
+
 /// Parser holds the state required for parsing. The methods provided here
 /// define how arguments should be treated as well as where they are constructed.
 ///
@@ -335,11 +957,14 @@ impl std::fmt::Display for Unused {
 /// as a bitset mapping which args have been matched. Rust does not provide
 /// an `O(1)` access to the args iterator, thus we store it. This also keeps
 /// implementation consistent when using
-/// [Parser::from_strings](#method.from_strings)
+/// [Parser::from_strings](#method.from_strings). Args given to
+/// [Parser::from_slice](#method.from_slice) are borrowed rather than stored,
+/// and the `'args` lifetime ties the parser to them.
 ///
 /// This structure can be dropped after handling of args/help are complete.
-pub struct Parser {
-    args: Vec<String>,
+pub struct Parser<'args> {
+    args: Vec<Cow<'args, str>>,
+    by_value: Option<Vec<usize>>, // arg indices sorted by arg, built on the first subcommand
     mask: bit_set::BitSet,
     run_masks: BTreeMap<usize, bit_set::BitSet>,
 
@@ -348,118 +973,604 @@ pub struct Parser {
     max_depth: usize,
     parse_done: bool,
     curr_group: Option<&'static str>,
+    curr_pos_group: Option<&'static str>,
 
     help: bool,
+    help_on_empty: bool,
+    help_codes: (char, &'static str),
+    version: bool,
+    version_codes: Option<(char, &'static str)>,
+    version_claim: Option<usize>, // index of the arg the version flag was matched in
+    error_hint: bool,
+    suggest_on_error: bool,
     has_variadic: bool,
+    strict_values: bool,
+    posix_strict: bool,
+    long_only: bool,
+    negative_numbers: bool,
+    auto_shorts: bool,
+    shorts: BTreeSet<char>,
+    top_subs: Vec<&'static str>,
+    sub_path: Vec<&'static str>,
+    sub_expected_at: Option<usize>,
+    repeated_at: Option<(usize, &'static str)>, // level and name of a subcommand given more than once
+    implications: Vec<(&'static str, &'static str)>,
+    abbreviations: bool,
+    abbreviated: Vec<(String, &'static str)>, // token given, long it was matched to
+    tree: Tree,
+    warnings: Vec<String>,
+    collect_missing: bool,
+    accumulate: bool,
+    errors: Vec<Error>,
+    missing: Vec<String>,
+    env: EnvSource,
+    on_unknown: Option<UnknownHandler>,
+    help_renderer: Option<HelpRenderer>,
+    config: BTreeMap<String, String>,
+    matched: Vec<MatchRecord<'args>>,
     argstop: Option<usize>,
+    argstop_token: &'static str,
     printer: printer::Printer,
 }
-impl Parser {
+impl<'args> Parser<'args> {
     /// Creates a new parser for the arg strings given.
-    pub fn from_strings(input: Vec<String>) -> Parser {
-        let argstop = match input.iter().enumerate().find(|(_, a)| a.as_str() == "--") {
-            Some((i, _)) => { Some(i) }
-            None => { None }
-        };
-        let count = argstop.unwrap_or(input.len());
-
-        let mut bits = bit_set::BitSet::with_capacity(count);
-        // TODO: PR with BitSet::set_all() -- or an inverse iter that iterates all unset
-        for i in 1..count {
-            bits.insert(i);
-        }
+    pub fn from_strings(input: Vec<String>) -> Parser<'args> {
+        Parser::new(input.into_iter().map(Cow::Owned).collect())
+    }
 
+    // shared by the constructors, which differ only in whether the args are owned or
+    // borrowed from the caller
+    fn new(args: Vec<Cow<'args, str>>) -> Parser<'args> {
         let mut p = Parser{
-            args: input,
-            mask: bits,
+            args,
+            by_value: None,
+            mask: bit_set::BitSet::new(),
             run_masks: BTreeMap::new(),
             walk_depth: 0,
             commit_depth: 0,
             max_depth: 0,
             parse_done: false,
             curr_group: None,
+            curr_pos_group: None,
 
             help: false,
+            help_on_empty: false,
+            help_codes: ('h', "help"),
+            version: false,
+            version_codes: Some(('V', "version")),
+            version_claim: None,
+            error_hint: false,
+            suggest_on_error: false,
             has_variadic: false,
-            argstop,
+            strict_values: false,
+            posix_strict: false,
+            long_only: false,
+            negative_numbers: false,
+            auto_shorts: false,
+            shorts: BTreeSet::new(),
+            top_subs: vec!(),
+            sub_path: vec!(),
+            sub_expected_at: None,
+            repeated_at: None,
+            implications: vec!(),
+            abbreviations: false,
+            abbreviated: vec!(),
+            tree: Tree::new(),
+            warnings: vec!(),
+            collect_missing: false,
+            accumulate: false,
+            errors: vec!(),
+            missing: vec!(),
+            env: Box::new(|key| env::var(key).ok()),
+            on_unknown: None,
+            help_renderer: None,
+            config: BTreeMap::new(),
+            matched: vec!(),
+            argstop: None,
+            argstop_token: "--",
             printer: printer::Printer::new(printer::App::empty()),
         };
+        p.reset();
+        p
+    }
+
+    // (re)computes the parse state from the args: finds the arg-stop sentinel, marks
+    // all args before it as unused, and handles the help flag. settings which change
+    // how the args are interpreted must reset so they apply to the help flag as well
+    fn reset(&mut self) {
+        let token = self.argstop_token;
+        self.argstop = self.args.iter().enumerate()
+            .find(|(_, a)| *a == token)
+            .map(|(i, _)| i);
+        let count = self.argstop.unwrap_or(self.args.len());
+
+        let mut bits = bit_set::BitSet::with_capacity(count);
+        // TODO: PR with BitSet::set_all() -- or an inverse iter that iterates all unset
+        for i in 1..count {
+            bits.insert(i);
+        }
+        self.mask = bits;
+        self.by_value = None;
+        self.run_masks.clear();
+        self.matched.clear();
+        self.missing.clear();
+        self.errors.clear();
+        self.abbreviated.clear();
+        self.shorts.clear();
+        self.tree.declared.clear();
+        self.tree.builtins = usize::MAX; // until the built-in flags are declared
+
+        // registered before the help flag so it is never added to the help dialog
+        let mut wants_version = false;
+        self.version = false;
+        self.version_claim = None;
+        if let Some((short, long)) = self.version_codes {
+            self.flag(short, long, "print version information", &mut wants_version, false)
+                .expect("could not handle version flag");
+        }
+        self.version = wants_version;
+        // nothing else has been matched, so the arg claimed is the only one used
+        let version_claim = if wants_version {
+            (1..count).find(|i| !self.mask.contains(*i))
+                .or_else(|| self.run_masks.keys().next().copied())
+        } else {
+            None
+        };
 
         let mut wants_help = false;
-        p.flag('h', "help", "print this help dialog", &mut wants_help, false)
+        self.help = false;
+        let (short, long) = self.help_codes;
+        self.flag(short, long, "print this help dialog", &mut wants_help, false)
             .expect("could not handle help flag");
-        p.help = wants_help;
+        self.help = wants_help || (self.help_on_empty && (self.args.len() <= 1));
+        self.tree.builtins = self.tree.declared.len();
+        self.version_claim = version_claim;
+    }
 
-        p
+    // gives way to a declaration using a code of the built-in version flag, so that an
+    // application declaring e.g. `-V` for `--verbose` keeps receiving it. the code is
+    // dropped from the version flag, and if the version was requested with that code the
+    // arg is released for the declaration to match
+    fn yield_version_codes(&mut self, short: char, long: &'static str) {
+        if self.version_codes.is_none() || self.tree.declaring_builtins() {
+            return;
+        }
+        // the version flag is always the first declaration
+        let (vshort, vlong) = match self.tree.declared.first_mut() {
+            Some(Declared::Option(_, s, l, _, _, _)) => {
+                let codes = (*s, *l);
+                if (short != '\0') && (short == *s) { *s = '\0'; }
+                if !long.is_empty() && (long == *l) { *l = ""; }
+                codes
+            }
+            _ => { return; }
+        };
+        let short_clash = (short != '\0') && (short == vshort);
+        let long_clash = !long.is_empty() && (long == vlong);
+
+        let idx = match self.version_claim {
+            Some(idx) => { idx }
+            None => { return; }
+        };
+        let long_form = self.args[idx].starts_with("--");
+        if (long_form && !long_clash) || (!long_form && !short_clash) {
+            return;
+        }
+
+        if let Some(run) = self.run_masks.get_mut(&idx) {
+            for (i, _) in self.args[idx].match_indices(vshort).filter(|(i, _)| *i > 0) {
+                run.insert(i);
+            }
+        }
+        self.mask.insert(idx);
+        self.matched.retain(|m| (m.short, m.long) != (vshort, vlong));
+        self.version = false;
+        self.version_claim = None;
+    }
+
+    // applies a setting which changes how the built-in flags or every arg are matched by
+    // matching again from scratch. declarations already made would be lost, so the setting
+    // is refused with `misuse` once any have been made
+    fn reset_for(&mut self, misuse: &'static str) -> Result<&mut Parser<'args>, Error> {
+        if self.tree.has_user_declarations() {
+            return Err(Error::InvalidState(misuse));
+        }
+        self.reset();
+        Ok(self)
+    }
+
+    /// Creates a new parser which borrows the arg strings given rather than copying them,
+    /// as [Parser::from_strings](#method.from_strings) takes ownership of its args. Values
+    /// are parsed and recorded (see [Parser::values_of](#method.values_of)) from the
+    /// borrowed strings, so the parser may not outlive them.
+    ///
+    /// This is useful when the args are already held elsewhere (e.g. embedding `rags` in a
+    /// larger application) or when testing an application built on `rags`, as the first
+    /// element is expected to be the program name just like `argv[0]`
+    /// (e.g. `Parser::from_slice(&["my_app", "--debug"])`).
+    pub fn from_slice(input: &[&'args str]) -> Parser<'args> {
+        Parser::new(input.iter().map(|a| Cow::Borrowed(*a)).collect())
     }
 
     /// Collects the arguments given on the command line and defers to
     /// [Parser::from_strings](#method.from_strings).
-    pub fn from_args() -> Parser {
+    pub fn from_args() -> Parser<'args> {
         let args = env::args().collect::<Vec<String>>();
         Parser::from_strings(args)
     }
 
+    /// Same as [Parser::from_args](#method.from_args), but drops the first `n` args
+    /// following the program name. This is useful when another dispatcher (e.g. a
+    /// multicall binary) has already consumed some leading args, so that parsing
+    /// (including finding the arg-stop sentinel) only considers the remainder.
+    pub fn from_args_skipping(n: usize) -> Parser<'args> {
+        Parser::from_strings(skip_leading(env::args().collect(), n))
+    }
+
+    /// Same as [Parser::from_args](#method.from_args), but each arg of the form `@file`
+    /// is replaced by the args within the file, separated by whitespace. This allows
+    /// tools driven by build systems to pass long argument lists without exceeding the
+    /// limits of the OS. See
+    /// [Parser::from_strings_expanding_response_files](#method.from_strings_expanding_response_files)
+    /// for details.
+    pub fn from_args_expanding_response_files() -> Result<Parser<'args>, Error> {
+        Parser::from_strings_expanding_response_files(env::args().collect())
+    }
+
+    /// Same as [Parser::from_strings](#method.from_strings), but each arg of the form
+    /// `@file` is replaced by the args within the file, separated by whitespace.
+    /// References within a file are expanded as well, relative to the working directory.
+    /// The program name and args following the arg-stop sentinel (`--`) are never
+    /// expanded.
+    ///
+    /// A file which cannot be read, or which references itself (directly or through
+    /// other files), results in
+    /// [Error::ResponseFile](enum.Error.html#variant.ResponseFile).
+    pub fn from_strings_expanding_response_files(mut input: Vec<String>) -> Result<Parser<'args>, Error> {
+        if input.is_empty() {
+            return Ok(Parser::from_strings(input));
+        }
+        let rest = input.split_off(1);
+        input.extend(expand_response_files(rest, &mut vec!())?);
+        Ok(Parser::from_strings(input))
+    }
+
     /// Unused returns all unmatched args. The [Unused](struct.Unused.html) struct
     /// contains the necessary information to call out unrecognized args or typos in
     /// passed arguments.
     ///
     /// If there is an unused character in a run of shortcodes (e.g. `-abcd`, with `b` unused)
     /// the argument within the [Unused](struct.Unused.html) struct will be prefixed with a dash.
+    /// Dashes embedded within a run (e.g. `-v--`) are never matched and are each reported
+    /// as a lone `-`.
     pub fn unused(&self) -> Vec<Unused> {
+        self.unused_at().into_iter().map(|(_, _, u)| u).collect()
+    }
+
+    // all unmatched args along with their index and, for a character within a run of
+    // short codes, its offset in the run
+    fn unused_at(&self) -> Vec<(usize, Option<usize>, Unused)> {
         let mut result = vec!();
         for i in self.mask.iter() {
             match self.run_masks.get(&i) {
                 None => {}
                 Some(mask) => {
+                    // the run mask holds byte offsets, so skip any that fall within a
+                    // multi-byte character as the character is reported at its start
                     for m in mask.iter() {
-                        let s = format!("-{}", self.args[i].chars().nth(m+1).unwrap());
-                        result.push(Unused{
+                        let c = match self.args[i].get(m..).and_then(|s| s.chars().next()) {
+                            Some(c) => { c }
+                            None => { continue; }
+                        };
+                        // an embedded dash (e.g. `-v--`) is reported alone rather than
+                        // as `--` so it does not look like a long arg
+                        let s = if c == '-' { c.to_string() } else { format!("-{}", c) };
+                        result.push((i, Some(m), Unused{
                             arg: s,
                             looks_like: LooksLike::ShortArg,
-                        });
+                        }));
                     }
                     continue;
                 }
             }
 
-            result.push(Unused::new(self.args[i].clone()));
+            result.push((i, None, self.classify(self.args[i].to_string())));
         }
 
         result
     }
 
-
-    //----------------------------------------------------------------
-    // help setup
-    //----------------------------------------------------------------
-
-    /// Sets the name of the application to be printed in the help dialog.
-    /// Printed on the first line of the dialog.
-    pub fn app_name<'a>(&'a mut self, name: &'static str) -> &'a mut Parser {
-        self.printer.set_name(name);
-        self
+    // classifies an arg as Unused::new does, except a single dash never begins a short
+    // code when short codes are disabled, nor when it begins an allowed negative number
+    fn classify(&self, arg: String) -> Unused {
+        Unused {
+            looks_like: self.looks_like(&arg),
+            arg,
+        }
     }
 
-    /// Sets the description of the application to be printed in the help dialog.
-    /// Printed on the first line of the dialog.
-    pub fn app_desc<'a>(&'a mut self, desc: &'static str) -> &'a mut Parser {
-        self.printer.set_short_desc(desc);
-        self
+    // the classification used by classify, without taking ownership of the arg
+    fn looks_like(&self, arg: &str) -> LooksLike {
+        match LooksLike::of(arg) {
+            LooksLike::ShortArg if self.long_only || self.is_negative_number(arg) => {
+                LooksLike::Positional
+            }
+            kind => { kind }
+        }
     }
 
-    /// Sets the long-form description of the application to be printed in the
-    /// help dialog. Printed after the base application info and usage lines.
-    pub fn app_long_desc<'a>(&'a mut self, desc: &'static str) -> &'a mut Parser {
-        self.printer.set_long_desc(desc);
-        self
+    /// Returns a snapshot of which args have been consumed so far. See
+    /// [ConsumedMask](struct.ConsumedMask.html) for details.
+    pub fn consumption(&self) -> ConsumedMask {
+        ConsumedMask{
+            unconsumed: self.mask.clone(),
+            len: self.args.len(),
+        }
     }
 
-    /// Sets the version of the application to be printed in the help dialog.
-    /// Printed on the first line of the dialog.
-    pub fn app_version<'a>(&'a mut self, vers: &'static str) -> &'a mut Parser {
-        self.printer.set_version(vers);
-        self
+    /// Returns all matched options keyed by their long name (or short code if the
+    /// option has no long name) mapped to the raw string values captured for them.
+    ///
+    /// Flags appear with an empty vector of values. Counts appear with a single value
+    /// holding the number of times the count was given (e.g. `-vvv` yields `["3"]`).
+    /// Args have a single value, and lists have one value per occurrence.
+    ///
+    /// Options which were not matched are not present in the map.
+    pub fn matched_options(&self) -> BTreeMap<String, Vec<String>> {
+        let mut result = BTreeMap::new();
+        for rec in self.matched.iter() {
+            result.insert(rec.key(), rec.values.iter().map(|v| v.to_string()).collect());
+        }
+        result
+    }
+
+    /// Reconstructs `T` from the options matched so far, as returned by
+    /// [Parser::matched_options](#method.matched_options). Each option is a field keyed by
+    /// its long name, so names containing dashes need `#[serde(rename_all = "kebab-case")]`
+    /// or a per-field rename.
+    ///
+    /// Flags deserialize as `true`, values are parsed into the type of the field, and
+    /// options given more than once deserialize as sequences. Options which were not
+    /// matched are absent, so fields should be `Option` or `#[serde(default)]` unless the
+    /// option is required.
+    ///
+    /// Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn deserialize<T: serde::de::DeserializeOwned>(&self) -> Result<T, Error> {
+        de::from_matched(self.matched_options())
+            .map_err(|e| Error::Deserialize(e.to_string()))
+    }
+
+    /// Returns the raw string values captured for the option with the given codes, in
+    /// the order they were given. This is most useful for lists, but works for any
+    /// option recorded by [Parser::matched_options](#method.matched_options). Returns an
+    /// empty vector if the option was not matched.
+    ///
+    /// Options with a long name are looked up by it alone, so the short code may be left
+    /// as `'\0'` (e.g. when it was assigned by
+    /// [Parser::auto_short_codes](#method.auto_short_codes)).
+    pub fn values_of(&self, short: char, long: &'static str) -> Vec<&str> {
+        match self.matched.iter().find(|m| m.is(short, long)) {
+            Some(rec) => { rec.values.iter().map(|v| v.as_ref()).collect() }
+            None => { vec!() }
+        }
+    }
+
+    /// Returns a snapshot of the parse so far: the matched options and their values,
+    /// the matched subcommands, the unused args, and whether help was requested.
+    /// See [ParseOutcome](struct.ParseOutcome.html).
+    pub fn outcome(&self) -> ParseOutcome {
+        ParseOutcome{
+            matched: self.matched_options(),
+            subcommands: self.sub_path.iter().map(|s| s.to_string()).collect(),
+            unused: self.unused().into_iter().map(|u| u.arg).collect(),
+            help: self.wants_help(),
+            version: self.wants_version(),
+        }
+    }
+
+    /// Returns the token given where a subcommand was expected but none of the declared
+    /// subcommands matched, allowing an "unknown subcommand" error to be reported rather
+    /// than a generic unused argument. This is the first unused positional-looking arg
+    /// when the deepest matched level (or the top level) declared subcommands.
+    ///
+    /// Returns `None` if a subcommand matched at the deepest level, no subcommands were
+    /// declared there, or help was requested.
+    pub fn unmatched_subcommand(&self) -> Option<&str> {
+        if self.wants_help() || (self.sub_expected_at != Some(self.commit_depth)) {
+            return None;
+        }
+
+        self.mask.iter()
+            .filter(|i| !self.run_masks.contains_key(i))
+            .map(|i| self.args[i].as_ref())
+            .find(|a| LooksLike::of(a) == LooksLike::Positional)
+    }
+
+    /// Returns the subcommand closest to a mistyped token (e.g. `build` for `biuld`), so
+    /// an application can report "unknown subcommand 'biuld'; did you mean 'build'?".
+    /// Only the subcommands declared at the deepest matched level (or the top level) are
+    /// considered, making this a natural companion to
+    /// [Parser::unmatched_subcommand](#method.unmatched_subcommand).
+    ///
+    /// Returns `None` if the token names one of those subcommands exactly, or if none of
+    /// them is close enough to be a likely misspelling.
+    pub fn suggest_subcommand(&self, token: &str) -> Option<&'static str> {
+        let scope = self.tree.scope_of(&self.sub_path)?;
+        let subs = self.tree.subcommands(scope);
+        if subs.contains(&token) {
+            return None;
+        }
+
+        subs.into_iter()
+            .map(|name| (edit_distance(token, name), name))
+            .filter(|(dist, name)| {
+                (*dist < name.chars().count()) && (*dist <= std::cmp::max(2, name.len() / 3))
+            })
+            .min_by_key(|(dist, _)| *dist)
+            .map(|(_, name)| name)
+    }
+
+    fn record_match(&mut self, short: char, long: &'static str, value: Option<Cow<'args, str>>) {
+        let existing = self.matched.iter_mut().find(|m| m.is(short, long));
+        let rec = match existing {
+            Some(rec) => { rec }
+            None => {
+                self.matched.push(MatchRecord{ short, long, values: vec!() });
+                self.matched.last_mut().expect("failed to insert match record")
+            }
+        };
+        if let Some(v) = value {
+            rec.values.push(v);
+        }
+    }
+
+
+    //----------------------------------------------------------------
+    // help setup
+    //----------------------------------------------------------------
+
+    /// Sets the name of the application to be printed in the help dialog.
+    /// Printed on the first line of the dialog.
+    pub fn app_name<'a>(&'a mut self, name: &'static str) -> &'a mut Parser<'args> {
+        self.printer.set_name(name);
+        self
+    }
+
+    /// Sets the description of the application to be printed in the help dialog.
+    /// Printed on the first line of the dialog.
+    pub fn app_desc<'a>(&'a mut self, desc: &'static str) -> &'a mut Parser<'args> {
+        self.printer.set_short_desc(desc);
+        self
+    }
+
+    /// Sets the long-form description of the application to be printed in the
+    /// help dialog. Printed after the base application info and usage lines.
+    pub fn app_long_desc<'a>(&'a mut self, desc: &'static str) -> &'a mut Parser<'args> {
+        self.printer.set_long_desc(desc);
+        self
+    }
+
+    /// Sets the version of the application to be printed in the help dialog.
+    /// Printed on the first line of the dialog.
+    pub fn app_version<'a>(&'a mut self, vers: &'static str) -> &'a mut Parser<'args> {
+        self.printer.set_version(vers);
+        self
+    }
+
+    /// Sets the command name printed in the usage line of the help dialog. By default
+    /// the usage line uses the name given to [Parser::app_name](#method.app_name), but
+    /// an application invoked under a different name (symlinks, multi-call binaries)
+    /// may want the usage to reflect how it was invoked. The first line of the dialog
+    /// continues to use the application name.
+    ///
+    /// See [Parser::usage_name_from_args](#method.usage_name_from_args) to use the
+    /// name the binary was invoked as.
+    pub fn usage_name<'a>(&'a mut self, name: &str) -> &'a mut Parser<'args> {
+        self.printer.set_usage_name(name);
+        self
+    }
+
+    /// Sets the usage name (see [Parser::usage_name](#method.usage_name)) to the file
+    /// name of the first argument (`argv[0]`) given to the parser.
+    pub fn usage_name_from_args(&mut self) -> &mut Parser<'args> {
+        let name = match self.args.first() {
+            Some(a) => {
+                std::path::Path::new(a.as_ref()).file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| a.to_string())
+            }
+            None => { return self; }
+        };
+        self.usage_name(&name)
+    }
+
+    /// Sets the width, in columns, at which the help dialog wraps. The usage line
+    /// is broken between arguments at this width, with continuation lines indented
+    /// under the command name. Defaults to 80 columns.
+    pub fn help_width(&mut self, width: usize) -> &mut Parser<'args> {
+        self.printer.set_width(width);
+        self
+    }
+
+    /// Sets the number of spaces between the left column of the help dialog (codes,
+    /// names, and labels) and the descriptions. Defaults to 8.
+    pub fn help_gap(&mut self, gap: usize) -> &mut Parser<'args> {
+        self.printer.set_gap(gap);
+        self
+    }
+
+    /// Sets both paddings of the help dialog: the number of spaces options, subcommands,
+    /// and positionals are indented by (defaults to 4), and the number of spaces between
+    /// the left column and the descriptions as in [Parser::help_gap](#method.help_gap)
+    /// (defaults to 8).
+    pub fn help_layout(&mut self, left_pad: usize, mid_pad: usize) -> &mut Parser<'args> {
+        self.printer.set_indent(left_pad);
+        self.printer.set_gap(mid_pad);
+        self
+    }
+
+    /// Hints that the text given for the help dialog (descriptions, labels, and names)
+    /// contains ANSI escape sequences, such as pre-styled text from a build script. When
+    /// set, the escape sequences are not counted when aligning the columns of the dialog.
+    /// This must be called before any arguments are declared.
+    pub fn descriptions_contain_ansi(&mut self, ansi: bool) -> &mut Parser<'args> {
+        self.printer.set_ansi(ansi);
+        self
+    }
+
+    /// Sets whether the help dialog is printed with color: section headers in bold,
+    /// option codes in cyan, and `required` markers in red. Defaults to
+    /// [ColorMode::Never](enum.ColorMode.html#variant.Never). With
+    /// [ColorMode::Auto](enum.ColorMode.html#variant.Auto), color is only used when the
+    /// help is printed to a terminal (stdout for [Parser::print_help](#method.print_help),
+    /// stderr for [Parser::print_help_err](#method.print_help_err)) so that piped output
+    /// stays clean. [Parser::help_string](#method.help_string) is never colored, and
+    /// [Parser::print_help_to](#method.print_help_to) is only colored with
+    /// [ColorMode::Always](enum.ColorMode.html#variant.Always).
+    pub fn color(&mut self, mode: ColorMode) -> &mut Parser<'args> {
+        self.printer.set_color(mode);
+        self
+    }
+
+    /// Sets how the annotations following each description in the help dialog are
+    /// rendered. The function is given the [Accessory](struct.Accessory.html) of an
+    /// argument or positional and returns the text to append to its description,
+    /// including any leading space (or an empty string for no annotations).
+    ///
+    /// By default, annotations are bracketed (e.g. ` [choices: a|b] [required, default: a]`).
+    pub fn accessory_format<F: Fn(&Accessory) -> String + 'static>(&mut self, format: F)
+        -> &mut Parser<'args>
+    {
+        self.printer.set_accessory_format(Box::new(format));
+        self
+    }
+
+    /// Sets where required arguments and positionals are marked in the help dialog.
+    /// Defaults to [RequiredMarker::TrailingBracket](enum.RequiredMarker.html#variant.TrailingBracket),
+    /// annotating the description with `[required]`. With
+    /// [RequiredMarker::LeadingAsterisk](enum.RequiredMarker.html#variant.LeadingAsterisk),
+    /// a `*` is placed before the option string or positional name instead (e.g.
+    /// `  * -f, --file`), and the [Accessory](struct.Accessory.html) given to a custom
+    /// [Parser::accessory_format](#method.accessory_format) is never marked as required.
+    pub fn required_marker(&mut self, marker: RequiredMarker) -> &mut Parser<'args> {
+        self.printer.set_required_marker(marker);
+        self
+    }
+
+    /// Sets the order options are printed in within each section of the help dialog.
+    /// Defaults to [OptionSort::Declared](enum.OptionSort.html#variant.Declared).
+    pub fn option_sort(&mut self, sort: OptionSort) -> &mut Parser<'args> {
+        self.printer.set_sort(sort);
+        self
+    }
+
+    /// Prints the options of all groups under the main `options:` section of the help
+    /// dialog rather than in separate sections per group. Only the layout of the help
+    /// dialog is affected, groups otherwise behave as usual.
+    pub fn flatten_groups_in_help(&mut self) -> &mut Parser<'args> {
+        self.printer.set_flatten_groups();
+        self
     }
 
     /// Returns whether the help argument was given and help should be printed.
@@ -469,9 +1580,509 @@ impl Parser {
     }
 
     /// Prints the help information. If subcommands are provided, the help for
-    /// the leaf subcommand is printed.
+    /// the leaf subcommand is printed. When a renderer is set by
+    /// [Parser::set_help_renderer](#method.set_help_renderer), it is given the help
+    /// information to render instead.
+    ///
+    /// A failure to write, such as stdout being closed early when piped to `head`, is
+    /// ignored. Use [Parser::print_help_to](#method.print_help_to) to handle it.
     pub fn print_help(&self) {
-        self.printer.print();
+        match self.help_renderer {
+            Some(ref render) => { render(&self.help_model()); }
+            None => {
+                let terminal = std::io::stdout().is_terminal();
+                // nowhere to report a failure, and the help being cut short is not worth a panic
+                let _ = self.printer.print(&mut std::io::stdout(), terminal);
+            }
+        }
+    }
+
+    /// Same as [Parser::print_help](#method.print_help), but prints the built-in help
+    /// dialog to stderr. This keeps stdout free for the output of the application, such
+    /// as when printing usage alongside an error. As with printing to stdout, a failure to
+    /// write is ignored.
+    pub fn print_help_err(&self) {
+        let terminal = std::io::stderr().is_terminal();
+        // nowhere to report a failure, and the help being cut short is not worth a panic
+        let _ = self.printer.print(&mut std::io::stderr(), terminal);
+    }
+
+    /// Sets a renderer which [Parser::print_help](#method.print_help) hands the structured
+    /// help information to, rather than printing the built-in dialog. This allows the
+    /// application to take full control of how help is presented (e.g. as a table) while
+    /// still declaring arguments with rags. Other ways of producing help, such as
+    /// [Parser::help_string](#method.help_string), are unaffected.
+    pub fn set_help_renderer<F: Fn(&HelpModel) + 'static>(&mut self, renderer: F) -> &mut Parser<'args> {
+        self.help_renderer = Some(Box::new(renderer));
+        self
+    }
+
+    /// Returns the structured help information: the application (or leaf subcommand),
+    /// the usage synopsis, and the subcommands, options, and positionals declared. As
+    /// with the help dialog, this is only populated when help was requested.
+    pub fn help_model(&self) -> HelpModel {
+        self.printer.model()
+    }
+
+    /// Same as [Parser::print_help](#method.print_help), but writes the help information
+    /// to the given writer. This allows help to be captured (e.g. into a `Vec<u8>` when
+    /// testing) or sent somewhere other than stdout.
+    pub fn print_help_to<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        self.printer.print(w, false)
+    }
+
+    /// Returns the help information as a string rather than printing it, such as for
+    /// display within a TUI or logging. This is exactly what
+    /// [Parser::print_help](#method.print_help) would print, including the selection of
+    /// the leaf subcommand, though without color.
+    pub fn help_string(&self) -> String {
+        self.printer.render()
+    }
+
+    /// Whether the version flag (`-V`/`--version` by default) was given. Just as with
+    /// help, required args, positionals, and subcommands are not enforced when the
+    /// version is requested.
+    pub fn wants_version(&self) -> bool {
+        self.version
+    }
+
+    /// Prints the application name and version (e.g. `rags 1.0`), as set by
+    /// [Parser::app_name](#method.app_name) and [Parser::app_version](#method.app_version).
+    pub fn print_version(&self) {
+        // as with help, a closed stdout is not worth a panic
+        let _ = writeln!(std::io::stdout(), "{}", self.printer.version_line());
+    }
+
+    /// Sets the codes of the help flag, which are `-h` and `--help` by default, such as
+    /// `-?` and `--usage` for applications using `-h` for something else (e.g. a host).
+    /// Either may be disabled by passing `'\0'` or `""` respectively.
+    ///
+    /// The help flag is matched when the parser is created, so it is matched again with
+    /// the new codes. This fails with
+    /// [Error::InvalidState](enum.Error.html#variant.InvalidState) once any argument has
+    /// been declared.
+    pub fn help_flags(&mut self, short: char, long: &'static str) -> Result<&mut Parser<'args>, Error> {
+        self.help_codes = (short, long);
+        self.reset_for("help_flags(...) called after declaring arguments")
+    }
+
+    /// Sets the codes of the version flag, which are `-V` and `--version` by default.
+    /// Either may be disabled by passing `'\0'` or `""` respectively. A code which the
+    /// application declares for an option of its own (e.g. `-V` for `--verbose`) is given
+    /// up by the version flag, so that option still receives it.
+    ///
+    /// As with [Parser::help_flags](#method.help_flags), this must come before any
+    /// argument is declared, otherwise
+    /// [Error::InvalidState](enum.Error.html#variant.InvalidState) is returned.
+    pub fn version_flag(&mut self, short: char, long: &'static str)
+        -> Result<&mut Parser<'args>, Error>
+    {
+        self.version_codes = Some((short, long));
+        self.reset_for("version_flag(...) called after declaring arguments")
+    }
+
+    /// Disables the version flag, such as for applications using `-V` for something else.
+    /// Like [Parser::version_flag](#method.version_flag), this fails once any argument has
+    /// been declared.
+    pub fn no_version_flag(&mut self) -> Result<&mut Parser<'args>, Error> {
+        self.version_codes = None;
+        self.reset_for("no_version_flag() called after declaring arguments")
+    }
+
+    /// Sets whether errors formatted by [Parser::format_error](#method.format_error)
+    /// include a hint to run with the help flag, such as "run with --help for usage".
+    pub fn error_help_hint(&mut self, hint: bool) -> &mut Parser<'args> {
+        self.error_hint = hint;
+        self
+    }
+
+    /// Sets whether an [Error::UnexpectedArgument](enum.Error.html#variant.UnexpectedArgument)
+    /// includes the closest declared long name when the unexpected argument looks like a
+    /// misspelling of it (e.g. "unexpected argument: --verbsoe; did you mean --verbose?").
+    pub fn suggest_on_error(&mut self, suggest: bool) -> &mut Parser<'args> {
+        self.suggest_on_error = suggest;
+        self
+    }
+
+    // the declared long name closest to a mistyped long arg (e.g. `--verbsoe`), if it is
+    // within a third of the length of the name
+    fn suggest_long(&self, arg: &str) -> Option<String> {
+        let given = arg.strip_prefix("--")?;
+        let given = given.split_once('=').map(|(g, _)| g).unwrap_or(given);
+
+        self.tree.declared.iter()
+            .filter_map(|d| match d {
+                Declared::Option(_, _, long, _, _, false) if !long.is_empty() => { Some(*long) }
+                _ => { None }
+            })
+            .map(|long| (edit_distance(given, long), long))
+            .filter(|(dist, long)| (*dist > 0) && (*dist <= std::cmp::max(1, long.len() / 3)))
+            .min_by_key(|(dist, _)| *dist)
+            .map(|(_, long)| format!("--{}", long))
+    }
+
+    /// Formats an error for display to the user. When enabled by
+    /// [Parser::error_help_hint](#method.error_help_hint), a line is appended
+    /// referencing the help flag.
+    pub fn format_error(&self, e: &Error) -> String {
+        if !self.error_hint {
+            return e.to_string();
+        }
+
+        let (short, long) = self.help_codes;
+        let flag = if long.is_empty() { format!("-{}", short) } else { format!("--{}", long) };
+        format!("{}\nrun with {} for usage", e, flag)
+    }
+
+    /// Same as [Parser::print_help](#method.print_help), but when stdout is a terminal
+    /// the help is piped through the pager named by the `PAGER` environment variable
+    /// (`less -R` if unset). The help is printed directly when stdout is not a terminal,
+    /// when `PAGER` is set but empty, or when the pager cannot be started. Color is
+    /// used as it would be when printing to a terminal (see [Parser::color](#method.color)).
+    pub fn print_help_paged(&self) {
+        let terminal = std::io::stdout().is_terminal();
+        // nowhere to report a failure, and the help being cut short is not worth a panic
+        let _ = self.page_help(self.pager(), terminal, &mut std::io::stdout());
+    }
+
+    // the pager command to use for paged help, or none when `PAGER` is set but empty
+    fn pager(&self) -> Option<String> {
+        match self.env_var("PAGER") {
+            Some(p) if p.trim().is_empty() => { None }
+            Some(p) => { Some(p) }
+            None => { Some("less -R".to_string()) }
+        }
+    }
+
+    // pipes the help through the given pager if interactive, writing to `fallback` when
+    // not interactive or the pager is unusable
+    fn page_help<W: Write>(&self,
+        pager: Option<String>, interactive: bool, fallback: &mut W
+    ) -> std::io::Result<()>
+    {
+        let pager = pager.filter(|p| interactive && !p.trim().is_empty());
+        let mut help: Vec<u8> = vec!();
+        // either the pager or the fallback shows the help on the terminal when interactive
+        self.printer.print(&mut help, interactive)?;
+        let mut parts = match pager {
+            Some(ref p) => { p.split_whitespace() }
+            None => { return fallback.write_all(&help); }
+        };
+
+        let cmd = parts.next().expect("non-empty pager has a command");
+        let child = std::process::Command::new(cmd)
+            .args(parts)
+            .stdin(std::process::Stdio::piped())
+            .spawn();
+        let mut child = match child {
+            Ok(c) => { c }
+            Err(_) => { return fallback.write_all(&help); }
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // the user quitting the pager early closes the pipe, which is not an error
+            match stdin.write_all(&help) {
+                Err(ref e) if e.kind() == std::io::ErrorKind::BrokenPipe => {}
+                other => { other?; }
+            }
+        }
+        child.wait().map(|_| ())
+    }
+
+    /// Renders the help information as a roff man page for the given manual section
+    /// (typically `1` for user commands). The page contains the standard `NAME`,
+    /// `SYNOPSIS`, and `DESCRIPTION` sections followed by `COMMANDS`, `OPTIONS`, and
+    /// `ARGUMENTS` sections for subcommands, options, and positionals respectively.
+    ///
+    /// As with [Parser::help_markdown](#method.help_markdown), the parser must have been
+    /// given a help argument for the information to be gathered.
+    pub fn generate_manpage(&self, section: u8) -> String {
+        self.printer.manpage(section)
+    }
+
+    /// Renders a bash completion script for the options and subcommands, to be sourced
+    /// by the shell. Values of options declared with [Parser::arg_completed](#method.arg_completed) complete according
+    /// to their [Completion](enum.Completion.html) hint.
+    ///
+    /// As with [Parser::help_markdown](#method.help_markdown), the parser must have been
+    /// given a help argument for the information to be gathered.
+    pub fn generate_bash_completion(&self, bin_name: &str) -> String {
+        self.printer.bash_completion(bin_name)
+    }
+
+    /// Renders a zsh completion script, to be saved as `_<bin_name>` somewhere in
+    /// `$fpath` so zsh autoloads it (or to be sourced directly). Options complete with
+    /// their descriptions, options taking a value prompt for it (according to their
+    /// [Completion](enum.Completion.html) hint, if any), and subcommands are offered with
+    /// their descriptions through `_describe`.
+    ///
+    /// Unlike [Parser::generate_bash_completion](#method.generate_bash_completion), the
+    /// script covers every declared subcommand, not only the level being parsed, so it
+    /// completes at any depth. It does not require the help argument to be given, but
+    /// should be called after all declarations have been made.
+    pub fn generate_zsh_completion(&self, bin_name: &str) -> String {
+        let func = format!("_{}", bin_name);
+        let mut out = format!("#compdef {}\n\n", bin_name);
+        self.tree.zsh_function(0, &func, &mut out);
+        out.push_str(&format!("\nif [ \"$funcstack[1]\" = \"{}\" ]; then\n", func));
+        out.push_str(&format!("    {} \"$@\"\n", func));
+        out.push_str("else\n");
+        out.push_str(&format!("    compdef {} {}\n", func, bin_name));
+        out.push_str("fi\n");
+        out
+    }
+
+    /// Renders the help information as Markdown, suitable for embedding in a README
+    /// or documentation site. The app name is a heading, the usage line is a fenced
+    /// block, and subcommands, options, and positionals are rendered as tables.
+    ///
+    /// Just as with [Parser::print_help](#method.print_help), the help information is
+    /// only gathered when a help argument was given, so documentation generators should
+    /// construct the parser with one (e.g. `Parser::from_slice(&["my_app", "--help"])`).
+    pub fn help_markdown(&self) -> String {
+        self.printer.markdown()
+    }
+
+
+    //----------------------------------------------------------------
+    // parse setup
+    //----------------------------------------------------------------
+
+    /// Sets the arg-stop sentinel, which is `--` by default. Arguments after the
+    /// sentinel are not parsed as arguments and are instead gathered by
+    /// [Parser::positional_list](#method.positional_list). When a custom sentinel is
+    /// used, `--` becomes an ordinary argument.
+    ///
+    /// Changing the sentinel changes which args may be matched at all, so
+    /// [Error::InvalidState](enum.Error.html#variant.InvalidState) is returned if any
+    /// argument has already been declared.
+    pub fn arg_stop_token(&mut self, token: &'static str) -> Result<&mut Parser<'args>, Error> {
+        self.argstop_token = token;
+        self.reset_for("arg_stop_token(...) called after declaring arguments")
+    }
+
+    /// Enables strict handling of values separated from their argument by a space.
+    /// By default, `--output --verbose` constructs `--output` from the string `--verbose`.
+    /// In strict mode a value which looks like an argument (see
+    /// [LooksLike](enum.LooksLike.html)) is refused and
+    /// [Error::MissingArgValue](enum.Error.html#variant.MissingArgValue) is returned.
+    ///
+    /// Because arguments are parsed in the order they are declared, an argument declared
+    /// later is not yet known when a value is claimed, so any argument-like value is refused.
+    /// Values which legitimately begin with a dash can still be given using an equal sign
+    /// (e.g. `--output=-`).
+    pub fn strict_values(&mut self) -> &mut Parser<'args> {
+        self.strict_values = true;
+        self
+    }
+
+    /// Stops matching options at the first non-option, as POSIX `getopt` does (or GNU
+    /// `getopt` with `POSIXLY_CORRECT` set). By default, options and positionals may be
+    /// freely interspersed (e.g. `cp src -v dst`), similar to the permuting behavior of
+    /// GNU `getopt`. In POSIX-strict mode, everything from the first non-option onward
+    /// is left for positionals (so `cp src -v dst` gives the positionals `src`, `-v`, and
+    /// `dst`), just as if it followed the arg-stop sentinel.
+    ///
+    /// Subcommands are not considered non-options: once a subcommand is matched, option
+    /// matching continues after it within the subcommand's scope. Because options are
+    /// matched in the order they are declared, a non-option directly following an option
+    /// which is not yet declared is assumed to be that option's value.
+    pub fn posix_strict(&mut self) -> &mut Parser<'args> {
+        self.posix_strict = true;
+        self
+    }
+
+    /// Disables short codes entirely, so only long names and positionals are matched.
+    /// Any arg beginning with a single dash (e.g. `-x` or `-abc`) is never treated as a
+    /// short code or a run of short codes, and is instead left for positionals (and
+    /// reported as a positional by [Parser::unused](#method.unused) if unclaimed).
+    ///
+    /// Short codes may still be declared, but are never matched. The built-in flags are
+    /// matched again without their short codes, so this fails with
+    /// [Error::InvalidState](enum.Error.html#variant.InvalidState) once any argument has
+    /// been declared.
+    pub fn long_only(&mut self) -> Result<&mut Parser<'args>, Error> {
+        self.long_only = true;
+        self.reset_for("long_only() called after declaring arguments")
+    }
+
+    /// Sets whether a long name may be given as any unambiguous prefix of itself, so
+    /// `--verb` matches `--verbose`. An exact long name always wins over a prefix, and a
+    /// prefix of more than one long name in scope fails with
+    /// [Error::AmbiguousLong](enum.Error.html#variant.AmbiguousLong).
+    ///
+    /// Because options are matched in the order they are declared, a prefix is matched
+    /// against the long names declared so far. Should an option declared later also begin
+    /// with a prefix which has already been matched, its declaration fails with
+    /// [Error::AmbiguousLong](enum.Error.html#variant.AmbiguousLong) as well. The help
+    /// and version flags are matched when the parser is created, so they are only given
+    /// by their full long names and never make a prefix they share ambiguous.
+    pub fn allow_abbreviations(&mut self, allow: bool) -> &mut Parser<'args> {
+        self.abbreviations = allow;
+        self
+    }
+
+    /// Treats args which look like negative numbers (e.g. `-5` or `-2.5`) as positionals
+    /// and values rather than short codes, so tools taking numeric positionals do not
+    /// have them claimed by short codes or reported as unknown options.
+    ///
+    /// An arg is only treated as a number when the digit following the dash is not a
+    /// declared short code, so declaring `-5` as a flag still matches it as that flag.
+    /// This must be called before declaring any arguments.
+    pub fn allow_negative_numbers(&mut self, allow: bool) -> &mut Parser<'args> {
+        self.negative_numbers = allow;
+        self
+    }
+
+    /// Requests help when no arguments (beyond the program name) are given, so running
+    /// the bare binary prints the help dialog rather than doing nothing. This must be
+    /// called before any arguments are declared so they are gathered for the help dialog,
+    /// but may come before or after settings such as
+    /// [Parser::help_flags](#method.help_flags).
+    pub fn help_on_empty(&mut self) -> &mut Parser<'args> {
+        self.help_on_empty = true;
+        if self.args.len() <= 1 {
+            self.help = true;
+        }
+        self
+    }
+
+    /// Enables automatic short codes. Any option declared without a short code (e.g.
+    /// via [Parser::long_arg](#method.long_arg)) is assigned the first alphabetic
+    /// character of its long name not already used as a short code. If every character
+    /// is taken, the option is left without a short code.
+    ///
+    /// Assignment happens in declaration order and only considers short codes declared
+    /// before it, so options with explicit short codes should be declared first. The
+    /// assigned codes are shown in the help dialog.
+    pub fn auto_short_codes(&mut self) -> &mut Parser<'args> {
+        self.auto_shorts = true;
+        self
+    }
+
+    /// Registers a handler deciding what becomes of each argument which was not matched
+    /// by any declaration (i.e. each entry of [Parser::unused](#method.unused)). This
+    /// allows for plugin dispatch or pass-through without post-processing the unused args.
+    ///
+    /// The handler is called by [Parser::finish](#method.finish), once per unmatched
+    /// argument from left to right, and returns an [UnknownAction](enum.UnknownAction.html).
+    /// The first argument the handler returns `UnknownAction::Error` for fails with
+    /// [Error::UnexpectedArgument](enum.Error.html#variant.UnexpectedArgument), while
+    /// consumed arguments are no longer reported as unused. The handler is not called
+    /// when help was requested.
+    pub fn on_unknown<F: FnMut(&Unused) -> UnknownAction + 'static>(&mut self, handler: F)
+        -> &mut Parser<'args>
+    {
+        self.on_unknown = Some(Box::new(handler));
+        self
+    }
+
+    /// Defers errors for required args and positionals which were not given. Rather
+    /// than failing on the first, each is recorded and all of them are reported together
+    /// as [Error::MissingArguments](enum.Error.html#variant.MissingArguments) by
+    /// [Parser::finish](#method.finish).
+    pub fn collect_required_errors(&mut self) -> &mut Parser<'args> {
+        self.collect_missing = true;
+        self
+    }
+
+    /// Records construction failures and missing required args or positionals rather
+    /// than failing on the first, so every mistake can be reported at once. The failing
+    /// declarations leave their targets untouched (holding whatever default they were
+    /// given) and the declaration chain continues. Other errors are still returned
+    /// immediately.
+    ///
+    /// The recorded errors are returned by [Parser::errors](#method.errors), which should
+    /// be checked once all arguments have been declared.
+    pub fn accumulate_errors(&mut self) -> &mut Parser<'args> {
+        self.accumulate = true;
+        self
+    }
+
+    /// Returns the errors recorded while [Parser::accumulate_errors](#method.accumulate_errors)
+    /// is set, in the order they occurred.
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    /// Sets the source of environment variables consulted by the parser, which is
+    /// `std::env::var` by default. This allows features reading the environment (e.g.
+    /// the pager used by [Parser::print_help_paged](#method.print_help_paged)) to be
+    /// tested deterministically without modifying the process environment.
+    pub fn env_source<F: Fn(&str) -> Option<String> + 'static>(&mut self, source: F)
+        -> &mut Parser<'args>
+    {
+        self.env = Box::new(source);
+        self
+    }
+
+    // looks up an environment variable through the configured source
+    fn env_var(&self, key: &str) -> Option<String> {
+        (self.env)(key)
+    }
+
+    /// Loads default values from the config file named by the environment variable
+    /// `env_key` (e.g. `MYAPP_CONFIG`). Values are keyed by long name and are used by
+    /// args (see [Parser::arg](#method.arg)) which are not given on the command line,
+    /// making the config the lowest precedence source. Values taken from the config are
+    /// reported by [Parser::matched_options](#method.matched_options) as if given.
+    ///
+    /// If the variable is not set, no config is loaded. A leading `~` and any `$VAR` or
+    /// `${VAR}` in the path are expanded using the environment. A file which cannot be
+    /// read or parsed results in [Error::ConfigFile](enum.Error.html#variant.ConfigFile).
+    ///
+    /// This must be called before declaring any arguments.
+    pub fn config_from_env(&mut self, env_key: &str, format: ConfigFormat)
+        -> Result<&mut Parser<'args>, Error>
+    {
+        let path = match self.env_var(env_key) {
+            Some(p) if !p.is_empty() => { self.expand_path(&p) }
+            _ => { return Ok(self); }
+        };
+
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| Error::ConfigFile(path.clone(), format!("{}", e)))?;
+        self.config = parse_config(&path, &contents, format)?;
+        Ok(self)
+    }
+
+    // expands a leading `~` to the home directory, and `$VAR` or `${VAR}` to the value of
+    // the environment variable (or nothing if it is not set)
+    fn expand_path(&self, path: &str) -> String {
+        let path = match path.strip_prefix('~') {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+                format!("$HOME{}", rest)
+            }
+            _ => { path.to_string() }
+        };
+
+        let mut result = String::new();
+        let mut chars = path.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                result.push(c);
+                continue;
+            }
+
+            let braced = chars.next_if_eq(&'{').is_some();
+            let mut name = String::new();
+            while let Some(n) = chars.next_if(|n| n.is_ascii_alphanumeric() || (*n == '_')) {
+                name.push(n);
+            }
+            if braced && chars.next_if_eq(&'}').is_none() {
+                // not a well-formed reference, so keep it as is
+                result.push_str("${");
+                result.push_str(&name);
+                continue;
+            }
+
+            if name.is_empty() {
+                result.push('$');
+            } else {
+                result.push_str(&self.env_var(&name).unwrap_or_default());
+            }
+        }
+        result
     }
 
 
@@ -479,13 +2090,210 @@ impl Parser {
     // parse helpers
     //----------------------------------------------------------------
 
-    /// Closes a context opened by calling [Parser::group](#method.group) or
+    /// Finishes parsing, reporting any errors which were deferred until all arguments
+    /// were declared. This should be called after all arguments have been declared.
+    ///
+    /// When [Parser::collect_required_errors](#method.collect_required_errors) is set,
+    /// all required args and positionals which were not given are returned as a single
+    /// [Error::MissingArguments](enum.Error.html#variant.MissingArguments).
+    ///
+    /// When a handler was given to [Parser::on_unknown](#method.on_unknown), it is then
+    /// called for each unmatched argument.
+    pub fn finish(&mut self) -> Result<&mut Parser<'args>, Error> {
+        if !self.missing.is_empty() {
+            return Err(Error::MissingArguments(self.missing.clone()));
+        }
+
+        if let Some(mut handler) = self.on_unknown.take() {
+            let result = self.handle_unknown(&mut handler);
+            self.on_unknown = Some(handler);
+            result?;
+        }
+        Ok(self)
+    }
+
+    // passes each unmatched arg, left to right, to the handler and applies its action
+    fn handle_unknown(&mut self, handler: &mut UnknownHandler) -> Result<(), Error> {
+        if self.wants_help() {
+            return Ok(());
+        }
+
+        for (idx, offset, unused) in self.unused_at() {
+            match handler(&unused) {
+                UnknownAction::Ignore => {}
+                UnknownAction::Error => {
+                    let suggestion = if self.suggest_on_error {
+                        self.suggest_long(&unused.arg)
+                    } else {
+                        None
+                    };
+                    return Err(Error::UnexpectedArgument(unused.arg, suggestion));
+                }
+                UnknownAction::Consume => {
+                    if let Some(off) = offset {
+                        let runmask = self.run_mask(idx);
+                        runmask.remove(off);
+                        if !runmask.is_empty() {
+                            continue;
+                        }
+                    }
+                    self.mask.remove(idx);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the declarations made so far for structural mistakes, returning all of them
+    /// at once as [Error::InvalidTree](enum.Error.html#variant.InvalidTree). This is meant
+    /// to be called at the end of setup during development and checks that:
+    ///
+    /// * no short or long code is declared twice within the same scope
+    /// * no positional is declared after a variadic positional
+    /// * no required positional is declared after an optional positional
+    /// * every subcommand and group has been closed with [Parser::done](#method.done)
+    ///
+    /// Declarations within subcommands which were not given are checked as well.
+    pub fn validate_tree(&mut self) -> Result<&mut Parser<'args>, Error> {
+        let mut problems = self.tree.problems();
+        if let Some(name) = self.curr_group.or(self.curr_pos_group) {
+            problems.push(format!("group {} not closed with done()", name));
+        }
+
+        if problems.is_empty() {
+            return Ok(self);
+        }
+        Err(Error::InvalidTree(problems))
+    }
+
+    /// Warnings about likely mistakes in the declarations which do not prevent parsing.
+    /// Currently, a warning is emitted when an option declared within a subcommand shares
+    /// a short or long code with a top-level option (including the built-in help flag),
+    /// as the top-level option claims the arg first.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Requires that the options with the given long names are either all given or none
+    /// are given, such as a certificate and key pair (`&["tls-cert", "tls-key"]`). When
+    /// only some are given,
+    /// [Error::IncompleteGroup](enum.Error.html#variant.IncompleteGroup) lists the
+    /// ones which are missing.
+    ///
+    /// This should be called after the options have been declared. No error is returned
+    /// when help was requested.
+    pub fn all_or_none(&mut self, longs: &[&'static str]) -> Result<&mut Parser<'args>, Error> {
+        if self.wants_help() {
+            return Ok(self);
+        }
+
+        let (given, missing): (Vec<&'static str>, Vec<&'static str>) = longs.iter()
+            .partition(|l| self.matched.iter().any(|m| m.long == **l));
+        if given.is_empty() || missing.is_empty() {
+            return Ok(self);
+        }
+        Err(Error::IncompleteGroup(missing.iter().map(|l| format!("--{}", l)).collect()))
+    }
+
+    /// Requires that at most one of the options with the given long names is given, such
+    /// as output formats (`&["json", "yaml"]`). When more than one is given,
+    /// [Error::MutuallyExclusive](enum.Error.html#variant.MutuallyExclusive) lists the
+    /// ones which were given.
+    ///
+    /// This should be called after the options have been declared, and before any which
+    /// should not be constructed when the check fails. No error is returned when help was
+    /// requested.
+    pub fn exclusive_group(&mut self, longs: &[&'static str]) -> Result<&mut Parser<'args>, Error> {
+        if self.wants_help() {
+            return Ok(self);
+        }
+
+        let given = longs.iter()
+            .filter(|l| self.matched.iter().any(|m| m.long == **l))
+            .copied()
+            .collect::<Vec<&'static str>>();
+        if given.len() <= 1 {
+            return Ok(self);
+        }
+        Err(Error::MutuallyExclusive(given))
+    }
+
+    /// Requires that exactly one of the options with the given long names is given, such
+    /// as input sources (`&["stdin", "file", "url"]`). When none are given,
+    /// [Error::MissingOneOf](enum.Error.html#variant.MissingOneOf) lists all of them, and
+    /// when more than one is given,
+    /// [Error::MutuallyExclusive](enum.Error.html#variant.MutuallyExclusive) lists the
+    /// ones which were given. The usage line shows them together as
+    /// `( --stdin | --file FILE | --url URL )`.
+    ///
+    /// This should be called after the options have been declared. Within a subcommand
+    /// which was not given this is a no-op, and no error is returned when help was requested.
+    pub fn required_one_of(&mut self, longs: &[&'static str]) -> Result<&mut Parser<'args>, Error> {
+        if self.should_ignore(ItemType::Argument) { return Ok(self); }
+        if self.wants_help() {
+            self.printer.add_one_of(longs);
+            return Ok(self);
+        }
+
+        let given = longs.iter()
+            .filter(|l| self.matched.iter().any(|m| m.long == **l))
+            .copied()
+            .collect::<Vec<&'static str>>();
+        match given.len() {
+            0 => { Err(Error::MissingOneOf(longs.to_vec())) }
+            1 => { Ok(self) }
+            _ => { Err(Error::MutuallyExclusive(given)) }
+        }
+    }
+
+    // the constructed value, or None when the construction failed and errors are being
+    // accumulated, in which case the target is left untouched
+    fn constructed<T>(&mut self, res: Result<T, Error>) -> Result<Option<T>, Error> {
+        match res {
+            Ok(v) => { Ok(Some(v)) }
+            Err(e) if self.accumulate => {
+                self.errors.push(e);
+                Ok(None)
+            }
+            Err(e) => { Err(e) }
+        }
+    }
+
+    // reports a required arg or positional was not given. the error is returned unless
+    // required errors are being collected, in which case it is deferred until finish(),
+    // or the version was requested, in which case nothing is required
+    fn missing(&mut self, err: Error) -> Result<(), Error> {
+        if self.wants_version() {
+            return Ok(());
+        }
+        if self.accumulate {
+            self.errors.push(err);
+            return Ok(());
+        }
+        if !self.collect_missing {
+            return Err(err);
+        }
+        match err {
+            Error::MissingArgument(name) | Error::MissingPositional(name) => {
+                self.missing.push(name);
+                Ok(())
+            }
+            _ => { Err(err) }
+        }
+    }
+
+    /// Closes a context opened by calling [Parser::group](#method.group),
+    /// [Parser::positional_group](#method.positional_group), or
     /// [Parser::subcommand](#method.subcommand).
-    pub fn done(&mut self) -> Result<&mut Parser, Error> {
+    pub fn done(&mut self) -> Result<&mut Parser<'args>, Error> {
         if self.curr_group.is_some() {
             self.curr_group = None;
             return Ok(self);
         }
+        if self.curr_pos_group.is_some() {
+            self.curr_pos_group = None;
+            return Ok(self);
+        }
 
         if self.walk_depth == 0 {
             return Err(Error::InvalidState("call to done() at top-level"));
@@ -495,10 +2303,26 @@ impl Parser {
             self.parse_done = true;
         }
         self.walk_depth -= 1;
+        self.tree.leave();
 
         Ok(self)
     }
 
+    /// Hides the option declared just before from the help dialog and usage line, for
+    /// internal or experimental options (e.g. `.flag(...)?.hidden()`). The option is
+    /// still parsed as usual, but is left out of shell completions and suggestions for
+    /// mistyped args. As abbreviations are matched while declaring, options declared
+    /// after a hidden option do not consider it when matching an abbreviation.
+    ///
+    /// Only options may be hidden. Following a positional or subcommand, this has no
+    /// effect.
+    pub fn hidden(&mut self) -> &mut Parser<'args> {
+        if self.tree.hide_last() {
+            self.printer.hide_last();
+        }
+        self
+    }
+
     fn should_ignore(&self, item: ItemType) -> bool {
         if self.parse_done {
             return true;
@@ -530,37 +2354,33 @@ impl Parser {
 
     fn handle_run(&mut self, idx: usize, short: char, expect_value: bool) -> MatchResult {
         let arg = &self.args[idx];
+        if !arg.contains(short) {
+            // no matches here
+            return Ok(None);
+        }
         if expect_value && !arg.ends_with(short) {
-            return Err(Error::ValuedArgInRun(short, arg.clone()));
+            // a number ending the run is the value of the short code before it
+            return match self.run_suffix(idx) {
+                Some((off, start)) if arg[off..].starts_with(short) => {
+                    if !self.claim_run_suffix(idx, off) {
+                        return Ok(None);
+                    }
+                    Ok(Some(FoundMatch::new(idx, 1, ValueLocation::Attached(start))))
+                }
+                _ => { Err(Error::ValuedArgInRun(short, arg.to_string())) }
+            };
         }
 
         let matches = arg.match_indices(short).map(|(i,_)| i).collect::<Vec<usize>>();
-        if matches.is_empty() {
-            // no matches here
-            return Ok(None);
-        }
 
-        // fetch the current mask for this run, or insert a new one
-        let runmask = match self.run_masks.get_mut(&idx) {
-            Some(mutref) => {
-                mutref
-            }
-            None => {
-                let mut bits = bit_set::BitSet::with_capacity(arg.len());
-                for i in 1..arg.len() { // skip 0, because we want to skip the leading '-'
-                    bits.insert(i);
-                }
-                self.run_masks.insert(idx, bits);
-                self.run_masks.get_mut(&idx).expect("failed to insert run mask")
-            }
-        };
+        let runmask = self.run_mask(idx);
         if runmask.is_empty() {
             return Ok(None);
         }
 
         let mut count: usize = 0;
         for i in matches.iter() {
-            if runmask.contains(*i) == false { continue; }
+            if !runmask.contains(*i) { continue; }
 
             runmask.remove(*i);
             count += 1;
@@ -583,8 +2403,82 @@ impl Parser {
         )))
     }
 
+    // fetches the mask of unused offsets for the run at `idx`, or inserts a new one
+    fn run_mask(&mut self, idx: usize) -> &mut bit_set::BitSet {
+        let len = self.args[idx].len();
+        self.run_masks.entry(idx).or_insert_with(|| {
+            let mut bits = bit_set::BitSet::with_capacity(len);
+            for i in 1..len { // skip 0, because we want to skip the leading '-'
+                bits.insert(i);
+            }
+            bits
+        })
+    }
+
+    // locates a number ending a run of short codes, which belongs to the short code just
+    // before it (e.g. `-vvn5` is `n` with `5`). returns the offsets of that short code and
+    // of the number. a number containing declared short codes is part of the run instead
+    fn run_suffix(&self, idx: usize) -> Option<(usize, usize)> {
+        let arg: &str = &self.args[idx];
+        if self.long_only || !arg.starts_with('-') || arg.starts_with("--") || arg.contains('=') {
+            return None;
+        }
+        if self.is_negative_number(arg) {
+            return None;
+        }
+
+        let start = arg.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+        if (start == arg.len()) || arg[start..].chars().any(|c| self.shorts.contains(&c)) {
+            return None;
+        }
+        match arg[..start].chars().last() {
+            Some('-') | None => { None }
+            Some(c) => { Some((start - c.len_utf8(), start)) }
+        }
+    }
+
+    // claims the number ending a run along with the short code it belongs to. returns
+    // false if the short code was already claimed
+    fn claim_run_suffix(&mut self, idx: usize, off: usize) -> bool {
+        let len = self.args[idx].len();
+        let runmask = self.run_mask(idx);
+        if !runmask.contains(off) {
+            return false;
+        }
+        for i in off..len {
+            runmask.remove(i);
+        }
+
+        // when we empty a runmask, we set the "parent" index to be fully used
+        if runmask.is_empty() {
+            self.mask.remove(idx);
+        }
+        true
+    }
+
+    // whether the arg is a number (e.g. `-5` or `-2.5`) to be left for positionals and
+    // values. only when negative numbers are allowed and the leading digit is not a
+    // declared short code
+    fn is_negative_number(&self, arg: &str) -> bool {
+        if !self.negative_numbers {
+            return false;
+        }
+        let num = match arg.strip_prefix('-') {
+            Some(n) => { n }
+            None => { return false; }
+        };
+
+        let is_digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+        let valid = match num.split_once('.') {
+            Some((whole, frac)) => { is_digits(whole) && is_digits(frac) }
+            None => { is_digits(num) }
+        };
+        valid && !num.chars().next().is_some_and(|c| self.shorts.contains(&c))
+    }
+
     fn matches_short(&mut self, idx: usize, short: char, expect_value: bool) -> MatchResult {
-        if short == '\0' { return Ok(None); } // no match
+        if (short == '\0') || self.long_only { return Ok(None); } // no match
+        if self.is_negative_number(&self.args[idx]) { return Ok(None); }
 
         let arg = &self.args[idx];
         if arg.len() < 2 {
@@ -597,15 +2491,13 @@ impl Parser {
 
 
         let mut chars = arg.chars();
-        let arg_0 = chars.next().or(Some('\0')).unwrap();
-        let arg_1 = chars.next().or(Some('\0')).unwrap();
-        let arg_2 = chars.next().or(Some('\0')).unwrap();
+        let arg_0 = chars.next().unwrap_or('\0');
+        let arg_1 = chars.next().unwrap_or('\0');
+        let arg_2 = chars.next().unwrap_or('\0');
 
         // expect arg[0] to be '-'  -- otherwise, looks like a positional
         // also expect arg[1] NOT to be '-'  -- otherwise, looks like a long
-        if arg_0 != '-' {
-            return Ok(None);
-        } else if arg_1 == '-' {
+        if (arg_0 != '-') || (arg_1 == '-') {
             return Ok(None);
         }
 
@@ -636,6 +2528,12 @@ impl Parser {
             return Ok(Some(FoundMatch::new(idx, 0, ValueLocation::HasEqual(2))));
         }
 
+        // a valued arg takes the rest of the arg as its value (e.g. `-ffoo`), as the value
+        // could not otherwise be the last character of a run
+        if expect_value {
+            return Ok(Some(FoundMatch::new(idx, 0, ValueLocation::Attached(1 + short.len_utf8()))));
+        }
+
         // we know the arg has len>=3, arg[2] != '=', so it must be a run
         self.handle_run(idx, short, expect_value)
     }
@@ -643,12 +2541,12 @@ impl Parser {
     fn matches_long(&self, idx: usize, long: &'static str, expect_value: bool) -> MatchResult {
         if long.is_empty() { return Ok(None); }
 
-        let arg = self.args[idx].as_str();
+        let arg: &str = &self.args[idx];
         let end_of_arg = 2 + long.len();
 
-        // not enough string to match
+        // not enough string to match, unless abbreviated
         if arg.len() < end_of_arg {
-            return Ok(None);
+            return self.matches_abbreviation(idx, long, expect_value);
         }
 
         // not a long arg
@@ -657,7 +2555,7 @@ impl Parser {
         }
 
         if &arg[2..end_of_arg] != long {
-            return Ok(None);
+            return self.matches_abbreviation(idx, long, expect_value);
         }
 
         // we got exactly what we were looking for, so return
@@ -684,15 +2582,71 @@ impl Parser {
         }
 
         // otherwise, no match
-        Ok(None)
+        self.matches_abbreviation(idx, long, expect_value)
+    }
+
+    // matches a long arg given as a strict prefix of the long name, when abbreviations
+    // are allowed and no other long name in scope shares the prefix
+    fn matches_abbreviation(&self, idx: usize, long: &'static str, expect_value: bool)
+        -> MatchResult
+    {
+        if !self.abbreviations { return Ok(None); }
+
+        let given = match self.args[idx].strip_prefix("--") {
+            Some(g) => { g }
+            None => { return Ok(None); }
+        };
+        let (token, equal_at) = match given.find('=') {
+            Some(i) => { (&given[..i], Some(2 + i)) }
+            None => { (given, None) }
+        };
+        if token.is_empty() || (token.len() >= long.len()) || !long.starts_with(token) {
+            return Ok(None);
+        }
+
+        let candidates = self.tree.longs_starting_with(token);
+        // an exact long name always wins, and is matched by its own declaration
+        if candidates.contains(&token) {
+            return Ok(None);
+        }
+        if candidates.len() > 1 {
+            return Err(Error::AmbiguousLong(token.to_string(), candidates));
+        }
+
+        let location = match equal_at {
+            Some(end_of_arg) => { ValueLocation::HasEqual(end_of_arg) }
+            None if expect_value && self.mask.contains(idx + 1) => { ValueLocation::TakesNext }
+            None => { ValueLocation::Unknown }
+        };
+        Ok(Some(FoundMatch::new(idx, 0, location)))
+    }
+
+    // fails when an abbreviation already matched to another long name is also a prefix
+    // of the one being declared, as it would have been ambiguous had both been known
+    fn check_abbreviated(&self, long: &'static str) -> Result<(), Error> {
+        let clash = self.abbreviated.iter()
+            .find(|(token, matched)| (*matched != long) && long.starts_with(token.as_str()));
+        match clash {
+            Some((token, matched)) => {
+                Err(Error::AmbiguousLong(token.clone(), vec!(matched, long)))
+            }
+            None => { Ok(()) }
+        }
     }
 
     fn find_match(&mut self, short: char, long: &'static str, expect_value: bool)
         -> MatchResult
     {
-        let mask_view = self.mask.iter().collect::<Vec<usize>>();
-        for i in mask_view.iter() {
-            match self.matches_short(*i, short, expect_value) {
+        if self.abbreviations && !long.is_empty() {
+            self.check_abbreviated(long)?;
+        }
+
+        // walk the mask by index rather than collecting it, as matching needs `&mut self`
+        let end = self.candidates_end();
+        let mut next = 0;
+        while let Some(i) = (next..end).find(|i| self.mask.contains(*i)) {
+            next = i + 1;
+            match self.matches_short(i, short, expect_value) {
                 Ok(Some(mat)) => {
                     return Ok(Some(mat));
                 }
@@ -700,8 +2654,12 @@ impl Parser {
                 Err(e) => { return Err(e); }
             }
 
-            match self.matches_long(*i, long, expect_value) {
+            match self.matches_long(i, long, expect_value) {
                 Ok(Some(mat)) => {
+                    let given = self.args[i][2..].split('=').next().unwrap_or("");
+                    if given != long {
+                        self.abbreviated.push((given.to_string(), long));
+                    }
                     return Ok(Some(mat));
                 }
                 Ok(None) => {} // no match, so ignore
@@ -711,45 +2669,98 @@ impl Parser {
         Ok(None)
     }
 
-    fn find_subcommand(&self, name: &'static str) -> Option<FoundMatch> {
+    // the index past the last arg which may be matched as an option. in POSIX-strict mode
+    // this is the first non-option, otherwise the arg-stop sentinel (or the end)
+    fn candidates_end(&self) -> usize {
+        let boundary = if self.posix_strict { self.first_non_option() } else { None };
+        boundary.or(self.argstop).unwrap_or(self.args.len())
+    }
+
+    // the unused args which may be matched as options
+    fn option_candidates(&self) -> impl Iterator<Item = usize> + '_ {
+        let end = self.candidates_end();
+        self.mask.iter().take_while(move |i| *i < end)
+    }
+
+    // the index of the first unused arg which is not an option. a non-option directly
+    // following an unused option (without an `=`) is skipped, as it may yet be claimed
+    // as the value of that option once declared
+    fn first_non_option(&self) -> Option<usize> {
+        let mut prev_option: Option<usize> = None;
         for i in self.mask.iter() {
-            let arg = &self.args[i];
-            if arg == name {
-                return Some(FoundMatch::new(i, 0, ValueLocation::Unknown));
+            if self.looks_like_arg(i) {
+                prev_option = Some(i);
+                continue;
+            }
+
+            let maybe_value = (prev_option == Some(i - 1)) && !self.args[i - 1].contains('=');
+            if !maybe_value {
+                return Some(i);
             }
         }
         None
     }
 
-    // takes index of the arg that matched, not the value to be constructed
-    fn construct_arg<T: FromStr>(&mut self,
+    // the left-most unused arg equal to the subcommand name. the args are sorted once so
+    // each subcommand is a binary search rather than a scan of every arg
+    fn find_subcommand(&mut self, name: &'static str) -> Option<FoundMatch> {
+        let args = &self.args;
+        let end = self.argstop.unwrap_or(args.len());
+        let sorted = self.by_value.get_or_insert_with(|| {
+            let mut sorted = (1..end).collect::<Vec<usize>>();
+            // stable, so equal args remain in order of appearance
+            sorted.sort_by(|a, b| args[*a].cmp(&args[*b]));
+            sorted
+        });
+
+        let mask = &self.mask;
+        let start = sorted.partition_point(|i| *args[*i] < *name);
+        sorted[start..].iter()
+            .take_while(|i| args[**i] == name)
+            .find(|i| mask.contains(**i))
+            .map(|i| FoundMatch::new(*i, 0, ValueLocation::Unknown))
+    }
+
+    fn looks_like_arg(&self, idx: usize) -> bool {
+        self.looks_like(&self.args[idx]) != LooksLike::Positional
+    }
+
+    // takes index of the arg that matched, not the value to be constructed.
+    // claims the value (when it is the next arg) and returns the raw value string
+    fn take_value(&mut self,
         info: &FoundMatch,
-        short: char, long: &'static str,
-        into: &mut T
-    ) -> Result<(), Error>
-        where <T as FromStr>::Err: std::fmt::Display
+        short: char, long: &'static str
+    ) -> Result<Cow<'args, str>, Error>
     {
         match info.value {
             ValueLocation::Unknown => {
                 Err(Error::MissingArgValue(short, long))
             }
             ValueLocation::TakesNext => {
-                if self.mask.contains(info.index + 1) == false {
+                if !self.mask.contains(info.index + 1) {
+                    return Err(Error::MissingArgValue(short, long));
+                }
+                if self.strict_values && self.looks_like_arg(info.index + 1) {
                     return Err(Error::MissingArgValue(short, long));
                 }
                 self.mask.remove(info.index + 1); // mark the argument index as having been used/claimed
-                let val = &self.args[info.index + 1];
-                *into = T::from_str(val.as_str())
-                    .map_err(|e| Error::ConstructionError(short, long, format!("{}", e)))?;
-                Ok(())
+                Ok(self.args[info.index + 1].clone())
             }
             ValueLocation::HasEqual(off) => {
-                let val = &self.args[info.index][(off+1)..];
                 // TODO: val.len() > 1 check + error
-                *into = T::from_str(val)
-                    .map_err(|e| Error::ConstructionError(short, long, format!("{}", e)))?;
-                Ok(())
+                Ok(self.arg_from(info.index, off + 1))
             }
+            ValueLocation::Attached(off) => {
+                Ok(self.arg_from(info.index, off))
+            }
+        }
+    }
+
+    // the remainder of an arg following a byte offset, borrowed when the arg is
+    fn arg_from(&self, idx: usize, off: usize) -> Cow<'args, str> {
+        match &self.args[idx] {
+            Cow::Borrowed(a) => { Cow::Borrowed(&a[off..]) }
+            Cow::Owned(a) => { Cow::Owned(a[off..].to_string()) }
         }
     }
 
@@ -760,7 +2771,9 @@ impl Parser {
 
     /// Registers a long and short code which are expected to be followed by a value.
     /// The associated value can be separated by either a space or an equal sign
-    /// (e.g. `--foo=7` or `--foo 7`).
+    /// (e.g. `--foo=7` or `--foo 7`). The value may also be attached directly to the
+    /// short code (e.g. `-f7`), unless an earlier declaration matched the characters
+    /// following it as a run of short codes.
     ///
     /// The type you wish to be parse the arg value into must implement `From<String>`
     /// for construction as well as `ToString` for printing defaults in the help dialog.
@@ -774,36 +2787,508 @@ impl Parser {
     pub fn arg<'a, T: FromStr+ToString>(&'a mut self,
         short: char, long: &'static str, desc: &'static str,
         into: &mut T, label: Option<&'static str>, required: bool
-    ) -> Result<&'a mut Parser, Error>
+    ) -> Result<&'a mut Parser<'args>, Error>
         where <T as FromStr>::Err: std::fmt::Display
     {
+        self.arg_parsed(short, long, desc, into, label, required,
+            T::from_str)
+    }
 
-        if self.should_ignore(ItemType::Argument) { return Ok(self); }
+    /// Same as [Parser::arg](#method.arg), but the error returned by `FromStr` is kept.
+    /// A failed construction results in an
+    /// [Error::WithSource](enum.Error.html#variant.WithSource) wrapping the
+    /// [Error::ConstructionError](enum.Error.html#variant.ConstructionError), whose
+    /// `source()` is the original error.
+    pub fn arg_typed<'a, T: FromStr+ToString>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut T, label: Option<&'static str>, required: bool
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: Into<BoxedError>
+    {
+        let default = into.to_string();
+        self.arg_parsed_impl(short, long, desc, into, label, required, Some(default),
+            construct_typed)
+    }
+
+    /// Same as [Parser::arg](#method.arg), but includes a hint for how shells should
+    /// complete the value (e.g. [Completion::File](enum.Completion.html#variant.File)),
+    /// which is used by [Parser::generate_bash_completion](#method.generate_bash_completion)
+    /// and [Parser::generate_zsh_completion](#method.generate_zsh_completion).
+    #[allow(clippy::too_many_arguments)]
+    pub fn arg_completed<'a, T: FromStr+ToString>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut T, label: Option<&'static str>, required: bool, complete: Completion
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: std::fmt::Display
+    {
+        let short = self.short_code(short, long, desc, Takes::Completed(complete));
+        let help = || printer::Argument::new(
+            short, long, desc,
+            label, Some(into.to_string()), required
+        ).with_completion(complete);
+        if let Some(val) = self.find_arg_value(short, long, required, None, help)? {
+            let res = construct(&val).map_err(|e| Error::construction(short, long, e));
+            if let Some(v) = self.constructed(res)? {
+                *into = v;
+                self.record_match(short, long, Some(val));
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Same as [Parser::arg](#method.arg), but the value must exactly match one of
+    /// `choices` (e.g. `&["trace", "debug", "info", "warn", "error"]`), which is checked
+    /// before construction. Other values result in a
+    /// [Error::InvalidChoice](enum.Error.html#variant.InvalidChoice) listing the choices.
+    /// The help dialog lists the choices following the description.
+    #[allow(clippy::too_many_arguments)]
+    pub fn arg_choices<'a, T: FromStr+ToString>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut T, label: Option<&'static str>, required: bool,
+        choices: &'static [&'static str]
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: std::fmt::Display
+    {
+        let short = self.short_code(short, long, desc, Takes::Completed(Completion::Words(choices)));
+        let help = || printer::Argument::new(
+            short, long, desc,
+            label, Some(into.to_string()), required
+        ).with_choices(choices);
+        if let Some(val) = self.find_arg_value(short, long, required, None, help)? {
+            if !is_choice(&val, choices) {
+                return Err(Error::InvalidChoice(short, long, val.into_owned(), choices));
+            }
+            let res = construct(&val).map_err(|e| Error::construction(short, long, e));
+            if let Some(v) = self.constructed(res)? {
+                *into = v;
+                self.record_match(short, long, Some(val));
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Same as [Parser::arg](#method.arg), but the constructed value must be within the
+    /// inclusive bounds `min..=max` (e.g. `--threads` within `1..=64`). Values outside of
+    /// the bounds result in a [Error::OutOfRange](enum.Error.html#variant.OutOfRange),
+    /// and `into` is left untouched. The help dialog shows the bounds following the
+    /// description (e.g. `[range: 1..=64]`).
+    #[allow(clippy::too_many_arguments)]
+    pub fn arg_range<'a, T: FromStr+ToString+PartialOrd>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut T, label: Option<&'static str>, required: bool,
+        min: T, max: T
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: std::fmt::Display
+    {
+        let range = format!("{}..={}", min.to_string(), max.to_string());
+        let short = self.short_code(short, long, desc, Takes::Value);
+        let help = || printer::Argument::new(
+            short, long, desc,
+            label, Some(into.to_string()), required
+        ).with_range(range.clone());
+        if let Some(val) = self.find_arg_value(short, long, required, None, help)? {
+            let res = construct(&val).map_err(|e| Error::construction(short, long, e));
+            let parsed = match self.constructed(res)? {
+                Some(p) => { p }
+                None => { return Ok(self); }
+            };
+            // written so that incomparable values (e.g. NaN) are out of range
+            if !((min <= parsed) && (parsed <= max)) {
+                return Err(Error::OutOfRange(short, long,
+                    format!("'{}' is not within {}", val, range)));
+            }
+            *into = parsed;
+            self.record_match(short, long, Some(val));
+        }
+
+        Ok(self)
+    }
+
+    /// Same as [Parser::arg](#method.arg), but constructs into an `Option` so that an arg
+    /// which was not given (`None`) can be told apart from one given with a value equal to
+    /// the default (e.g. `--timeout 0`). `into` is set to `Some(value)` when the arg is
+    /// given and left untouched otherwise. The help dialog shows a default of `none` when
+    /// `into` is `None`.
+    pub fn opt_arg<'a, T: FromStr+ToString>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut Option<T>, label: Option<&'static str>, required: bool
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: std::fmt::Display
+    {
+        let short = self.short_code(short, long, desc, Takes::Value);
+        let shown = match into {
+            Some(v) => { v.to_string() }
+            None => { "none".to_string() }
+        };
+        let help = || printer::Argument::new(short, long, desc, label, Some(shown), required);
+        if let Some(val) = self.find_arg_value(short, long, required, None, help)? {
+            let res = construct(&val).map_err(|e| Error::construction(short, long, e));
+            if let Some(v) = self.constructed(res)? {
+                *into = Some(v);
+                self.record_match(short, long, Some(val));
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Same as [Parser::arg](#method.arg), but `into` is set to `default` when the arg is
+    /// not given. The help dialog shows `default` rather than whatever `into` holds
+    /// beforehand, separating the documented default from the initial value of the
+    /// target (e.g. a `String` initialized empty still shows `[default: out.txt]`).
+    /// When [accumulating errors](#method.accumulate_errors), a value given which fails
+    /// to construct leaves `into` untouched rather than setting the default.
+    pub fn arg_default<'a, T: FromStr+ToString>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut T, label: Option<&'static str>, default: T
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: std::fmt::Display
+    {
+        let short = self.short_code(short, long, desc, Takes::Value);
+        let shown = default.to_string();
+        let help = || printer::Argument::new(short, long, desc, label, Some(shown), false);
+        match self.find_arg_value(short, long, false, None, help)? {
+            Some(val) => {
+                let res = construct(&val).map_err(|e| Error::construction(short, long, e));
+                if let Some(v) = self.constructed(res)? {
+                    *into = v;
+                    self.record_match(short, long, Some(val));
+                }
+            }
+            None => { *into = default; }
+        }
+
+        Ok(self)
+    }
+
+    /// Same as [Parser::arg](#method.arg), but falls back to the environment variable `env`
+    /// when the arg is not given (e.g. `--database-url` falling back to `$DATABASE_URL`).
+    /// The command line takes precedence over the environment, which takes precedence
+    /// over a config file (see [Parser::config_from_env](#method.config_from_env)) and the
+    /// current value of `into`. A required arg is satisfied by the environment variable.
+    ///
+    /// The help dialog notes the variable (e.g. `[env: DATABASE_URL]`). A value from the
+    /// environment which cannot be constructed results in a
+    /// [Error::ConstructionError](enum.Error.html#variant.ConstructionError), just as one
+    /// given on the command line.
+    #[allow(clippy::too_many_arguments)]
+    pub fn arg_env<'a, T: FromStr+ToString>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut T, label: Option<&'static str>, required: bool, env: &'static str
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: std::fmt::Display
+    {
+        let short = self.short_code(short, long, desc, Takes::Value);
+        let help = || printer::Argument::new(
+            short, long, desc,
+            label, Some(into.to_string()), required
+        ).with_env(env);
+        if let Some(val) = self.find_arg_value(short, long, required, Some(env), help)? {
+            let res = construct(&val).map_err(|e| Error::construction(short, long, e));
+            if let Some(v) = self.constructed(res)? {
+                *into = v;
+                self.record_match(short, long, Some(val));
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Same as [Parser::arg](#method.arg), but the value is converted to lowercase
+    /// before construction. For instance, `--format JSON` constructs the target from `json`.
+    pub fn arg_lowercase<'a, T: FromStr+ToString>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut T, label: Option<&'static str>, required: bool
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: std::fmt::Display
+    {
+        self.arg_parsed(short, long, desc, into, label, required,
+            |v| T::from_str(v.to_lowercase().as_str()))
+    }
+
+    /// Same as [Parser::arg](#method.arg), but the value is converted to uppercase
+    /// before construction. For instance, `--level warn` constructs the target from `WARN`.
+    pub fn arg_uppercase<'a, T: FromStr+ToString>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut T, label: Option<&'static str>, required: bool
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: std::fmt::Display
+    {
+        self.arg_parsed(short, long, desc, into, label, required,
+            |v| T::from_str(v.to_uppercase().as_str()))
+    }
+
+    /// Same as [Parser::arg](#method.arg), but constructs a socket address. Malformed
+    /// values produce an error describing the expected `IP:PORT` form rather than the
+    /// terse message of the underlying parser.
+    pub fn addr_arg<'a>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut std::net::SocketAddr, label: Option<&'static str>, required: bool
+    ) -> Result<&'a mut Parser<'args>, Error>
+    {
+        self.arg_parsed(short, long, desc, into, label, required, |v| {
+            std::net::SocketAddr::from_str(v).map_err(|e| format!(
+                "expected IP:PORT like 127.0.0.1:8080 or [::1]:8080, got '{}' ({})", v, e
+            ))
+        })
+    }
+
+    /// Same as [Parser::arg](#method.arg), but constructs an IP address (v4 or v6).
+    /// Malformed values produce an error describing the expected form rather than the
+    /// terse message of the underlying parser.
+    pub fn ip_arg<'a>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut std::net::IpAddr, label: Option<&'static str>, required: bool
+    ) -> Result<&'a mut Parser<'args>, Error>
+    {
+        self.arg_parsed(short, long, desc, into, label, required, |v| {
+            std::net::IpAddr::from_str(v).map_err(|e| format!(
+                "expected an IP address like 127.0.0.1 or ::1, got '{}' ({})", v, e
+            ))
+        })
+    }
+
+    /// Same as [Parser::arg](#method.arg), but constructs a fraction from a percentage
+    /// (e.g. `50%` is `0.5`) or a bare float (e.g. `0.75`). Values outside of `0.0..=1.0`
+    /// (e.g. `150%`) are rejected with a
+    /// [Error::ConstructionError](enum.Error.html#variant.ConstructionError).
+    pub fn percent_arg<'a>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut f64, label: Option<&'static str>, required: bool
+    ) -> Result<&'a mut Parser<'args>, Error>
+    {
+        self.arg_parsed(short, long, desc, into, label, required, |v| {
+            let parsed = match v.strip_suffix('%') {
+                Some(pct) => { f64::from_str(pct.trim()).map(|p| p / 100.0) }
+                None => { f64::from_str(v) }
+            };
+            let frac = parsed.map_err(|_| format!(
+                "expected a percentage like 50% or a fraction like 0.5, got '{}'", v
+            ))?;
+
+            if !(0.0..=1.0).contains(&frac) {
+                return Err(format!("'{}' is out of range, expected 0% to 100% (0.0 to 1.0)", v));
+            }
+            Ok(frac)
+        })
+    }
+
+    /// Same as [Parser::arg](#method.arg), but constructs a date using the given
+    /// [chrono format string](https://docs.rs/chrono/0.4/chrono/format/strftime/index.html)
+    /// (e.g. `%Y-%m-%d`). Malformed values produce an error describing the expected form
+    /// (e.g. "expected a date like YYYY-MM-DD").
+    ///
+    /// Requires the `chrono` feature.
+    #[cfg(feature = "chrono")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn date_arg<'a>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut chrono::NaiveDate, format: &'static str,
+        label: Option<&'static str>, required: bool
+    ) -> Result<&'a mut Parser<'args>, Error>
+    {
+        self.arg_parsed(short, long, desc, into, label, required, |v| {
+            chrono::NaiveDate::parse_from_str(v, format).map_err(|e| format!(
+                "expected a date like {}, got '{}' ({})", format_hint(format), v, e
+            ))
+        })
+    }
+
+    /// Same as [Parser::date_arg](#method.date_arg), but constructs a date and time
+    /// (e.g. with a format of `%Y-%m-%dT%H:%M:%S`).
+    ///
+    /// Requires the `chrono` feature.
+    #[cfg(feature = "chrono")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn datetime_arg<'a>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut chrono::NaiveDateTime, format: &'static str,
+        label: Option<&'static str>, required: bool
+    ) -> Result<&'a mut Parser<'args>, Error>
+    {
+        self.arg_parsed(short, long, desc, into, label, required, |v| {
+            chrono::NaiveDateTime::parse_from_str(v, format).map_err(|e| format!(
+                "expected a date and time like {}, got '{}' ({})", format_hint(format), v, e
+            ))
+        })
+    }
+
+    /// Same as [Parser::arg](#method.arg), but the value is constructed by `parse` rather
+    /// than `FromStr`. This allows parsing into types which do not implement `FromStr`
+    /// (e.g. a `std::time::Duration` from `90s`) without a wrapper type. An error returned
+    /// by `parse` results in a
+    /// [Error::ConstructionError](enum.Error.html#variant.ConstructionError) carrying the
+    /// message.
+    ///
+    /// As the target need not be printable, no default is shown in the help dialog.
+    #[allow(clippy::too_many_arguments)]
+    pub fn arg_with<'a, T, F: Fn(&str) -> Result<T, String>>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut T, label: Option<&'static str>, required: bool,
+        parse: F
+    ) -> Result<&'a mut Parser<'args>, Error>
+    {
+        self.arg_parsed_impl(short, long, desc, into, label, required, None,
+            |v| parse(v).map_err(Failure::Message))
+    }
+
+    // shared implementation for the arg family: locates the value for the arg and
+    // hands the raw string to `parse` for construction of the target
+    #[allow(clippy::too_many_arguments)]
+    fn arg_parsed<'a, T: ToString, E: std::fmt::Display, F: Fn(&str) -> Result<T, E>>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut T, label: Option<&'static str>, required: bool,
+        parse: F
+    ) -> Result<&'a mut Parser<'args>, Error>
+    {
+        let default = into.to_string();
+        self.arg_parsed_impl(short, long, desc, into, label, required, Some(default),
+            |v| parse(v).map_err(Failure::message))
+    }
+
+    // the same as arg_parsed, where `default` is what the help dialog shows as the default
+    #[allow(clippy::too_many_arguments)]
+    fn arg_parsed_impl<'a, T, F: Fn(&str) -> Result<T, Failure>>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut T, label: Option<&'static str>, required: bool,
+        default: Option<String>, parse: F
+    ) -> Result<&'a mut Parser<'args>, Error>
+    {
+        let short = self.short_code(short, long, desc, Takes::Value);
+        let help = || printer::Argument::new(short, long, desc, label, default, required);
+        if let Some(val) = self.find_arg_value(short, long, required, None, help)? {
+            let res = parse(&val).map_err(|e| Error::construction(short, long, e));
+            if let Some(v) = self.constructed(res)? {
+                *into = v;
+                self.record_match(short, long, Some(val));
+            }
+        }
+
+        Ok(self)
+    }
+
+    // tracks the short code of a declaration, assigning one from the long name if it
+    // has none and automatic short codes are enabled. the declaration is recorded in
+    // the tree along with what it takes as a value
+    fn short_code(&mut self,
+        short: char, long: &'static str, desc: &'static str, takes: Takes
+    ) -> char {
+        // only the declaration which follows may be hidden
+        self.printer.forget_last();
+        let short = if (short == '\0') && self.auto_shorts {
+            long.chars()
+                .find(|c| c.is_alphabetic() && !self.shorts.contains(c))
+                .unwrap_or('\0')
+        } else {
+            short
+        };
+
+        if short != '\0' {
+            self.shorts.insert(short);
+        }
+        if !self.should_ignore(ItemType::Argument) {
+            self.yield_version_codes(short, long);
+        }
+        if let Some(global) = self.tree.shadowed(short, long) {
+            let kind = if global == self.help_codes { "the built-in help flag" } else { "a global option" };
+            let warning = format!("{}: {} shadows {} ({})", self.tree.scope_name(),
+                arg_string(short, long, false), kind, arg_string(global.0, global.1, false));
+            self.warnings.push(warning);
+        }
+        self.tree.option(short, long, desc, takes);
+        short
+    }
+
+    // shared lookup for the arg family. when help is wanted the arg is registered with
+    // the printer and nothing is returned, otherwise the raw value string is located and
+    // claimed. an arg not given falls back to the environment variable `env` (if any), then
+    // the config. returns an error if the arg is required but not given
+    fn find_arg_value<F: FnOnce() -> printer::Argument>(&mut self,
+        short: char, long: &'static str, required: bool, env: Option<&'static str>, help: F
+    ) -> Result<Option<Cow<'args, str>>, Error>
+    {
+        if self.should_ignore(ItemType::Argument) { return Ok(None); }
 
         // only add help if it is wanted
         if self.wants_help() {
-            self.printer.add_arg(
-                printer::Argument::new(
-                    short, long, desc,
-                    label, Some(into.to_string()), required
-                ),
-                self.curr_group
-            )?;
-            return Ok(self);
+            self.printer.add_arg(help(), self.curr_group)?;
+            return Ok(None);
         }
 
         let found_opt = self.find_match(short, long, true)?;
         if found_opt.is_none() {
+            if let Some(val) = env.and_then(|e| self.env_var(e)) {
+                return Ok(Some(Cow::Owned(val)));
+            }
+            if let Some(val) = self.config.get(long) {
+                return Ok(Some(Cow::Owned(val.clone())));
+            }
+
             // only required if !help
             if required  && !self.wants_help() {
-                return Err(Error::MissingArgument(arg_string(short, long, false)));
+                self.missing(Error::MissingArgument(arg_string(short, long, false)))?;
             }
-            return Ok(self);
+            return Ok(None);
         }
 
         let found = found_opt.unwrap();
-        self.mask.remove(found.index);
-        self.construct_arg(&found, short, long, into)?;
+        if found.run_count == 0 { // was not part of a run, remove entire index
+            self.mask.remove(found.index);
+        }
+        let val = self.take_value(&found, short, long)?;
+
+        // the scope of a repeated subcommand is entered once for all occurrences, so a
+        // single value cannot hold what was given to each
+        if let Some((depth, sub)) = self.repeated_at {
+            if (depth == self.walk_depth) && self.find_match(short, long, true)?.is_some() {
+                return Err(Error::RepeatedValue(arg_string(short, long, false), sub));
+            }
+        }
+        Ok(Some(val))
+    }
+
+    /// Array arg parses a single delimited value into a fixed-length array. For example,
+    /// `--rgb 255,128,0` with a delimiter of `,` parses into a `[u8; 3]`.
+    ///
+    /// The value must split into exactly `N` parts, otherwise
+    /// [Error::WrongArity](enum.Error.html#variant.WrongArity) is returned. Each part is
+    /// constructed just as in [Parser::arg](#method.arg), and the default printed in the
+    /// help dialog is the current contents of the array joined by the delimiter.
+    #[allow(clippy::too_many_arguments)]
+    pub fn array_arg<'a, T: FromStr + ToString, const N: usize>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut [T; N], delim: char, label: Option<&'static str>, required: bool
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: std::fmt::Display
+    {
+        let short = self.short_code(short, long, desc, Takes::Value);
+        let help = || printer::Argument::new(
+            short, long, desc, label,
+            Some(into.iter().map(|v| v.to_string()).collect::<Vec<String>>()
+                .join(&delim.to_string())),
+            required
+        );
+        let val = match self.find_arg_value(short, long, required, None, help)? {
+            Some(v) => { v }
+            None => { return Ok(self); }
+        };
+
+        let parts = val.split(delim).collect::<Vec<&str>>();
+        if parts.len() != N {
+            return Err(Error::WrongArity(long, N, parts.len()));
+        }
+
+        let res = parts.iter()
+            .map(|p| construct(p).map_err(|e| Error::construction(short, long, e)))
+            .collect::<Result<Vec<T>, Error>>();
+        if let Some(values) = self.constructed(res)? {
+            for (slot, v) in into.iter_mut().zip(values) {
+                *slot = v;
+            }
+            self.record_match(short, long, Some(val));
+        }
 
         Ok(self)
     }
@@ -812,7 +3297,7 @@ impl Parser {
     pub fn short_arg<'a, T: FromStr+ToString>(&'a mut self,
         short: char, desc: &'static str, into: &mut T, label: Option<&'static str>,
         required: bool
-    ) -> Result<&'a mut Parser, Error>
+    ) -> Result<&'a mut Parser<'args>, Error>
         where <T as FromStr>::Err: std::fmt::Display
     {
         self.arg(short, "", desc, into, label, required)
@@ -822,7 +3307,7 @@ impl Parser {
     pub fn long_arg<'a, T: FromStr+ToString>(&'a mut self,
         long: &'static str, desc: &'static str, into: &mut T, label: Option<&'static str>,
         required: bool
-    ) -> Result<&'a mut Parser, Error>
+    ) -> Result<&'a mut Parser<'args>, Error>
         where <T as FromStr>::Err: std::fmt::Display
     {
         self.arg('\0', long, desc, into, label, required)
@@ -834,11 +3319,9 @@ impl Parser {
     // flag(s)
     //----------------------------------------------------------------
 
-    // TODO: the help flags should be stored on `self` which is why this is
-    // a member function. once the flag(s) are configurable we will store them
-    // on the parser for this case
     fn is_help_flags(&self, short: char, long: &'static str) -> bool  {
-        (short == 'h') || (long == "help")
+        let (help_short, help_long) = self.help_codes;
+        ((short != '\0') && (short == help_short)) || (!long.is_empty() && (long == help_long))
     }
 
     /// Flag defines an argument that takes no value, but instead sets a boolean.
@@ -848,9 +3331,9 @@ impl Parser {
     pub fn flag<'a>(&'a mut self,
         short: char, long: &'static str, desc: &'static str,
         into: &mut bool, invert: bool
-    ) -> Result<&'a mut Parser, Error>
+    ) -> Result<&'a mut Parser<'args>, Error>
     {
-
+        let short = self.short_code(short, long, desc, Takes::Nothing);
         if self.should_ignore(ItemType::Argument) { return Ok(self); }
 
         if self.wants_help() {
@@ -866,21 +3349,125 @@ impl Parser {
 
         let found_opt = self.find_match(short, long, false)?;
         if found_opt.is_none() {
+            if self.is_implied(long) {
+                *into = !invert;
+                self.record_match(short, long, None);
+            }
+            return Ok(self);
+        }
+
+        let found = found_opt.unwrap();
+        if found.run_count == 0 { // was not part of a run, remove entire index
+            self.mask.remove(found.index);
+        }
+
+        match found.value {
+            ValueLocation::Unknown => {
+                *into = !invert;
+                self.record_match(short, long, None);
+            }
+            ValueLocation::TakesNext => {
+                return Err(Error::InvalidInput(short, long, "flag should not have a value"));
+            }
+            ValueLocation::HasEqual(_) => {
+                return Err(Error::InvalidInput(short, long, "flag should not have a value"));
+            }
+            ValueLocation::Attached(_) => {
+                return Err(Error::InvalidInput(short, long, "flag should not have a value"));
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Checks whether a flag was given, consuming it if so. Unlike
+    /// [Parser::flag](#method.flag), nothing is registered with the help dialog and the
+    /// current scope is not considered, making this suited to imperative or plugin-style
+    /// parsing where the options are not known up front.
+    ///
+    /// As with a flag, the short code may be given within a run (e.g. `-xvz`). A flag given
+    /// with a value (e.g. `--verbose=1`) is not considered present and is left unused.
+    pub fn take_flag(&mut self, short: char, long: &'static str) -> bool {
+        let found = match self.find_match(short, long, false) {
+            Ok(Some(found)) => { found }
+            _ => { return false; }
+        };
+        if let ValueLocation::HasEqual(_) = found.value {
+            return false;
+        }
+
+        if found.run_count == 0 { // was not part of a run, remove entire index
+            self.mask.remove(found.index);
+        }
+        self.record_match(short, long, None);
+        true
+    }
+
+    /// Same as [Parser::flag](#method.flag), but `into` is set to `default` when the flag
+    /// is not given, and to the opposite when it is. The help dialog shows `default`
+    /// rather than whatever `into` holds beforehand.
+    pub fn flag_default<'a>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut bool, default: bool
+    ) -> Result<&'a mut Parser<'args>, Error>
+    {
+        *into = default;
+        self.flag(short, long, desc, into, default)
+    }
+
+    /// Same as [Parser::flag](#method.flag), but also accepts an explicit boolean after an
+    /// equal sign (e.g. `--debug=false`), which is useful for overriding a default loaded
+    /// from a config file. Given bare, the flag sets `into` to `true`.
+    ///
+    /// The accepted values are `true`/`false`, `yes`/`no`, and `1`/`0`, ignoring case. Any
+    /// other value results in [Error::InvalidChoice](enum.Error.html#variant.InvalidChoice).
+    pub fn bool_flag<'a>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut bool
+    ) -> Result<&'a mut Parser<'args>, Error>
+    {
+        let short = self.short_code(short, long, desc, Takes::Nothing);
+        if self.should_ignore(ItemType::Argument) { return Ok(self); }
+
+        if self.wants_help() {
+            self.printer.add_arg(
+                printer::Argument::new(short, long, desc, None, Some(into.to_string()), false),
+                self.curr_group
+            )?;
             return Ok(self);
         }
 
-        let found = found_opt.unwrap();
-        self.mask.remove(found.index);
-
+        let found = match self.find_match(short, long, false)? {
+            Some(f) => { f }
+            None => {
+                if self.is_implied(long) {
+                    *into = true;
+                    self.record_match(short, long, None);
+                }
+                return Ok(self);
+            }
+        };
+        if found.run_count == 0 { // was not part of a run, remove entire index
+            self.mask.remove(found.index);
+        }
+
         match found.value {
             ValueLocation::Unknown => {
-                *into = !invert;
-            }
-            ValueLocation::TakesNext => {
-                return Err(Error::InvalidInput(short, long, "flag should not have a value"));
+                *into = true;
+                self.record_match(short, long, None);
             }
             ValueLocation::HasEqual(_) => {
-                return Err(Error::InvalidInput(short, long, "flag should not have a value"));
+                const CHOICES: &[&str] = &["true", "false", "yes", "no", "1", "0"];
+                let val = self.take_value(&found, short, long)?;
+                *into = match val.to_lowercase().as_str() {
+                    "true" | "yes" | "1" => { true }
+                    "false" | "no" | "0" => { false }
+                    _ => { return Err(Error::InvalidChoice(short, long, val.into_owned(), CHOICES)); }
+                };
+                self.record_match(short, long, Some(val));
+            }
+            ValueLocation::TakesNext | ValueLocation::Attached(_) => {
+                return Err(Error::InvalidInput(short, long, "flag value must follow an equal sign"));
             }
         }
 
@@ -891,7 +3478,7 @@ impl Parser {
     pub fn short_flag<'a>(&'a mut self,
         short: char, desc: &'static str,
         into: &mut bool, invert: bool
-    ) -> Result<&'a mut Parser, Error>
+    ) -> Result<&'a mut Parser<'args>, Error>
     {
         self.flag(short, "", desc, into, invert)
     }
@@ -900,11 +3487,111 @@ impl Parser {
     pub fn long_flag<'a>(&'a mut self,
         long: &'static str, desc: &'static str,
         into: &'a mut bool, invert: bool
-    ) -> Result<&'a mut Parser, Error>
+    ) -> Result<&'a mut Parser<'args>, Error>
     {
         self.flag('\0', long, desc, into, invert)
     }
 
+    /// Linked flags define a pair of flags controlling the same boolean. The `enable`
+    /// codes set the backing bool to `true` while the `disable` codes set it to `false`
+    /// (e.g. `--verbose` and `--quiet`). Codes are given as `(short, long)` tuples where
+    /// either may be empty (`'\0'` or `""`) as with [Parser::flag](#method.flag).
+    ///
+    /// Both flags may be given multiple times, in which case the last one given wins.
+    /// In the help dialog the pair is printed on a single line.
+    pub fn linked_flags<'a>(&'a mut self,
+        enable: (char, &'static str), disable: (char, &'static str),
+        desc: &'static str, into: &mut bool
+    ) -> Result<&'a mut Parser<'args>, Error>
+    {
+        let enable = (self.short_code(enable.0, enable.1, desc, Takes::Nothing), enable.1);
+        let disable = (self.short_code(disable.0, disable.1, desc, Takes::Nothing), disable.1);
+        if self.should_ignore(ItemType::Argument) { return Ok(self); }
+
+        if self.wants_help() {
+            self.printer.add_arg(
+                printer::Argument::new(
+                    enable.0, enable.1, desc, None, Some(into.to_string()), false
+                ).linked(disable.0, disable.1),
+                self.curr_group
+            )?;
+            return Ok(self);
+        }
+
+        let last_enable = self.find_last_flag(enable.0, enable.1)?;
+        let last_disable = self.find_last_flag(disable.0, disable.1)?;
+        match (last_enable, last_disable) {
+            (Some(e), Some(d)) => { *into = e > d; }
+            (Some(_), None) => { *into = true; }
+            (None, Some(_)) => { *into = false; }
+            (None, None) => {
+                if self.is_implied(enable.1) {
+                    *into = true;
+                    self.record_match(enable.0, enable.1, None);
+                }
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Declares that giving the `trigger` flag implies the `implied` flag, both given by
+    /// their long codes. For example, `implies("debug", "verbose")` sets the backing bool
+    /// of `--verbose` when `--debug` is given, as if `--verbose` had been given as well.
+    ///
+    /// The implication and the trigger flag must both be declared before the implied flag
+    /// (or [Parser::linked_flags](#method.linked_flags) pair), as only a trigger which has
+    /// already been matched implies anything. Flags given explicitly always
+    /// take precedence, so `--debug --quiet` with a linked `--verbose`/`--quiet` pair
+    /// leaves verbose off.
+    pub fn implies(&mut self, trigger: &'static str, implied: &'static str) -> &mut Parser<'args> {
+        self.implications.push((trigger, implied));
+        self
+    }
+
+    // whether a flag is implied by a matched (or itself implied) trigger flag. only
+    // triggers already consumed count, so one swallowed as the value of another arg or
+    // given within a subcommand which was not taken does not imply anything
+    fn is_implied(&self, long: &'static str) -> bool {
+        if long.is_empty() { return false; }
+
+        self.implications.iter()
+            .filter(|(_, implied)| *implied == long)
+            .any(|(trigger, _)| self.matched.iter().any(|m| m.long == *trigger))
+    }
+
+    // consumes every occurrence of a flag, returning the position (arg index, offset
+    // within the arg) of the last occurrence so that linked flags can resolve last-wins
+    fn find_last_flag(&mut self, short: char, long: &'static str)
+        -> Result<Option<(usize, usize)>, Error>
+    {
+        let mut last: Option<(usize, usize)> = None;
+        loop {
+            let found = match self.find_match(short, long, false)? {
+                Some(f) => { f }
+                None => { return Ok(last); }
+            };
+            if found.run_count == 0 { // was not part of a run, remove entire index
+                self.mask.remove(found.index);
+            }
+
+            if let ValueLocation::HasEqual(_) = found.value {
+                return Err(Error::InvalidInput(short, long, "flag should not have a value"));
+            }
+
+            let offset = if found.run_count == 0 {
+                0
+            } else {
+                self.args[found.index].rfind(short).unwrap_or(0)
+            };
+            let pos = (found.index, offset);
+            if last.map_or(true, |l| pos > l) {
+                last = Some(pos);
+            }
+            self.record_match(short, long, None);
+        }
+    }
+
 
     //----------------------------------------------------------------
     // count(s)
@@ -920,22 +3607,113 @@ impl Parser {
     pub fn count<'a, T: std::ops::AddAssign + ToString + Clone>(&'a mut self,
         short: char, long: &'static str, desc: &'static str,
         into: &mut T, step: T
-    ) -> Result<&'a mut Parser, Error>
+    ) -> Result<&'a mut Parser<'args>, Error>
+    {
+        let add = |v: &mut T| { v.add_assign(step.clone()); true };
+        self.count_impl(short, long, desc, into, &add, false, None)
+    }
+
+    /// Same as [Parser::count](#method.count), but the number of repetitions may also be
+    /// given explicitly: `-v3`, `-v=3`, and `--verbose=3` are all equivalent to `-vvv`.
+    ///
+    /// A number attached to the short code is only treated as a repetition when none of
+    /// its digits are themselves declared as short codes, otherwise the arg is treated as
+    /// a run of short codes. Likewise, a number ending a run of short codes belongs to the
+    /// last code in the run, so `-xvv3` counts `v` four times.
+    pub fn count_numeric<'a, T: std::ops::AddAssign + ToString + Clone>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut T, step: T
+    ) -> Result<&'a mut Parser<'args>, Error>
+    {
+        let add = |v: &mut T| { v.add_assign(step.clone()); true };
+        self.count_impl(short, long, desc, into, &add, true, None)
+    }
+
+    /// Same as [Parser::count](#method.count), but the count stops at `max`. For example,
+    /// with a `max` of 3, `-vvvvv` counts as `-vvv`. The help dialog shows the maximum
+    /// alongside the default (e.g. `[default: 0, max: 3]`), and the number of occurrences
+    /// recorded (e.g. by [Parser::matched_options](#method.matched_options)) is the
+    /// number counted. The backing value is left as-is when the arg is not given.
+    pub fn count_max<'a, T: std::ops::AddAssign + PartialOrd + ToString + Clone>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut T, step: T, max: T
+    ) -> Result<&'a mut Parser<'args>, Error>
     {
+        // a step overshooting the max stops at it
+        let add = |v: &mut T| {
+            if *v >= max {
+                return false;
+            }
+            v.add_assign(step.clone());
+            if *v > max {
+                *v = max.clone();
+            }
+            true
+        };
+        self.count_impl(short, long, desc, into, &add, false, Some(max.to_string()))
+    }
 
+    // shared implementation of count, optionally accepting explicit repetitions. `add`
+    // applies a single step, returning whether it was counted, and `max` is what the help
+    // dialog shows as the maximum
+    #[allow(clippy::too_many_arguments)]
+    fn count_impl<'a, T: ToString>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut T, add: &dyn Fn(&mut T) -> bool, numeric: bool, max: Option<String>
+    ) -> Result<&'a mut Parser<'args>, Error>
+    {
+        let short = self.short_code(short, long, desc, Takes::Nothing);
         if self.should_ignore(ItemType::Argument) { return Ok(self); }
 
         if self.wants_help() {
-            self.printer.add_arg(
-                printer::Argument::new(short, long, desc, None, Some(into.to_string()), false),
-                self.curr_group
-            )?;
+            let mut help = printer::Argument::new(short, long, desc, None, Some(into.to_string()), false);
+            if let Some(m) = max {
+                help = help.with_max(m);
+            }
+            self.printer.add_arg(help, self.curr_group)?;
             return Ok(self);
         }
 
+        // applies `n` steps, returning how many were counted
+        let count = |into: &mut T, n: usize| (0..n).filter(|_| add(into)).count();
+        let mut given = false;
+        let mut occurrences: usize = 0;
+        if numeric {
+            // claim the explicit forms first so `-v3` is not mistaken for a run
+            let explicit = self.option_candidates()
+                .filter(|i| !self.run_masks.contains_key(i))
+                .filter_map(|i| self.numeric_repetition(i, short, long).map(|n| (i, n)))
+                .collect::<Vec<(usize, usize)>>();
+            for (idx, n) in explicit {
+                self.mask.remove(idx);
+                occurrences += count(into, n);
+                given = true;
+            }
+
+            // then a number ending a run of short codes (e.g. `-vvn5`)
+            let suffixed = self.option_candidates()
+                .filter_map(|i| self.run_suffix(i).map(|(off, start)| (i, off, start)))
+                .filter(|(i, off, _)| self.args[*i][*off..].starts_with(short))
+                .collect::<Vec<(usize, usize, usize)>>();
+            for (idx, off, start) in suffixed {
+                let n = match self.args[idx][start..].parse::<usize>() {
+                    Ok(n) => { n }
+                    Err(_) => { continue; }
+                };
+                if !self.claim_run_suffix(idx, off) {
+                    continue;
+                }
+                occurrences += count(into, n);
+                given = true;
+            }
+        }
+
         loop { // loop until we get no results back
             let found_opt = self.find_match(short, long, false)?;
             if found_opt.is_none() {
+                if given {
+                    self.record_match(short, long, Some(Cow::Owned(occurrences.to_string())));
+                }
                 return Ok(self);
             }
 
@@ -946,13 +3724,8 @@ impl Parser {
 
             match found.value {
                 ValueLocation::Unknown => {
-                    if found.run_count == 0 {
-                        into.add_assign(step.clone());
-                    } else {
-                        for _ in 0..found.run_count {
-                            into.add_assign(step.clone());
-                        }
-                    }
+                    occurrences += count(into, std::cmp::max(1, found.run_count));
+                    given = true;
                 }
                 ValueLocation::TakesNext => {
                     return Err(Error::InvalidInput(short, long, "count should not have a value"));
@@ -960,15 +3733,43 @@ impl Parser {
                 ValueLocation::HasEqual(_) => {
                     return Err(Error::InvalidInput(short, long, "count should not have a value"));
                 }
+                ValueLocation::Attached(_) => {
+                    return Err(Error::InvalidInput(short, long, "count should not have a value"));
+                }
+            }
+        }
+    }
+
+    // the explicit repetition given by the arg at `idx` (e.g. `-v3` or `--verbose=3`)
+    fn numeric_repetition(&self, idx: usize, short: char, long: &'static str) -> Option<usize> {
+        let arg: &str = &self.args[idx];
+        let short_prefix = format!("-{}", short);
+        let long_prefix = format!("--{}=", long);
+
+        let digits = if (short != '\0') && !self.long_only && arg.starts_with(&short_prefix) {
+            let rest = &arg[short_prefix.len()..];
+            let rest = rest.strip_prefix('=').unwrap_or(rest);
+            if rest.chars().any(|c| self.shorts.contains(&c)) {
+                return None;
             }
+            rest
+        } else if !long.is_empty() && arg.starts_with(&long_prefix) {
+            &arg[long_prefix.len()..]
+        } else {
+            return None;
+        };
+
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
         }
+        digits.parse::<usize>().ok()
     }
 
     /// Convenience method for declaring a [Parser::count](#method.count) without a long code.
     pub fn short_count<'a, T: std::ops::AddAssign + ToString + Clone>(&'a mut self,
         short: char, desc: &'static str,
         into: &mut T, step: T
-    ) -> Result<&'a mut Parser, Error>
+    ) -> Result<&'a mut Parser<'args>, Error>
     {
         self.count(short, "", desc, into, step)
     }
@@ -977,7 +3778,7 @@ impl Parser {
     pub fn long_count<'a, T: std::ops::AddAssign + ToString + Clone>(&'a mut self,
         long: &'static str, desc: &'static str,
         into: &mut T, step: T
-    ) -> Result<&'a mut Parser, Error>
+    ) -> Result<&'a mut Parser<'args>, Error>
     {
         self.count('\0', long, desc, into, step)
     }
@@ -996,15 +3797,75 @@ impl Parser {
     pub fn list<'a, T: FromStr + ToString>(&'a mut self,
         short: char, long: &'static str, desc: &'static str,
         into: &mut Vec<T>, label: Option<&'static str>, required: bool
-    ) -> Result<&'a mut Parser, Error>
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: std::fmt::Display
+    {
+        self.list_impl(short, long, desc, into, label, required, None, false, None)
+    }
+
+    /// Same as [Parser::list](#method.list), but a single occurrence collects every value
+    /// following it (e.g. `--tags foo bar baz`), rather than requiring the option to be
+    /// repeated per value. Values are claimed until the next arg which looks like an
+    /// option, the arg-stop sentinel (`--`), or an arg already claimed by an earlier
+    /// declaration. The option may still be given multiple times, appending the values
+    /// of each occurrence.
+    pub fn multi_list<'a, T: FromStr + ToString>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut Vec<T>, label: Option<&'static str>, required: bool
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: std::fmt::Display
+    {
+        self.list_impl(short, long, desc, into, label, required, None, true, None)
+    }
+
+    /// Same as [Parser::list](#method.list), but when no values are given `into` is set
+    /// to `default`. The help dialog shows the default values joined by `,`, rather than
+    /// anything `into` holds beforehand.
+    pub fn list_default<'a, T: FromStr + ToString>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut Vec<T>, label: Option<&'static str>, default: Vec<T>
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: std::fmt::Display
+    {
+        let shown = default.iter().map(|d| d.to_string()).collect::<Vec<String>>().join(",");
+        let mut given: Vec<T> = vec!();
+        self.list_impl(short, long, desc, &mut given, label, false, Some(shown), false, None)?;
+
+        *into = if given.is_empty() { default } else { given };
+        Ok(self)
+    }
+
+    /// Same as [Parser::list](#method.list), but each value is split on `delim` and every
+    /// segment is appended, so `-l a,b,c` with a delimiter of `,` appends three items.
+    /// Empty segments (e.g. from a trailing delimiter) are skipped. The option may still
+    /// be given multiple times, so `-l a,b -l c` appends three items as well.
+    #[allow(clippy::too_many_arguments)]
+    pub fn delimited_list<'a, T: FromStr + ToString>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut Vec<T>, label: Option<&'static str>, required: bool, delim: char
+    ) -> Result<&'a mut Parser<'args>, Error>
         where <T as FromStr>::Err: std::fmt::Display
     {
+        self.list_impl(short, long, desc, into, label, required, None, false, Some(delim))
+    }
 
+    // shared implementation of the list family, where `default` is what the help
+    // dialog shows as the default. when `greedy`, the args following each occurrence
+    // are claimed as values as well. when delimited, each value is split into segments
+    #[allow(clippy::too_many_arguments)]
+    fn list_impl<'a, T: FromStr + ToString>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut Vec<T>, label: Option<&'static str>, required: bool,
+        default: Option<String>, greedy: bool, delim: Option<char>
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: std::fmt::Display
+    {
+        let short = self.short_code(short, long, desc, Takes::Value);
         if self.should_ignore(ItemType::Argument) { return Ok(self); }
 
         if self.wants_help() {
             self.printer.add_arg(
-                printer::Argument::new(short, long, desc, label, None, required),
+                printer::Argument::new(short, long, desc, label, default, required),
                 self.curr_group
             )?;
             return Ok(self);
@@ -1016,35 +3877,47 @@ impl Parser {
             if found_opt.is_none() { // TODO: required count -- does this make sense?
                 // only requried when !help
                 if required && (found_count == 0) && !self.wants_help() {
-                    return Err(Error::MissingArgument(arg_string(short, long, false)));
+                    self.missing(Error::MissingArgument(arg_string(short, long, false)))?;
                 }
                 return Ok(self);
             }
             found_count += 1;
 
             let found = found_opt.unwrap();
-            self.mask.remove(found.index);
+            if found.run_count == 0 { // was not part of a run, remove entire index
+                self.mask.remove(found.index);
+            }
 
-            let ctor_result = match found.value {
-                ValueLocation::Unknown => {
-                    return Err(Error::MissingArgValue(short, long));
-                }
-                ValueLocation::TakesNext => {
-                    self.mask.remove(found.index + 1);
-                    let str_val = &self.args[found.index + 1];
-                    T::from_str(str_val)
-                }
-                ValueLocation::HasEqual(eq_idx) => {
-                    // index already removed
-                    let str_val = &self.args[found.index][eq_idx + 1..];
-                    T::from_str(str_val)
+            let mut values = vec!(self.take_value(&found, short, long)?);
+            if greedy {
+                // unclaimed args are always before the arg-stop sentinel
+                let mut next = match found.value {
+                    ValueLocation::TakesNext => { found.index + 2 }
+                    _ => { found.index + 1 }
+                };
+                while self.mask.contains(next) && !self.looks_like_arg(next) {
+                    self.mask.remove(next);
+                    values.push(self.args[next].clone());
+                    next += 1;
                 }
-            };
+            }
 
-            into.push(
-                ctor_result
-                    .map_err(|e| Error::ConstructionError(short, long, format!("{}", e)))?
-            );
+            if let Some(d) = delim {
+                values = values.iter()
+                    .flat_map(|v| v.split(d))
+                    .filter(|v| !v.is_empty())
+                    .map(|v| Cow::Owned(v.to_string()))
+                    .collect();
+            }
+
+            for str_val in values {
+                let res = construct(&str_val)
+                    .map_err(|e| Error::construction(short, long, e));
+                if let Some(v) = self.constructed(res)? {
+                    into.push(v);
+                    self.record_match(short, long, Some(str_val));
+                }
+            }
         }
     }
 
@@ -1052,22 +3925,109 @@ impl Parser {
     pub fn short_list<'a, T: FromStr + ToString>(&'a mut self,
         short: char, desc: &'static str,
         into: &mut Vec<T>, label: Option<&'static str>, required: bool
-    ) -> Result<&'a mut Parser, Error>
+    ) -> Result<&'a mut Parser<'args>, Error>
         where <T as FromStr>::Err: std::fmt::Display
     {
         self.list(short, "", desc, into, label, required)
     }
 
+    /// Same as [Parser::list](#method.list), but each constructed element is checked by
+    /// `validate` (e.g. that a port is within `1..=65535`). The first element failing
+    /// validation results in a
+    /// [Error::ValidationFailed](enum.Error.html#variant.ValidationFailed) naming the
+    /// offending value and carrying the message returned by the validator. Nothing is
+    /// appended to `into` when an element fails.
+    #[allow(clippy::too_many_arguments)]
+    pub fn list_validated<'a, T: FromStr + ToString, F: Fn(&T) -> Result<(), String>>(
+        &'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut Vec<T>, label: Option<&'static str>, required: bool,
+        validate: F
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: std::fmt::Display
+    {
+        let mut parsed: Vec<T> = vec!();
+        self.list(short, long, desc, &mut parsed, label, required)?;
+
+        for p in parsed.iter() {
+            validate(p).map_err(|e| Error::ValidationFailed(short, long, p.to_string(), e))?;
+        }
+        into.extend(parsed);
+
+        Ok(self)
+    }
+
     /// Convenience method for declaring a [Parser::list](#method.list) without a short code.
     pub fn long_list<'a, T: FromStr + ToString>(&'a mut self,
         long: &'static str, desc: &'static str,
         into: &mut Vec<T>, label: Option<&'static str>, required: bool
-    ) -> Result<&'a mut Parser, Error>
+    ) -> Result<&'a mut Parser<'args>, Error>
         where <T as FromStr>::Err: std::fmt::Display
     {
         self.list('\0', long, desc, into, label, required)
     }
 
+    /// Ordered key-value arg collects `key<sep>value` pairs (e.g. `-H accept:json` with a
+    /// separator of `:`) in the order they are given. Each value is split on the first
+    /// separator, so values may themselves contain the separator. Keys may be repeated.
+    ///
+    /// Values without the separator result in a
+    /// [Error::ConstructionError](enum.Error.html#variant.ConstructionError). Otherwise
+    /// this behaves as [Parser::list](#method.list).
+    #[allow(clippy::too_many_arguments)]
+    pub fn ordered_kv_arg<'a>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut Vec<(String, String)>, sep: char,
+        label: Option<&'static str>, required: bool
+    ) -> Result<&'a mut Parser<'args>, Error>
+    {
+        let mut raw: Vec<String> = vec!();
+        self.list(short, long, desc, &mut raw, label, required)?;
+
+        for r in raw.iter() {
+            match r.split_once(sep) {
+                Some((k, v)) => { into.push((k.to_string(), v.to_string())); }
+                None => {
+                    return Err(Error::ConstructionError(short, long,
+                        format!("expected KEY{}VALUE, got '{}'", sep, r)));
+                }
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Dotted set arg collects `dotted.key=value` pairs (e.g.
+    /// `--set server.port=8080 --set server.host=localhost`) into `into`. Each value is
+    /// split on the first `=`, so keys may contain dots and values may contain `=`. When
+    /// a key is given more than once, the last value wins.
+    ///
+    /// The pairs are kept flat, and may be expanded into nested objects with
+    /// [expand_dotted](fn.expand_dotted.html) when the `serde` feature is enabled.
+    ///
+    /// Values without an `=` or with an empty key result in a
+    /// [Error::ConstructionError](enum.Error.html#variant.ConstructionError). Otherwise
+    /// this behaves as [Parser::list](#method.list).
+    pub fn dotted_set_arg<'a>(&'a mut self,
+        short: char, long: &'static str, desc: &'static str,
+        into: &mut BTreeMap<String, String>,
+        label: Option<&'static str>, required: bool
+    ) -> Result<&'a mut Parser<'args>, Error>
+    {
+        let mut pairs: Vec<(String, String)> = vec!();
+        self.ordered_kv_arg(short, long, desc, &mut pairs, '=', label, required)?;
+
+        for (k, v) in pairs.into_iter() {
+            if k.is_empty() {
+                return Err(Error::ConstructionError(short, long,
+                    format!("expected KEY=VALUE, got '={}'", v)));
+            }
+            into.insert(k, v);
+        }
+
+        Ok(self)
+    }
+
 
 
     //----------------------------------------------------------------
@@ -1093,17 +4053,54 @@ impl Parser {
     pub fn subcommand<'a, T: FromStr + ToString>(&'a mut self,
         name: &'static str, desc: &'static str, into: &mut Vec<T>,
         long_desc: Option<&'static str>
-    ) -> Result<&'a mut Parser, Error>
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: std::fmt::Display
+    {
+        self.subcommand_impl(name, desc, into, long_desc, false)
+    }
+
+    /// Same as [Parser::subcommand](#method.subcommand), but the subcommand may be given
+    /// multiple times at the same level, enabling pipeline-style interfaces such as
+    /// `my_app filter foo filter bar`. Every occurrence is appended to the vector and the
+    /// scope is entered once for all of them.
+    ///
+    /// Arguments declared within the scope are matched against the arguments following
+    /// any occurrence, in the order they were given. Lists and positional lists therefore
+    /// gather the values of every occurrence (`[foo, bar]` above), and positionals take
+    /// the first value given. An arg taking a single value given more than once results in
+    /// [Error::RepeatedValue](enum.Error.html#variant.RepeatedValue), as one value cannot
+    /// hold what was given to each occurrence.
+    pub fn repeated_subcommand<'a, T: FromStr + ToString>(&'a mut self,
+        name: &'static str, desc: &'static str, into: &mut Vec<T>,
+        long_desc: Option<&'static str>
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: std::fmt::Display
+    {
+        self.subcommand_impl(name, desc, into, long_desc, true)
+    }
+
+    // shared implementation for subcommands, optionally matching every occurrence
+    fn subcommand_impl<'a, T: FromStr + ToString>(&'a mut self,
+        name: &'static str, desc: &'static str, into: &mut Vec<T>,
+        long_desc: Option<&'static str>, repeated: bool
+    ) -> Result<&'a mut Parser<'args>, Error>
         where <T as FromStr>::Err: std::fmt::Display
     {
         // even if we do not match this subcommand, all parsing until the
         // associated ::done() call happens within the next level so we
         // must move into it unconditionally
         self.walk_next_level();
+        self.tree.enter(name, desc);
+        if self.walk_depth == 1 {
+            self.top_subs.push(name);
+        }
 
         if self.should_ignore(ItemType::Subcommand) {
             return Ok(self);
         }
+        // note the level a subcommand was expected at. if one matches, the level is
+        // committed and it is no longer the deepest
+        self.sub_expected_at = Some(self.commit_depth);
 
         if self.wants_help() {
             self.printer.add_subcommand(printer::Subcommand::new(name, desc));
@@ -1115,24 +4112,47 @@ impl Parser {
             return Err(Error::InvalidState("subcommand(...) given empty name"));
         }
 
-        if let Some(info) = self.find_subcommand(name) {
+        let mut occurrences = 0;
+        while let Some(info) = self.find_subcommand(name) {
             self.mask.remove(info.index);
-            let arg = &self.args[info.index];
-            into.push(
-                T::from_str(arg)
-                    .map_err(|e| Error::SubConstructionError(name, format!("{}", e)))?
-            );
+            let res = construct(&self.args[info.index])
+                .map_err(|e| Error::sub_construction(name, e));
+            if let Some(v) = self.constructed(res)? {
+                into.push(v);
+            }
+
+            occurrences += 1;
+            if !repeated { break; }
+        }
 
+        if occurrences > 0 {
             self.commit_next_level();
+            if occurrences > 1 {
+                self.repeated_at = Some((self.commit_depth, name));
+            }
+            self.sub_path.push(name);
             self.printer.new_level(
                 name, desc,
-                if let Some(d) = long_desc { d } else { "" }
+                long_desc.unwrap_or_default()
             );
         }
 
         Ok(self)
     }
 
+    /// Requires that one of the top-level subcommands was given, returning
+    /// [Error::MissingSubcommand](enum.Error.html#variant.MissingSubcommand) listing
+    /// the available subcommands otherwise. This should be called after all top-level
+    /// subcommands (and their [Parser::done](#method.done) calls) have been declared.
+    ///
+    /// No error is returned when help or the version was requested.
+    pub fn require_subcommand(&mut self) -> Result<&mut Parser<'args>, Error> {
+        if self.wants_help() || self.wants_version() || (self.commit_depth > 0) {
+            return Ok(self);
+        }
+        Err(Error::MissingSubcommand(self.top_subs.clone()))
+    }
+
     //----------------------------------------------------------------
     // group(s)
     //----------------------------------------------------------------
@@ -1144,9 +4164,9 @@ impl Parser {
     /// [Parser::done](#method.done). However, no masking of arguments occurs in
     /// this created scope. The only effect a group has is on the printing of args.
     pub fn group<'a>(&'a mut self, name: &'static str, desc: &'static str)
-        -> Result<&'a mut Parser, Error>
+        -> Result<&'a mut Parser<'args>, Error>
     {
-        if let Some(orig) = self.curr_group {
+        if let Some(orig) = self.curr_group.or(self.curr_pos_group) {
             return Err(Error::NestedGroup(orig, name));
         }
 
@@ -1159,6 +4179,29 @@ impl Parser {
         Ok(self)
     }
 
+    /// Positional group is the positional counterpart to [Parser::group](#method.group).
+    /// Positionals declared within the group are printed in a named section of the help
+    /// dialog rather than under `positionals`. When a help argument is not provided,
+    /// this is a no-op.
+    ///
+    /// Parsing is unaffected: positionals are still taken in the order they are declared
+    /// regardless of grouping. This scope must be closed using [Parser::done](#method.done).
+    pub fn positional_group<'a>(&'a mut self, name: &'static str, desc: &'static str)
+        -> Result<&'a mut Parser<'args>, Error>
+    {
+        if let Some(orig) = self.curr_group.or(self.curr_pos_group) {
+            return Err(Error::NestedGroup(orig, name));
+        }
+
+        if self.should_ignore(ItemType::Group) { return Ok(self); }
+
+        self.curr_pos_group = Some(name);
+        if self.wants_help() {
+            self.printer.add_positional_group(name, desc)?;
+        }
+        Ok(self)
+    }
+
 
     //----------------------------------------------------------------
     // positional(s)
@@ -1176,44 +4219,133 @@ impl Parser {
     /// be defined after all subcommands. You can, however, safely define positionals within
     /// a leaf subcommand scope.
     ///
+    /// An empty argument (e.g. `mytool ""`) is captured as an empty value rather than
+    /// being skipped.
+    ///
     /// Just as in the base [Parser::arg](#method.arg) case, the target type must implement
     /// both `From<String>` and `ToString`.
     pub fn positional<'a, T: ToString + FromStr>(&'a mut self,
         name: &'static str, desc: &'static str,
         into: &mut T, required: bool
-    ) -> Result<&'a mut Parser, Error>
+    ) -> Result<&'a mut Parser<'args>, Error>
         where <T as FromStr>::Err: std::fmt::Display
     {
-        if self.should_ignore(ItemType::Argument) { return Ok(self); }
+        self.positional_checked(name, desc, into, required, None, construct)
+    }
+
+    /// Same as [Parser::positional](#method.positional), but the value must exactly match
+    /// one of the given choices, otherwise
+    /// [Error::InvalidPositionalChoice](enum.Error.html#variant.InvalidPositionalChoice)
+    /// is returned. The choices are checked before construction and are listed in the
+    /// help dialog.
+    pub fn positional_choices<'a, T: ToString + FromStr>(&'a mut self,
+        name: &'static str, desc: &'static str,
+        into: &mut T, required: bool, choices: &'static [&'static str]
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: std::fmt::Display
+    {
+        self.positional_checked(name, desc, into, required, Some(choices), construct)
+    }
+
+    /// Same as [Parser::positional](#method.positional), but the error returned by
+    /// `FromStr` is kept. A failed construction results in an
+    /// [Error::WithSource](enum.Error.html#variant.WithSource) wrapping the
+    /// [Error::PositionalConstructionError](enum.Error.html#variant.PositionalConstructionError),
+    /// whose `source()` is the original error.
+    pub fn positional_typed<'a, T: ToString + FromStr>(&'a mut self,
+        name: &'static str, desc: &'static str,
+        into: &mut T, required: bool
+    ) -> Result<&'a mut Parser<'args>, Error>
+        where <T as FromStr>::Err: Into<BoxedError>
+    {
+        self.positional_checked(name, desc, into, required, None, construct_typed)
+    }
+
+    // shared implementation for named positionals, optionally restricting the value
+    fn positional_checked<'a, T: ToString, F: Fn(&str) -> Result<T, Failure>>(&'a mut self,
+        name: &'static str, desc: &'static str,
+        into: &mut T, required: bool, choices: Option<&'static [&'static str]>,
+        parse: F
+    ) -> Result<&'a mut Parser<'args>, Error>
+    {
+        let default = || into.to_string();
+        if let Some(val) = self.take_positional(name, desc, default, required, choices)? {
+            let res = parse(&val).map_err(|e| Error::positional_construction(name, e));
+            if let Some(v) = self.constructed(res)? {
+                *into = v;
+            }
+        }
+
+        Ok(self)
+    }
+
+    // shared lookup for named positionals. when help is wanted the positional is registered
+    // with the printer and nothing is returned, otherwise the next unused arg is claimed.
+    // returns an error if the positional is required but not given
+    fn take_positional<F: FnOnce() -> String>(&mut self,
+        name: &'static str, desc: &'static str, default: F,
+        required: bool, choices: Option<&'static [&'static str]>
+    ) -> Result<Option<Cow<'args, str>>, Error>
+    {
+        self.tree.positional(name, required, false);
+        if self.should_ignore(ItemType::Argument) { return Ok(None); }
 
         if self.has_variadic {
             return Err(Error::UnorderedPositionals(name));
         }
 
         if self.wants_help() {
-            let def = into.to_string();
-            self.printer.add_positional(printer::Positional::new(
+            let def = default();
+            let mut pos = printer::Positional::new(
                 name, desc, if def.is_empty() { None } else { Some(def) },
                 required, false
-            ))?;
-            return Ok(self);
+            );
+            if let Some(c) = choices {
+                pos = pos.with_choices(c);
+            }
+            self.printer.add_positional(pos, self.curr_pos_group)?;
+            return Ok(None);
         }
 
         let idx = match self.mask.iter().next() {
             Some(i) => { i }
             None => {
                 if required {
-                    return Err(Error::MissingPositional(name.to_string()));
-                } else {
-                    return Ok(self);
+                    self.missing(Error::MissingPositional(name.to_string()))?;
                 }
+                return Ok(None);
             }
         };
-        let val = &self.args[idx];
-        *into = T::from_str(val)
-            .map_err(|e| Error::PositionalConstructionError(name, format!("{}", e)))?;
+        let val = self.args[idx].clone();
+        if let Some(c) = choices {
+            if !is_choice(&val, c) {
+                return Err(Error::InvalidPositionalChoice(name, val.into_owned(), c));
+            }
+        }
 
         self.mask.remove(idx);
+        Ok(Some(val))
+    }
+
+    /// Declares a named positional holding a path, as in
+    /// [Parser::positional](#method.positional). When `must_exist` is true, a path which
+    /// does not exist results in [Error::PathNotFound](enum.Error.html#variant.PathNotFound).
+    pub fn file_positional<'a>(&'a mut self,
+        name: &'static str, desc: &'static str,
+        into: &mut std::path::PathBuf, required: bool, must_exist: bool
+    ) -> Result<&'a mut Parser<'args>, Error>
+    {
+        let default = || into.display().to_string();
+        let val = match self.take_positional(name, desc, default, required, None)? {
+            Some(v) => { v }
+            None => { return Ok(self); }
+        };
+
+        let path = std::path::PathBuf::from(val.into_owned());
+        if must_exist && !path.exists() {
+            return Err(Error::PathNotFound(path.display().to_string()));
+        }
+        *into = path;
 
         Ok(self)
     }
@@ -1232,13 +4364,21 @@ impl Parser {
     /// this method is a vector of [Parser::positional](#method.positional) sharing a
     /// single name for the set.
     ///
+    /// When declared after named positionals, the named positionals claim their values
+    /// first and this list absorbs only the remaining tokens. For example, with two
+    /// named positionals followed by a list, `my_app a b c d e` assigns `a` and `b` to
+    /// the named positionals and `[c, d, e]` to the list. This is the way to accept
+    /// more positionals than are individually named; without a trailing list, the
+    /// excess is reported by [Parser::unused](#method.unused).
+    ///
     /// This method may only be called once, or an error will be returned.
     pub fn positional_list<'a, T: ToString + FromStr>(&'a mut self,
         name: &'static str, desc: &'static str,
         into: &mut Vec<T>, required: bool
-    ) -> Result<&'a mut Parser, Error>
+    ) -> Result<&'a mut Parser<'args>, Error>
         where <T as FromStr>::Err: std::fmt::Display
     {
+        self.tree.positional(name, required, true);
         if self.should_ignore(ItemType::Argument) { return Ok(self); }
 
         if self.has_variadic {
@@ -1251,47 +4391,33 @@ impl Parser {
         if self.wants_help() {
             self.printer.add_positional(printer::Positional::new(
                 name, desc, None, required, true
-            ))?;
+            ), self.curr_pos_group)?;
             return Ok(self);
         }
 
         let mut found_count: usize = 0;
         // TODO: I hate this, but self.mask.iter() is immut and mask mod is mut....
-        let mut found_idxs: Vec<usize> = vec!();
-        for i in self.mask.iter() {
-            let val = &self.args[i];
-            into.push(
-                T::from_str(val).map_err(|e|
-                    Error::PositionalConstructionError(name, format!("{}", e))
-                )?
-            );
-
-            found_count += 1;
-            found_idxs.push(i);
-        }
+        let mut found_idxs: Vec<usize> = self.mask.iter().collect();
         for i in found_idxs.iter() {
             self.mask.remove(*i);
         }
-
         if let Some(stop) = self.argstop {
-            for i in (stop+1)..self.args.len() {
-                let val = &self.args[i];
-                into.push(
-                    T::from_str(val).map_err(|e|
-                        Error::PositionalConstructionError(name, format!("{}", e))
-                    )?
-                );
+            found_idxs.extend((stop+1)..self.args.len());
+        }
 
-                found_count += 1;
-                found_idxs.push(i);
+        for i in found_idxs {
+            let res = construct(&self.args[i])
+                .map_err(|e| Error::positional_construction(name, e));
+            if let Some(v) = self.constructed(res)? {
+                into.push(v);
             }
+            found_count += 1;
         }
 
         if required && (found_count == 0) {
-            Err(Error::MissingPositional(format!("{}...", name)))
-        } else {
-            Ok(self)
+            self.missing(Error::MissingPositional(format!("{}...", name)))?;
         }
+        Ok(self)
     }
 }
 
@@ -1321,6 +4447,37 @@ mod handle_args {
         ;
     }
 
+    #[test]
+    fn as_str_slice() {
+        let mut verbosity: u64 = 0;
+        Parser::from_slice(&["argv[0]", "-v", "3"])
+            .arg('v', "verbose", "set verbosity", &mut verbosity, None, false)
+                .expect("failed to handle verbose argument(s)")
+        ;
+        assert_eq!(verbosity, 3, "did not parse from slice");
+    }
+
+    #[test]
+    fn borrows_slice() {
+        let mut level: String = "".to_string();
+        let mut threads: usize = 0;
+        let args = string_vec!("argv[0]", "--level=debug", "-t", "4");
+        let borrowed: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+        let mut parser = Parser::from_slice(&borrowed);
+        parser
+            .arg('l', "level", "log level", &mut level, None, false)
+                .expect("failed to handle level argument")
+            .arg('t', "threads", "thread count", &mut threads, None, false)
+                .expect("failed to handle threads argument")
+        ;
+        assert_eq!((level.as_str(), threads), ("debug", 4));
+
+        let value = parser.values_of('l', "level")[0];
+        assert!(std::ptr::eq(value, &args[1]["--level=".len()..]), "expected value to borrow the arg");
+        let value = parser.values_of('t', "threads")[0];
+        assert!(std::ptr::eq(value, args[3].as_str()), "expected value to borrow the arg");
+    }
+
     #[test]
     fn as_args_iter() {
         let mut verbosity: u64 = 0;
@@ -1,14 +1,21 @@
 use std::collections::BTreeMap;
+use std::io::Write;
 
 use crate::errors::Error;
 
 const LEFT_PAD_LENGTH: usize = 4;
 const MID_PAD_LENGTH: usize = 8;
+const DEFAULT_HELP_WIDTH: usize = 80;
+
+// ANSI styles used when color is enabled
+const STYLE_HEADER: &str = "1"; // bold
+const STYLE_CODE: &str = "36"; // cyan
+const STYLE_REQUIRED: &str = "31"; // red
 
 pub fn arg_string(short: char, long: &'static str, prefix_long: bool) -> String {
-    if short.is_alphabetic() && (long.len() > 0) {
+    if (short != '\0') && (!long.is_empty()) {
         format!("-{}, --{}", short, long)
-    } else if short.is_alphabetic() && long.is_empty() {
+    } else if (short != '\0') && long.is_empty() {
         format!("-{}", short)
     } else if !long.is_empty() {
         if prefix_long {
@@ -28,9 +35,9 @@ pub fn arg_string_len(short: char, long: &'static str) -> usize {
     let len_short = 2; /* '-c' */
     let len_sep = 2; /* ', ' */
     let len_long = 2 + long.len(); /* '--long' */
-    if short.is_alphabetic() && (long.len() > 0) {
+    if (short != '\0') && (!long.is_empty()) {
         len_short + len_sep + len_long
-    } else if short.is_alphabetic() && long.is_empty() {
+    } else if (short != '\0') && long.is_empty() {
         len_short
     } else if !long.is_empty() {
         // same as a fully-specified arg because we pad the short and separator
@@ -41,12 +48,339 @@ pub fn arg_string_len(short: char, long: &'static str) -> usize {
     }
 }
 
+// wraps the text in the given ANSI style when color is enabled
+fn paint(s: &str, style: &str, color: bool) -> String {
+    if color {
+        format!("\x1b[{}m{}\x1b[0m", style, s)
+    } else {
+        s.to_string()
+    }
+}
+
+// the bracketed annotations following a description (e.g. ` [required, default: 7]`),
+// with the required marker in red when color is enabled
+fn accessories(required: bool, default: Option<&str>, max: Option<&str>, color: bool)
+    -> String
+{
+    let mut parts = vec!();
+    if required {
+        parts.push(paint("required", STYLE_REQUIRED, color));
+    }
+    if let Some(d) = default {
+        parts.push(format!("default: {}", d));
+    }
+    if let Some(m) = max {
+        parts.push(format!("max: {}", m));
+    }
+
+    if parts.is_empty() {
+        "".to_string()
+    } else {
+        format!(" [{}]", parts.join(", "))
+    }
+}
+
+// the `[choices: a|b|c]` suffix listing the allowed values, if restricted
+fn choices_string(choices: Option<&'static [&'static str]>) -> String {
+    match choices {
+        Some(c) => { format!(" [choices: {}]", c.join("|")) }
+        None => { "".to_string() }
+    }
+}
+
+/// Accessory describes the annotations of an argument or positional which follow its
+/// description in the help dialog, as rendered by the function given to
+/// [Parser::accessory_format](../struct.Parser.html#method.accessory_format).
+pub struct Accessory<'a> {
+    pub required: bool,
+    pub default: Option<&'a str>,
+    pub choices: Option<&'static [&'static str]>,
+    pub env: Option<&'static str>,
+    /// The inclusive bounds of the value (e.g. `1..=64`).
+    pub range: Option<&'a str>,
+    /// The value a count stops at.
+    pub max: Option<&'a str>,
+}
+
+pub type AccessoryFormat = Box<dyn Fn(&Accessory) -> String>;
+
+// the default rendering of the annotations (e.g. ` [choices: a|b] [required, default: a]`)
+fn default_accessory(a: &Accessory, color: bool) -> String {
+    format!("{}{}{}{}", choices_string(a.choices), range_string(a.range),
+        accessories(a.required, a.default, a.max, color), env_string(a.env))
+}
+
+// the `[range: min..=max]` suffix giving the inclusive bounds of a value, if bounded
+fn range_string(range: Option<&str>) -> String {
+    match range {
+        Some(r) => { format!(" [range: {}]", r) }
+        None => { "".to_string() }
+    }
+}
+
+// the `[env: VAR]` suffix naming the environment variable an arg falls back to
+fn env_string(env: Option<&'static str>) -> String {
+    match env {
+        Some(e) => { format!(" [env: {}]", e) }
+        None => { "".to_string() }
+    }
+}
+
+// the number of columns the string occupies when printed. when `ansi` is set, escape
+// sequences (e.g. `\x1b[1m`) are skipped as they are not visible
+fn text_width(s: &str, ansi: bool) -> usize {
+    if !ansi {
+        return s.len();
+    }
+
+    let mut width = 0;
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            width += 1;
+            continue;
+        }
+        // CSI sequences are `ESC [` followed by parameters and end with a byte in '@'..='~'
+        if chars.peek() == Some(&'[') {
+            chars.next();
+            for t in chars.by_ref() {
+                if ('@'..='~').contains(&t) { break; }
+            }
+        }
+    }
+    width
+}
+
+/// The order options are printed in within each section of the help dialog, as set by
+/// [Parser::option_sort](../struct.Parser.html#method.option_sort). Parsing is not affected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionSort {
+    /// The order the options were declared in.
+    Declared,
+    /// Alphabetical by long name, or by short code for options without a long name.
+    Alphabetical,
+    /// Options with a short code first, then long-only options, each in declared order.
+    ShortsFirst,
+}
+
+/// A hint for how shells should complete the value of an option, as given to
+/// [Parser::arg_completed](../struct.Parser.html#method.arg_completed).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Completion {
+    /// Paths to files (and the directories leading to them).
+    File,
+    /// Paths to directories only.
+    Dir,
+    /// Known host names.
+    Host,
+    /// A fixed list of words.
+    Words(&'static [&'static str]),
+}
+impl Completion {
+    // the `compgen` arguments which produce the candidates in bash
+    fn bash_compgen(&self) -> String {
+        match self {
+            Completion::File => { "-f".to_string() }
+            Completion::Dir => { "-d".to_string() }
+            Completion::Host => { "-A hostname".to_string() }
+            Completion::Words(words) => { format!("-W \"{}\"", words.join(" ")) }
+        }
+    }
+
+    // the `_arguments` action which produces the candidates in zsh
+    pub(crate) fn zsh_action(&self) -> String {
+        match self {
+            Completion::File => { "_files".to_string() }
+            Completion::Dir => { "_files -/".to_string() }
+            Completion::Host => { "_hosts".to_string() }
+            Completion::Words(words) => {
+                format!("({})", words.iter()
+                    .map(|w| w.replace(' ', "\\ "))
+                    .collect::<Vec<String>>().join(" "))
+            }
+        }
+    }
+}
+
+/// Whether the help dialog is printed with color, as set by
+/// [Parser::color](../struct.Parser.html#method.color).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Always print with color.
+    Always,
+    /// Never print with color.
+    Never,
+    /// Print with color only when printing to a terminal.
+    Auto,
+}
+
+/// Where the help dialog marks required arguments and positionals, as set by
+/// [Parser::required_marker](../struct.Parser.html#method.required_marker).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequiredMarker {
+    /// A `[required]` annotation following the description.
+    TrailingBracket,
+    /// A `*` before the option string or positional name.
+    LeadingAsterisk,
+    /// Required items are not marked.
+    None,
+}
+
+/// HelpModel is the structured content of the help dialog, as given to the renderer set by
+/// [Parser::set_help_renderer](../struct.Parser.html#method.set_help_renderer). As with
+/// the built-in dialog, it describes the leaf subcommand and only what was declared
+/// while help was requested.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpModel {
+    /// Application name followed by the names of the matched subcommands.
+    pub name: String,
+    pub version: &'static str,
+    /// Short description of the application, or of the leaf subcommand.
+    pub desc: &'static str,
+    /// Long description of the application, or of the leaf subcommand.
+    pub long_desc: &'static str,
+    /// The usage synopsis, without the `usage: ` prefix.
+    pub usage: Option<String>,
+    /// Names and descriptions of the subcommands available at this level.
+    pub subcommands: Vec<(&'static str, &'static str)>,
+    /// Options not within a group.
+    pub options: Vec<HelpOption>,
+    pub groups: Vec<HelpGroup>,
+    pub positionals: Vec<HelpPositional>,
+}
+
+/// An option within a [HelpModel](struct.HelpModel.html). As when declaring, a missing
+/// short or long code is `'\0'` or `""` respectively.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpOption {
+    pub short: char,
+    pub long: &'static str,
+    pub desc: &'static str,
+    pub label: Option<&'static str>,
+    pub default: Option<String>,
+    pub required: bool,
+    /// The codes of the opposing flag when declared as
+    /// [Parser::linked_flags](../struct.Parser.html#method.linked_flags).
+    pub linked: Option<(char, &'static str)>,
+    /// The environment variable it falls back to, as declared by
+    /// [Parser::arg_env](../struct.Parser.html#method.arg_env).
+    pub env: Option<&'static str>,
+    /// The allowed values, as declared by
+    /// [Parser::arg_choices](../struct.Parser.html#method.arg_choices).
+    pub choices: Option<&'static [&'static str]>,
+    /// The inclusive bounds of the value, as declared by
+    /// [Parser::arg_range](../struct.Parser.html#method.arg_range).
+    pub range: Option<String>,
+    /// The value a count stops at, as declared by
+    /// [Parser::count_max](../struct.Parser.html#method.count_max).
+    pub max: Option<String>,
+}
+
+impl HelpOption {
+    // the annotations following the description, as given to an accessory format
+    fn accessory(&self) -> Accessory<'_> {
+        Accessory{
+            required: self.required, default: self.default.as_deref(), choices: self.choices,
+            env: self.env, range: self.range.as_deref(), max: self.max.as_deref()
+        }
+    }
+}
+
+/// A group of options within a [HelpModel](struct.HelpModel.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpGroup {
+    pub name: &'static str,
+    pub desc: &'static str,
+    pub options: Vec<HelpOption>,
+}
+
+/// A positional within a [HelpModel](struct.HelpModel.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpPositional {
+    pub name: &'static str,
+    pub desc: &'static str,
+    pub default: Option<String>,
+    pub required: bool,
+    pub variadic: bool,
+    pub choices: Option<&'static [&'static str]>,
+    /// The positional group it was declared within, if any.
+    pub group: Option<&'static str>,
+}
+
+// orders the options of a section for printing
+fn sort_options(mut opts: Vec<&Argument>, sort: OptionSort) -> Vec<&Argument> {
+    match sort {
+        OptionSort::Declared => {}
+        OptionSort::Alphabetical => {
+            opts.sort_by_key(|o| {
+                if o.long.is_empty() { o.short.to_string() } else { o.long.to_lowercase() }
+            });
+        }
+        OptionSort::ShortsFirst => {
+            opts.sort_by_key(|o| o.short == '\0');
+        }
+    }
+    opts
+}
+
+// settings shared by every line of the help dialog
+struct Layout<'a> {
+    longest_left: usize,
+    indent: usize,
+    gap: usize,
+    ansi: bool,
+    sort: OptionSort,
+    color: bool,
+    accessory: Option<&'a dyn Fn(&Accessory) -> String>, // custom rendering, if set
+    required_marker: RequiredMarker,
+}
+impl Layout<'_> {
+    // the padding between a left column of the given text and its description
+    fn mid(&self, left: &str) -> String {
+        " ".repeat(self.longest_left.saturating_sub(text_width(left, self.ansi)) + self.gap)
+    }
+
+    // wraps the text in the given ANSI style when color is enabled
+    fn paint(&self, s: &str, style: &str) -> String {
+        paint(s, style, self.color)
+    }
+
+    // the padding before the left column, which holds the leading required marker if used
+    fn left(&self, left_pad: usize, required: bool) -> String {
+        if required && (self.required_marker == RequiredMarker::LeadingAsterisk) {
+            format!("{}{} ", " ".repeat(left_pad.saturating_sub(2)), self.paint("*", STYLE_REQUIRED))
+        } else {
+            " ".repeat(left_pad)
+        }
+    }
+
+    // renders the annotations following a description. only the default rendering
+    // highlights the required marker, and items are only annotated as required when
+    // the marker trails the description
+    fn accessory(&self,
+        required: bool, default: &Option<String>, choices: Option<&'static [&'static str]>,
+        env: Option<&'static str>, range: &Option<String>, max: &Option<String>
+    ) -> String
+    {
+        let required = required && (self.required_marker == RequiredMarker::TrailingBracket);
+        let accessory = Accessory{
+            required, default: default.as_deref(), choices, env,
+            range: range.as_deref(), max: max.as_deref()
+        };
+        match self.accessory {
+            Some(format) => { format(&accessory) }
+            None => { default_accessory(&accessory, self.color) }
+        }
+    }
+}
+
 trait Descriptor {
-    fn left_len(&self) -> usize;
+    fn left_len(&self, ansi: bool) -> usize;
 }
 trait Printable {
     fn should_print(&self) -> bool;
-    fn print(&self, left_pad: usize, longest_left: usize);
+    fn print(&self, w: &mut dyn Write, left_pad: usize, layout: &Layout)
+        -> std::io::Result<()>;
 }
 
 
@@ -57,6 +391,13 @@ pub struct Argument {
     label: Option<&'static str>,
     default: Option<String>,
     required: bool,
+    pair: Option<(char, &'static str)>,
+    completion: Option<Completion>,
+    env: Option<&'static str>,
+    choices: Option<&'static [&'static str]>,
+    range: Option<String>,
+    max: Option<String>,
+    hidden: bool,
 }
 impl Argument {
     pub fn new(
@@ -66,57 +407,138 @@ impl Argument {
     ) -> Argument
     {
         Argument{
-            short: short,
-            long: long,
-            desc: desc,
-            label: label,
-            default: match default {
-                Some(d) => { if d.is_empty() { None } else { Some(d) } }
-                None => { None }
-            },
-            required: required,
+            short,
+            long,
+            desc,
+            label,
+            default: default.filter(|d| !d.is_empty()),
+            required,
+            pair: None,
+            completion: None,
+            env: None,
+            choices: None,
+            range: None,
+            max: None,
+            hidden: false,
         }
     }
 
+    /// Sets how shells should complete the value of this argument.
+    pub fn with_completion(mut self, completion: Completion) -> Argument {
+        self.completion = Some(completion);
+        self
+    }
+
+    /// Sets the environment variable this argument falls back to.
+    pub fn with_env(mut self, env: &'static str) -> Argument {
+        self.env = Some(env);
+        self
+    }
+
+    /// Restricts this argument to the given values, which are listed in the help dialog.
+    pub fn with_choices(mut self, choices: &'static [&'static str]) -> Argument {
+        self.choices = Some(choices);
+        self
+    }
+
+    /// Sets the inclusive bounds of this argument, which are shown in the help dialog.
+    pub fn with_range(mut self, range: String) -> Argument {
+        self.range = Some(range);
+        self
+    }
+
+    /// Sets the value this argument stops counting at, which is shown in the help dialog.
+    pub fn with_max(mut self, max: String) -> Argument {
+        self.max = Some(max);
+        self
+    }
+
+    // each code the argument can be given as (e.g. `-f` and `--file`)
+    fn codes(&self) -> Vec<String> {
+        let mut result = vec!();
+        let pair = self.pair.unwrap_or(('\0', ""));
+        for (short, long) in [(self.short, self.long), pair] {
+            if short != '\0' {
+                result.push(format!("-{}", short));
+            }
+            if !long.is_empty() {
+                result.push(format!("--{}", long));
+            }
+        }
+        result
+    }
+
+    /// Marks this argument as one half of a linked pair (e.g. `--verbose`/`--quiet`)
+    /// so that both codes are printed on the same line.
+    pub fn linked(mut self, short: char, long: &'static str) -> Argument {
+        self.pair = Some((short, long));
+        self
+    }
+
     pub fn arg_string(&self) -> String {
-        if let Some(l) = self.label {
-            format!("{} {}", arg_string(self.short, self.long, true), l)
+        self.code_string(true)
+    }
+
+    fn model(&self) -> HelpOption {
+        HelpOption{
+            short: self.short,
+            long: self.long,
+            desc: self.desc,
+            label: self.label,
+            default: self.default.clone(),
+            required: self.required,
+            linked: self.pair,
+            env: self.env,
+            choices: self.choices,
+            range: self.range.clone(),
+            max: self.max.clone(),
+        }
+    }
+
+    // the arg codes and label, optionally padding long-only args to align with shorts
+    fn code_string(&self, pad: bool) -> String {
+        let base = if let Some(l) = self.label {
+            format!("{} {}", arg_string(self.short, self.long, pad), l)
         } else {
-            arg_string(self.short, self.long, true)
+            arg_string(self.short, self.long, pad)
+        };
+
+        match self.pair {
+            Some((short, long)) => { format!("{} / {}", base, arg_string(short, long, false)) }
+            None => { base }
         }
     }
 }
 impl Descriptor for Argument {
-    fn left_len(&self) -> usize {
+    fn left_len(&self, ansi: bool) -> usize {
         let base = arg_string_len(self.short, self.long);
-        if let Some(l) = self.label {
-            base + 1 + l.len()
+        let base = if let Some(l) = self.label {
+            base + 1 + text_width(l, ansi)
         } else {
             base
+        };
+
+        match self.pair {
+            // ' / ' separator followed by the unpadded pair
+            Some((short, long)) => { base + 3 + arg_string(short, long, false).len() }
+            None => { base }
         }
     }
 }
 impl Printable for Argument {
     fn should_print(&self) -> bool {
-        self.short.is_alphabetic() || (!self.long.is_empty())
+        !self.hidden && ((self.short != '\0') || (!self.long.is_empty()))
     }
-    fn print(&self, left_pad: usize, longest_left: usize) {
+    fn print(&self, w: &mut dyn Write, left_pad: usize, layout: &Layout)
+        -> std::io::Result<()>
+    {
         let args = self.arg_string();
-        let left = " ".repeat(left_pad);
-        let mid = " ".repeat(longest_left - args.len() + MID_PAD_LENGTH);
-
-        let has_default = self.default.is_some();
-        let accesories = if has_default && self.required {
-            format!(" [required, default: {}]", self.default.as_ref().unwrap())
-        } else if has_default {
-            format!(" [default: {}]", self.default.as_ref().unwrap())
-        } else if self.required {
-            " [required]".to_string()
-        } else {
-            "".to_string()
-        };
+        let left = layout.left(left_pad, self.required);
+        let mid = layout.mid(&args);
+
+        let accesories = layout.accessory(self.required, &self.default, self.choices, self.env, &self.range, &self.max);
 
-        println!("{}{}{}{}{}", left, args, mid, self.desc, accesories);
+        writeln!(w, "{}{}{}{}{}", left, layout.paint(&args, STYLE_CODE), mid, self.desc, accesories)
     }
 }
 
@@ -126,7 +548,9 @@ pub struct Positional {
     desc: &'static str,
     default: Option<String>,
     required: bool,
-    variadic: bool
+    variadic: bool,
+    group: Option<&'static str>,
+    choices: Option<&'static [&'static str]>,
 }
 impl Positional {
     pub fn new(
@@ -135,14 +559,22 @@ impl Positional {
     ) -> Positional
     {
         Positional{
-            name: name,
-            desc: desc,
-            default: default,
-            required: required,
-            variadic: variadic,
+            name,
+            desc,
+            default,
+            required,
+            variadic,
+            group: None,
+            choices: None,
         }
     }
 
+    /// Restricts this positional to the given values, which are listed in the help dialog.
+    pub fn with_choices(mut self, choices: &'static [&'static str]) -> Positional {
+        self.choices = Some(choices);
+        self
+    }
+
     pub fn display_name(&self) -> String {
         if self.variadic {
             format!("{}...", self.name)
@@ -152,31 +584,24 @@ impl Positional {
     }
 }
 impl Descriptor for Positional {
-    fn left_len(&self) -> usize {
-        self.name.len()
+    fn left_len(&self, ansi: bool) -> usize {
+        text_width(&self.display_name(), ansi)
     }
 }
 impl Printable for Positional {
     fn should_print(&self) -> bool {
         !self.name.is_empty()
     }
-    fn print(&self, left_pad: usize, longest_left: usize) {
+    fn print(&self, w: &mut dyn Write, left_pad: usize, layout: &Layout)
+        -> std::io::Result<()>
+    {
         let display_name = self.display_name();
-        let left = " ".repeat(left_pad);
-        let mid = " ".repeat(longest_left - display_name.len() + MID_PAD_LENGTH);
-
-        let has_default = self.default.is_some();
-        let accesories = if has_default && self.required {
-            format!(" [required, default: {}]", self.default.as_ref().unwrap())
-        } else if has_default {
-            format!(" [default: {}]", self.default.as_ref().unwrap())
-        } else if self.required {
-            " [required]".to_string()
-        } else {
-            "".to_string()
-        };
+        let left = layout.left(left_pad, self.required);
+        let mid = layout.mid(&display_name);
 
-        println!("{}{}{}{}{}", left, display_name, mid, self.desc, accesories);
+        let accesories = layout.accessory(self.required, &self.default, self.choices, None, &None, &None);
+
+        writeln!(w, "{}{}{}{}{}", left, display_name, mid, self.desc, accesories)
     }
 }
 
@@ -187,24 +612,26 @@ pub struct Subcommand {
 impl Subcommand {
     pub fn new(name: &'static str, desc: &'static str) -> Subcommand {
         Subcommand{
-            name: name,
-            desc: desc,
+            name,
+            desc,
         }
     }
 }
 impl Descriptor for Subcommand {
-    fn left_len(&self) -> usize {
-        self.name.len()
+    fn left_len(&self, ansi: bool) -> usize {
+        text_width(self.name, ansi)
     }
 }
 impl Printable for Subcommand {
     fn should_print(&self) -> bool {
         !self.name.is_empty()
     }
-    fn print(&self, left_pad: usize, longest_left: usize) {
+    fn print(&self, w: &mut dyn Write, left_pad: usize, layout: &Layout)
+        -> std::io::Result<()>
+    {
         let left = " ".repeat(left_pad);
-        let mid = " ".repeat(longest_left - self.name.len() + MID_PAD_LENGTH);
-        println!("{}{}{}{}", left, self.name, mid, self.desc);
+        let mid = layout.mid(self.name);
+        writeln!(w, "{}{}{}{}", left, self.name, mid, self.desc)
     }
 }
 
@@ -216,29 +643,120 @@ pub struct Group {
 impl Group {
     pub fn new(name: &'static str, desc: &'static str) -> Group {
         Group {
-            name: name,
-            desc: desc,
+            name,
+            desc,
             opts: vec!(),
         }
     }
 }
 impl Printable for Group {
     fn should_print(&self) -> bool {
-        (!self.name.is_empty()) && (!self.opts.is_empty())
+        (!self.name.is_empty()) && self.opts.iter().any(|o| o.should_print())
     }
-    fn print(&self, left_pad: usize, longest_left: usize) {
-        let mid = " ".repeat(
-            // get the basic padding based on the naem
-            longest_left - self.name.len() +
-            // we do not pad left, so add that back in
-            // add in the middle padding all args share
-            // subtract the ':' after the name
-            LEFT_PAD_LENGTH + MID_PAD_LENGTH - 1
-        );
-        println!("{}:{}{}", self.name, mid, self.desc);
-        for o in self.opts.iter() {
-            o.print(left_pad + LEFT_PAD_LENGTH, longest_left);
+    fn print(&self, w: &mut dyn Write, left_pad: usize, layout: &Layout)
+        -> std::io::Result<()>
+    {
+        print_group_header(w, self.name, self.desc, layout)?;
+        for o in sort_options(self.opts.iter().collect(), layout.sort) {
+            o.print(w, left_pad + layout.indent, layout)?;
         }
+        Ok(())
+    }
+}
+
+// prints the `name:    desc` line heading a named section of the help dialog
+fn print_group_header(w: &mut dyn Write,
+    name: &'static str, desc: &'static str, layout: &Layout
+) -> std::io::Result<()>
+{
+    let mid = " ".repeat(std::cmp::max(1,
+        // get the basic padding based on the longest arg
+        // we do not pad left, so add that back in
+        // add in the middle padding all args share
+        // subtract the ':' after the name
+        (layout.longest_left + layout.indent + layout.gap).saturating_sub(1)
+        // and finally remove the name itself
+            .saturating_sub(text_width(name, layout.ansi))
+    ));
+    writeln!(w, "{}{}{}", layout.paint(&format!("{}:", name), STYLE_HEADER), mid, desc)
+}
+
+// escapes text for a table cell, where a pipe would end the cell and a newline
+// would end the row
+fn markdown_escape(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', "<br>")
+}
+
+// the description followed by any annotations (e.g. ` [choices: a|b]`)
+fn markdown_desc(desc: &'static str, annotations: &str, required: bool) -> String {
+    let desc = markdown_escape(&format!("{}{}", desc, annotations));
+    if required {
+        format!("{} *(required)*", desc)
+    } else {
+        desc
+    }
+}
+
+fn markdown_default(default: &Option<String>) -> String {
+    match default {
+        Some(d) => { format!("`{}`", markdown_escape(d)) }
+        None => { "".to_string() }
+    }
+}
+
+fn markdown_options(out: &mut String, opts: &[Argument], sort: OptionSort) {
+    out.push_str("| option | description | default |\n");
+    out.push_str("|---|---|---|\n");
+    for o in sort_options(opts.iter().collect(), sort) {
+        if !o.should_print() { continue; }
+        // required and the default have a marker and column of their own
+        let model = o.model();
+        let annotations = Accessory{ required: false, default: None, ..model.accessory() };
+        out.push_str(&format!("| `{}` | {} | {} |\n",
+            markdown_escape(&o.code_string(false)),
+            markdown_desc(o.desc, &default_accessory(&annotations, false), o.required),
+            markdown_default(&o.default)
+        ));
+    }
+    out.push('\n');
+}
+
+// escapes text so that it is printed literally by roff
+fn roff_escape(s: &str) -> String {
+    let escaped = s.replace('\\', "\\e").replace('-', "\\-");
+    // a control character leading any line would be interpreted as a request
+    escaped.split('\n')
+        .map(|l| if l.starts_with('.') || l.starts_with('\'') {
+            format!("\\&{}", l)
+        } else {
+            l.to_string()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// escapes each line of a (possibly multi-line) text block, with blank lines
+// becoming paragraph breaks
+fn roff_paragraphs(text: &str) -> String {
+    let mut out = String::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            out.push_str(".PP\n");
+        } else {
+            out.push_str(&format!("{}\n", roff_escape(line)));
+        }
+    }
+    out
+}
+
+fn roff_options(out: &mut String, opts: &[Argument], sort: OptionSort) {
+    for o in sort_options(opts.iter().collect(), sort) {
+        if !o.should_print() { continue; }
+        out.push_str(&format!(".TP\n.B {}\n{}\n",
+            roff_escape(&o.code_string(false)),
+            roff_escape(&format!("{}{}", o.desc, default_accessory(&o.model().accessory(), false)))
+        ));
     }
 }
 
@@ -248,6 +766,7 @@ pub struct App {
     short_desc: &'static str,
     long_desc: &'static str,
     version: &'static str,
+    usage_name: Option<String>,
 }
 impl App {
     pub fn empty() -> App {
@@ -259,11 +778,12 @@ impl App {
         vers: &'static str
     ) -> App {
         App{
-            name: name,
+            name,
             subnames: vec!(),
             short_desc: short,
             long_desc: long,
             version: vers,
+            usage_name: None,
         }
     }
 
@@ -277,31 +797,44 @@ impl App {
     }
 
     pub fn display_name(&self) -> String {
+        self.with_subnames(self.name)
+    }
+
+    /// Name used in the usage line, which may differ from the application name
+    /// (e.g. when invoked via a symlink).
+    pub fn usage_display_name(&self) -> String {
+        match self.usage_name {
+            Some(ref n) => { self.with_subnames(n) }
+            None => { self.display_name() }
+        }
+    }
+
+    fn with_subnames(&self, name: &str) -> String {
         if self.subnames.is_empty() {
-            self.name.to_string()
+            name.to_string()
         } else {
-            format!("{} {}", self.name,
+            format!("{} {}", name,
                 self.subnames.iter().map(|n| n.to_string()).collect::<Vec<String>>().join(" "))
         }
     }
 }
 impl Printable for App {
     fn should_print(&self) -> bool {
-        self.name.is_empty() == false
+        !self.name.is_empty()
     }
-    fn print(&self, _: usize, _: usize) {
+    fn print(&self, w: &mut dyn Write, _: usize, _: &Layout) -> std::io::Result<()> {
         let has_name = !self.name.is_empty();
         let has_vers = !self.version.is_empty();
         let has_desc = !self.short_desc.is_empty();
 
         if has_name && has_vers && has_desc {
-            println!("{} - {} - {}", self.display_name(), self.version, self.short_desc);
+            writeln!(w, "{} - {} - {}", self.display_name(), self.version, self.short_desc)?;
         } else if has_name && has_vers {
-            println!("{} - {}", self.display_name(), self.version);
+            writeln!(w, "{} - {}", self.display_name(), self.version)?;
         } else if has_name {
-            println!("{}", self.display_name());
+            writeln!(w, "{}", self.display_name())?;
         }
-        println!("");
+        writeln!(w)
     }
 }
 
@@ -312,19 +845,43 @@ pub struct Printer {
     groups: BTreeMap<&'static str, Group>,
     opts: Vec<Argument>,
     pos: Vec<Positional>,
+    pos_groups: BTreeMap<&'static str, &'static str>,
+    one_of: Vec<Vec<&'static str>>, // long names of options of which one is required
+    last_arg: Option<Option<&'static str>>, // group of the argument just added, if any
 
     longest_left: usize,
+    width: usize,
+    indent: usize,
+    gap: usize,
+    ansi: bool,
+    sort: OptionSort,
+    flatten_groups: bool,
+    color: ColorMode,
+    accessory: Option<AccessoryFormat>,
+    required_marker: RequiredMarker,
 }
 impl Printer {
     pub fn new(app: App) -> Printer {
         Printer {
-            app: app,
+            app,
             subs: vec!(),
             groups: BTreeMap::new(),
             opts: vec!(),
             pos: vec!(),
+            pos_groups: BTreeMap::new(),
+            one_of: vec!(),
+            last_arg: None,
 
             longest_left: 0usize,
+            width: DEFAULT_HELP_WIDTH,
+            indent: LEFT_PAD_LENGTH,
+            gap: MID_PAD_LENGTH,
+            ansi: false,
+            sort: OptionSort::Declared,
+            flatten_groups: false,
+            color: ColorMode::Never,
+            accessory: None,
+            required_marker: RequiredMarker::TrailingBracket,
         }
     }
 
@@ -332,72 +889,394 @@ impl Printer {
         &mut self, named: &'static str,
         short_desc: &'static str, long_desc: &'static str
     ) {
+        // the subcommands of the enclosing level are no longer printed, so they must not
+        // widen the left column either
         self.subs.clear();
+        self.recalculate_longest();
         self.app.append_subcommand(named, short_desc, long_desc);
     }
 
-    pub fn print(&self) {
+    /// Prints the help dialog, where `terminal` is whether the writer is a terminal and
+    /// so decides if [ColorMode::Auto](enum.ColorMode.html#variant.Auto) uses color.
+    pub fn print<W: Write>(&self, w: &mut W, terminal: bool) -> std::io::Result<()> {
+        let color = match self.color {
+            ColorMode::Always => { true }
+            ColorMode::Never => { false }
+            ColorMode::Auto => { terminal }
+        };
+        self.print_colored(w, color)
+    }
+
+    fn print_colored<W: Write>(&self, w: &mut W, color: bool) -> std::io::Result<()> {
         let pos_usage = self.generate_positionals();
+        let layout = Layout{
+            longest_left: self.longest_left,
+            indent: self.indent,
+            gap: self.gap,
+            ansi: self.ansi,
+            sort: self.sort,
+            color,
+            accessory: self.accessory.as_deref(),
+            required_marker: self.required_marker,
+        };
 
         if self.app.should_print() {
-            self.app.print(0, 0);
+            self.app.print(w, 0, &layout)?;
         }
 
-        let group_args_count = self.groups.iter()
-            .fold(0, |acc, (_, grp)| acc + grp.opts.len());
-        let has_args = (!self.opts.is_empty()) || (group_args_count > 0);
-
-        if has_args {
-            println!("usage: {} {}", self.app.display_name(), self.generate_usage(pos_usage));
-            println!("");
+        if let Some(parts) = self.usage_parts(pos_usage) {
+            writeln!(w, "{}", self.wrapped_usage(parts))?;
+            writeln!(w)?;
         }
 
         if !self.app.long_desc.is_empty() {
-            println!("{}", self.app.long_desc);
-            println!("");
+            writeln!(w, "{}", self.app.long_desc)?;
+            writeln!(w)?;
         }
 
         if !self.subs.is_empty() {
-            println!("subcommands:");
+            writeln!(w, "{}", layout.paint("subcommands:", STYLE_HEADER))?;
             for s in self.subs.iter() {
                 if !s.should_print() { continue; }
-                s.print(LEFT_PAD_LENGTH, self.longest_left);
+                s.print(w, layout.indent, &layout)?;
             }
-            println!("");
+            writeln!(w)?;
         }
 
-        for (_, desc) in self.groups.iter() {
-            if !desc.should_print() { continue; }
-            desc.print(0, self.longest_left); // NOTE: groups print at left-offset 0
-            println!("");
+        if !self.flatten_groups {
+            for (_, desc) in self.groups.iter() {
+                if !desc.should_print() { continue; }
+                desc.print(w, 0, &layout)?; // NOTE: groups print at left-offset 0
+                writeln!(w)?;
+            }
         }
 
-        if !self.opts.is_empty() {
-            println!("options:");
-            for o in self.opts.iter() {
+        // when flattened, group options are listed after the ungrouped options
+        let grouped = self.groups.iter()
+            .filter(|_| self.flatten_groups)
+            .flat_map(|(_, g)| g.opts.iter())
+            .collect::<Vec<&Argument>>();
+        if !self.opts.is_empty() || !grouped.is_empty() {
+            writeln!(w, "{}", layout.paint("options:", STYLE_HEADER))?;
+            for o in sort_options(self.opts.iter().chain(grouped).collect(), self.sort) {
                 if !o.should_print() { continue; }
-                o.print(LEFT_PAD_LENGTH, self.longest_left);
+                o.print(w, layout.indent, &layout)?;
             }
-            println!("");
+            writeln!(w)?;
         }
 
-        if !self.pos.is_empty() {
-            println!("positionals:");
-            for p in self.pos.iter() {
+        if self.pos.iter().any(|p| p.group.is_none()) {
+            writeln!(w, "{}", layout.paint("positionals:", STYLE_HEADER))?;
+            for p in self.pos.iter().filter(|p| p.group.is_none()) {
                 if !p.should_print() { continue; }
-                p.print(LEFT_PAD_LENGTH, self.longest_left);
+                p.print(w, layout.indent, &layout)?;
+            }
+            writeln!(w)?;
+        }
+
+        for (name, desc) in self.pos_groups.iter() {
+            let members = self.pos.iter().filter(|p| p.group == Some(name)).collect::<Vec<_>>();
+            if members.is_empty() { continue; }
+
+            print_group_header(w, name, desc, &layout)?;
+            for p in members {
+                if !p.should_print() { continue; }
+                p.print(w, layout.indent, &layout)?;
+            }
+            writeln!(w)?;
+        }
+
+        Ok(())
+    }
+
+    /// The structured content of the help dialog.
+    pub fn model(&self) -> HelpModel {
+        HelpModel{
+            name: self.app.display_name(),
+            version: self.app.version,
+            desc: self.app.short_desc,
+            long_desc: self.app.long_desc,
+            usage: self.usage_line(self.generate_positionals()),
+            subcommands: self.subs.iter().map(|s| (s.name, s.desc)).collect(),
+            options: sort_options(self.opts.iter().collect(), self.sort).into_iter()
+                .filter(|o| o.should_print())
+                .map(|o| o.model())
+                .collect(),
+            groups: self.groups.iter()
+                .filter(|(_, g)| g.should_print())
+                .map(|(_, g)| HelpGroup{
+                    name: g.name,
+                    desc: g.desc,
+                    options: sort_options(g.opts.iter().collect(), self.sort).into_iter()
+                        .filter(|o| o.should_print())
+                        .map(|o| o.model())
+                        .collect(),
+                })
+                .collect(),
+            positionals: self.pos.iter()
+                .map(|p| HelpPositional{
+                    name: p.name,
+                    desc: p.desc,
+                    default: p.default.clone(),
+                    required: p.required,
+                    variadic: p.variadic,
+                    choices: p.choices,
+                    group: p.group,
+                })
+                .collect(),
+        }
+    }
+
+    // the usage synopsis (without the `usage: ` prefix), if there are any args to show
+    fn usage_line(&self, pos_usage: String) -> Option<String> {
+        self.usage_parts(pos_usage)
+            .map(|parts| format!("{} {}", self.app.usage_display_name(), parts.join(" ")))
+    }
+
+    // the command name followed by the units of the synopsis, if there are any args to show
+    fn usage_parts(&self, pos_usage: String) -> Option<Vec<String>> {
+        let group_args_count = self.groups.iter()
+            .fold(0, |acc, (_, grp)| acc + grp.opts.len());
+        let has_args = (!self.opts.is_empty()) || (group_args_count > 0) || (!self.pos.is_empty());
+
+        if has_args {
+            Some(self.generate_usage(pos_usage))
+        } else {
+            None
+        }
+    }
+
+    // renders the `usage: ` line wrapped at the help width, with continuation lines
+    // indented under the first part following the command name
+    fn wrapped_usage(&self, parts: Vec<String>) -> String {
+        let prefix = format!("usage: {}", self.app.usage_display_name());
+        let indent = " ".repeat(prefix.len() + 1);
+
+        let mut out = String::new();
+        let mut line = prefix;
+        let mut line_has_parts = false;
+        for p in parts.iter() {
+            let line_width = text_width(&line, self.ansi);
+            if line_has_parts && (line_width + 1 + text_width(p, self.ansi) > self.width) {
+                out.push_str(&line);
+                out.push('\n');
+                line = format!("{}{}", indent, p);
+            } else {
+                line.push(' ');
+                line.push_str(p);
+            }
+            line_has_parts = true;
+        }
+        out.push_str(&line);
+        out
+    }
+
+    /// Renders the same information as the help dialog formatted as Markdown.
+    pub fn markdown(&self) -> String {
+        let mut out = String::new();
+
+        let title = self.app.display_name();
+        if !title.is_empty() {
+            out.push_str(&format!("# {}\n\n", title));
+        }
+        if !self.app.short_desc.is_empty() {
+            out.push_str(&format!("{}\n\n", self.app.short_desc));
+        }
+
+        if let Some(usage) = self.usage_line(self.generate_positionals()) {
+            out.push_str(&format!("```text\n{}\n```\n\n", usage.trim_end()));
+        }
+
+        if !self.app.long_desc.is_empty() {
+            out.push_str(&format!("{}\n\n", self.app.long_desc));
+        }
+
+        let subs = self.subs.iter().filter(|s| s.should_print()).collect::<Vec<_>>();
+        if !subs.is_empty() {
+            out.push_str("## Subcommands\n\n");
+            out.push_str("| subcommand | description |\n");
+            out.push_str("|---|---|\n");
+            for s in subs {
+                out.push_str(&format!("| `{}` | {} |\n", s.name, markdown_escape(s.desc)));
+            }
+            out.push('\n');
+        }
+
+        for (_, grp) in self.groups.iter() {
+            if !grp.should_print() { continue; }
+            out.push_str(&format!("## {}\n\n", grp.name));
+            if !grp.desc.is_empty() {
+                out.push_str(&format!("{}\n\n", grp.desc));
+            }
+            markdown_options(&mut out, &grp.opts, self.sort);
+        }
+
+        if self.opts.iter().any(|o| o.should_print()) {
+            out.push_str("## Options\n\n");
+            markdown_options(&mut out, &self.opts, self.sort);
+        }
+
+        let pos = self.pos.iter().filter(|p| p.should_print()).collect::<Vec<_>>();
+        if !pos.is_empty() {
+            out.push_str("## Positionals\n\n");
+            out.push_str("| positional | description | default |\n");
+            out.push_str("|---|---|---|\n");
+            for p in pos {
+                out.push_str(&format!("| `{}` | {} | {} |\n",
+                    p.display_name(),
+                    markdown_desc(p.desc, &choices_string(p.choices), p.required),
+                    markdown_default(&p.default)
+                ));
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+
+    /// Renders a bash completion script for the options and subcommands of this level.
+    /// Options with a completion hint complete their value accordingly.
+    pub fn bash_completion(&self, bin_name: &str) -> String {
+        let func = format!("_{}", bin_name.chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>());
+        let opts = self.opts.iter()
+            .chain(self.groups.values().flat_map(|g| g.opts.iter()))
+            .filter(|o| o.should_print())
+            .collect::<Vec<&Argument>>();
+
+        let mut words = opts.iter().flat_map(|o| o.codes()).collect::<Vec<String>>();
+        words.extend(self.subs.iter().filter(|s| s.should_print()).map(|s| s.name.to_string()));
+
+        let mut out = format!("{}() {{\n", func);
+        out.push_str("    local cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+        out.push_str("    local prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n");
+
+        let hinted = opts.iter()
+            .filter_map(|o| o.completion.map(|c| (o, c)))
+            .collect::<Vec<(&&Argument, Completion)>>();
+        if !hinted.is_empty() {
+            out.push_str("    case \"$prev\" in\n");
+            for (o, c) in hinted {
+                out.push_str(&format!("        {})\n", o.codes().join("|")));
+                out.push_str(&format!("            COMPREPLY=($(compgen {} -- \"$cur\"))\n",
+                    c.bash_compgen()));
+                out.push_str("            return 0\n");
+                out.push_str("            ;;\n");
+            }
+            out.push_str("    esac\n");
+        }
+
+        out.push_str(&format!("    COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n",
+            words.join(" ")));
+        out.push_str("}\n");
+        out.push_str(&format!("complete -F {} {}\n", func, bin_name));
+        out
+    }
+
+    /// Renders the same information as the help dialog as a roff man page.
+    pub fn manpage(&self, section: u8) -> String {
+        let mut out = String::new();
+
+        let title = self.app.display_name();
+        out.push_str(&format!(".TH \"{}\" \"{}\" \"\" \"{}\" \"\"\n",
+            roff_escape(&title.to_uppercase().replace(' ', "-")), section,
+            roff_escape(format!("{} {}", title, self.app.version).trim_end())
+        ));
+
+        out.push_str(".SH NAME\n");
+        if self.app.short_desc.is_empty() {
+            out.push_str(&format!("{}\n", roff_escape(&title)));
+        } else {
+            out.push_str(&format!("{} \\- {}\n",
+                roff_escape(&title), roff_escape(self.app.short_desc)));
+        }
+
+        if let Some(usage) = self.usage_line(self.generate_positionals()) {
+            let args = usage[self.app.usage_display_name().len()..].trim();
+            out.push_str(".SH SYNOPSIS\n");
+            out.push_str(&format!(".B {}\n", roff_escape(&self.app.usage_display_name())));
+            if !args.is_empty() {
+                out.push_str(&format!("{}\n", roff_escape(args)));
             }
-            println!("");
         }
+
+        if !self.app.long_desc.is_empty() {
+            out.push_str(".SH DESCRIPTION\n");
+            out.push_str(&roff_paragraphs(self.app.long_desc));
+        }
+
+        let subs = self.subs.iter().filter(|s| s.should_print()).collect::<Vec<_>>();
+        if !subs.is_empty() {
+            out.push_str(".SH COMMANDS\n");
+            for s in subs {
+                out.push_str(&format!(".TP\n.B {}\n{}\n", roff_escape(s.name), roff_escape(s.desc)));
+            }
+        }
+
+        let has_group_opts = self.groups.iter().any(|(_, g)| g.should_print());
+        if has_group_opts || self.opts.iter().any(|o| o.should_print()) {
+            out.push_str(".SH OPTIONS\n");
+            for (_, grp) in self.groups.iter() {
+                if !grp.should_print() { continue; }
+                out.push_str(&format!(".SS {}\n", roff_escape(grp.name)));
+                if !grp.desc.is_empty() {
+                    out.push_str(&format!("{}\n", roff_escape(grp.desc)));
+                }
+                roff_options(&mut out, &grp.opts, self.sort);
+            }
+            if has_group_opts && !self.opts.is_empty() {
+                out.push_str(".SS options\n");
+            }
+            roff_options(&mut out, &self.opts, self.sort);
+        }
+
+        let pos = self.pos.iter().filter(|p| p.should_print()).collect::<Vec<_>>();
+        if !pos.is_empty() {
+            out.push_str(".SH ARGUMENTS\n");
+            for p in pos {
+                out.push_str(&format!(".TP\n.B {}\n{}\n",
+                    roff_escape(&p.display_name()),
+                    roff_escape(&format!("{}{}{}", p.desc, choices_string(p.choices),
+                        accessories(p.required, p.default.as_deref(), None, false)))
+                ));
+            }
+        }
+
+        out
+    }
+
+    /// Renders the help dialog into a string rather than a stream. The string is never
+    /// colored, as where it is displayed is unknown.
+    pub fn render(&self) -> String {
+        let mut buf: Vec<u8> = vec!();
+        self.print_colored(&mut buf, false).expect("writing to a Vec cannot fail");
+        String::from_utf8(buf).expect("help dialog is not valid utf-8")
     }
 
     fn calculate_longest<T: Descriptor>(&mut self, desc: &T) {
-        self.longest_left = std::cmp::max(self.longest_left, desc.left_len());
+        self.longest_left = std::cmp::max(self.longest_left, desc.left_len(self.ansi));
+    }
+
+    // recomputes the longest left column from the items which are printed
+    fn recalculate_longest(&mut self) {
+        let opts = self.opts.iter()
+            .chain(self.groups.values().flat_map(|g| g.opts.iter()))
+            .filter(|o| o.should_print())
+            .map(|o| o.left_len(self.ansi));
+        let subs = self.subs.iter().map(|s| s.left_len(self.ansi));
+        let pos = self.pos.iter().map(|p| p.left_len(self.ansi));
+        self.longest_left = opts.chain(subs).chain(pos).max().unwrap_or(0);
     }
 
     pub fn set_name(&mut self, name: &'static str) {
         self.app.name = name;
     }
+    /// The application name followed by its version (e.g. `rags 1.0`).
+    pub fn version_line(&self) -> String {
+        format!("{} {}", self.app.name, self.app.version).trim().to_string()
+    }
+
     pub fn set_version(&mut self, vers: &'static str) {
         self.app.version = vers;
     }
@@ -407,15 +1286,45 @@ impl Printer {
     pub fn set_long_desc(&mut self, desc: &'static str) {
         self.app.long_desc = desc;
     }
+    pub fn set_usage_name(&mut self, name: &str) {
+        self.app.usage_name = Some(name.to_string());
+    }
+    pub fn set_width(&mut self, width: usize) {
+        self.width = width;
+    }
+    pub fn set_gap(&mut self, gap: usize) {
+        self.gap = gap;
+    }
+    pub fn set_indent(&mut self, indent: usize) {
+        self.indent = indent;
+    }
+    pub fn set_ansi(&mut self, ansi: bool) {
+        self.ansi = ansi;
+    }
+    pub fn set_sort(&mut self, sort: OptionSort) {
+        self.sort = sort;
+    }
+    pub fn set_color(&mut self, color: ColorMode) {
+        self.color = color;
+    }
+    pub fn set_accessory_format(&mut self, format: AccessoryFormat) {
+        self.accessory = Some(format);
+    }
+    pub fn set_required_marker(&mut self, marker: RequiredMarker) {
+        self.required_marker = marker;
+    }
+    pub fn set_flatten_groups(&mut self) {
+        self.flatten_groups = true;
+    }
 
-    fn generate_usage(&self, positionals: String) -> String {
+    fn generate_usage(&self, positionals: String) -> Vec<String> {
         let mut opt_shorts: Vec<String> = vec!();
         let mut opt_longs: Vec<String> = vec!();
         let mut req_shorts: Vec<String> = vec!();
         let mut req_longs: Vec<String> = vec!();
 
         let mut filter_opt = |o: &Argument| {
-            let (result, is_long) = if o.short.is_alphabetic() {
+            let (result, is_long) = if o.short != '\0' {
                 if let Some(label) = o.label {
                     (format!("-{} {}", o.short, label), true)
                 } else {
@@ -436,105 +1345,102 @@ impl Printer {
                 if is_long { opt_longs.push(result); }
                 else { opt_shorts.push(result); }
             }
+
+            // linked flags never take a value, and are never required
+            if let Some((short, long)) = o.pair {
+                if short != '\0' { opt_shorts.push(short.to_string()); }
+                else { opt_longs.push(format!("--{}", long)); }
+            }
         };
 
-        for o in self.opts.iter() {
+        let all_opts = || self.opts.iter().chain(self.groups.values().flat_map(|g| g.opts.iter()));
+        let in_one_of = |o: &Argument| self.one_of.iter().any(|grp| grp.contains(&o.long));
+        for o in all_opts().filter(|o| !o.hidden && !in_one_of(o)) {
             filter_opt(o);
         }
 
-        for (_, g) in self.groups.iter() {
-            for o in g.opts.iter() {
-                filter_opt(o);
-            }
-        }
-
-        let opt_short_string = opt_shorts.join("");
-        let opt_long_string = opt_longs.join(" ");
+        // each part is a unit the usage line may wrap between (e.g. `-l LIB` stays together)
+        let bracket = |mut parts: Vec<String>| {
+            if let Some(first) = parts.first_mut() { first.insert(0, '['); }
+            if let Some(last) = parts.last_mut() { last.push(']'); }
+            parts
+        };
 
-        let req_short_string = req_shorts.join("");
-        let req_long_string = req_longs.join(" ");
+        let mut opts: Vec<String> = vec!();
+        if !opt_shorts.is_empty() {
+            opts.push(format!("-{}", opt_shorts.join("")));
+        }
+        opts.extend(opt_longs);
+        let opts = bracket(opts);
 
-        let opts = if (!opt_short_string.is_empty()) && (!opt_long_string.is_empty()) {
-            format!("[-{} {}]", opt_short_string, opt_long_string)
-        } else if !opt_short_string.is_empty() {
-            format!("[-{}]", opt_short_string)
-        } else if !opt_long_string.is_empty() {
-            format!("[{}]", opt_long_string)
-        } else {
-            "".to_string()
-        };
+        let mut reqs: Vec<String> = vec!();
+        if !req_shorts.is_empty() {
+            reqs.push(format!("-{}", req_shorts.join("")));
+        }
+        reqs.extend(req_longs);
 
-        let reqs = if (!req_short_string.is_empty()) && (!req_long_string.is_empty()) {
-            format!("-{} {}", req_short_string, req_long_string)
-        } else if !req_short_string.is_empty() {
-            format!("-{}", req_short_string)
-        } else if !req_long_string.is_empty() {
-            format!("{}", req_long_string)
-        } else {
-            "".to_string()
-        };
+        // options of which one is required are listed together (e.g. `( --a | --b X )`)
+        for grp in self.one_of.iter() {
+            let members = grp.iter()
+                .map(|long| match all_opts().find(|o| o.long == *long).and_then(|o| o.label) {
+                    Some(label) => { format!("--{} {}", long, label) }
+                    None => { format!("--{}", long) }
+                })
+                .collect::<Vec<String>>();
+            reqs.push(format!("( {} )", members.join(" | ")));
+        }
 
         let arg_usage = if (!opts.is_empty()) && (!reqs.is_empty()) {
-            format!("{} {}", opts, reqs)
+            [opts, reqs].concat()
         } else if !opts.is_empty() {
             opts
-        } else if !opts.is_empty() {
+        } else if !reqs.is_empty() {
             reqs
         } else {
-            "".to_string()
+            vec!()
         };
 
+        let mut parts: Vec<String> = vec!();
         if !self.subs.is_empty() {
-            format!("{{subcommand}} {} {}", arg_usage, positionals)
-        } else {
-            format!("{} {}", arg_usage, positionals)
+            parts.push("{subcommand}".to_string());
         }
+        parts.extend(arg_usage);
+        parts.extend(positionals.split_whitespace().map(|p| p.to_string()));
+        parts
     }
 
 
+    // positionals in the order they were declared, with optional ones bracketed
     fn generate_positionals(&self) -> String {
-        let mut opt: Vec<String> = vec!();
-        let mut req: Vec<String> = vec!();
-        let mut variadic: String = "".to_string();
-
-        for p in self.pos.iter() {
-            // includes a space so we don't have to do a bunch of checks, just blindly print
-            if p.variadic {
-                if p.required {
-                    variadic = format!(" {}", p.display_name());
-                } else {
-                    variadic = format!(" [{}]", p.display_name());
-                }
-                continue;
-            }
-
-            if p.required {
-                req.push(p.display_name());
+        self.pos.iter()
+            .map(|p| if p.required {
+                p.display_name()
             } else {
-                opt.push(p.display_name());
-            }
-        }
-
-        let opt_str = if !opt.is_empty() {
-            format!("[{}]", opt.join(" "))
-        } else { "".to_string() };
+                format!("[{}]", p.display_name())
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
 
-        let req_str = if !req.is_empty() {
-            format!("{}", req.join(" "))
-        } else { "".to_string() };
 
-        if opt_str.is_empty() && req_str.is_empty() {
-            variadic
-        } else if !opt_str.is_empty() && req_str.is_empty() {
-            format!("{}{}", opt_str, variadic)
-        } else if opt_str.is_empty() && !req_str.is_empty() {
-            format!("{}{}", req_str, variadic)
-        } else {
-            format!("{} {}{}", opt_str, req_str, variadic)
+    /// Forgets the argument most recently added, so a following
+    /// [hide_last](#method.hide_last) has no effect until another is added.
+    pub fn forget_last(&mut self) {
+        self.last_arg = None;
+    }
+    /// Hides the argument most recently added from the help dialog and usage line.
+    pub fn hide_last(&mut self) {
+        let last = match self.last_arg {
+            Some(None) => { self.opts.last_mut() }
+            Some(Some(grp)) => { self.groups.get_mut(grp).and_then(|g| g.opts.last_mut()) }
+            None => { None }
+        };
+        if let Some(o) = last {
+            o.hidden = true;
+            self.recalculate_longest();
         }
     }
 
-
     pub fn add_subcommand(&mut self, sub: Subcommand) {
         // TODO: sanity checking?
         self.calculate_longest(&sub);
@@ -547,6 +1453,7 @@ impl Printer {
     pub fn add_arg(&mut self, opt: Argument, grp: Option<&'static str>) -> Result<(), Error> {
         // TODO: sanity checking?
         self.calculate_longest(&opt);
+        self.last_arg = Some(grp);
 
         if grp.is_none() {
             self.opts.push(opt);
@@ -564,10 +1471,29 @@ impl Printer {
             }
         }
     }
-    pub fn add_positional(&mut self, pos: Positional) -> Result<(), Error> {
+    pub fn add_positional_group(&mut self, name: &'static str, desc: &'static str)
+        -> Result<(), Error>
+    {
+        self.pos_groups.insert(name, desc);
+        Ok(())
+    }
+    pub fn add_positional(&mut self, mut pos: Positional, grp: Option<&'static str>)
+        -> Result<(), Error>
+    {
         // TODO: sanity checking?
+        if let Some(name) = grp {
+            if !self.pos_groups.contains_key(name) {
+                return Err(Error::PrinterMissingGroup(name));
+            }
+        }
+        pos.group = grp;
+
         self.calculate_longest(&pos);
         self.pos.push(pos);
         Ok(())
     }
+
+    pub fn add_one_of(&mut self, longs: &[&'static str]) {
+        self.one_of.push(longs.to_vec());
+    }
 }
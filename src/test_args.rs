@@ -44,6 +44,382 @@ mod args {
         assert!(long == 10, "got unexpected 'long' value: {}", long);
     }
 
+    #[test]
+    fn attached_short_value() {
+        for args in [
+            string_vec!("argv[0]", "-ffoo"),
+            string_vec!("argv[0]", "-f=foo"),
+            string_vec!("argv[0]", "-f", "foo"),
+        ] {
+            let mut file: String = "".to_string();
+            let mut verbose: usize = 0;
+            let mut parser = Parser::from_strings(args.clone());
+            parser
+                .arg('f', "file", "file to handle", &mut file, None, false)
+                    .expect("failed to parse file argument")
+                .count('o', "output", "a count sharing a char with the value", &mut verbose, 1)
+                    .expect("failed to parse count")
+            ;
+            assert_eq!(file, "foo", "unexpected value for {:?}", args);
+            assert_eq!(verbose, 0, "did not expect the value to count for {:?}", args);
+            assert!(parser.unused().is_empty(), "expected no unused args for {:?}", args);
+        }
+
+        let mut libs: Vec<String> = vec!();
+        let mut level: usize = 0;
+        let mut verbose: usize = 0;
+        Parser::from_strings(string_vec!("argv[0]", "-lssl", "-lz", "-vvn5"))
+            .list('l', "lib", "libraries to link", &mut libs, None, false)
+                .expect("failed to parse lib list")
+            .count('v', "verbose", "verbosity", &mut verbose, 1)
+                .expect("failed to parse count")
+            .arg('n', "level", "a level", &mut level, None, false)
+                .expect("failed to parse level")
+        ;
+        assert_eq!(libs, string_vec!("ssl", "z"));
+        assert_eq!(verbose, 2, "expected the run to still count");
+        assert_eq!(level, 5, "expected the value ending the run");
+    }
+
+    #[test]
+    fn case_folded() {
+        let mut format: String = "".to_string();
+        let mut level: String = "".to_string();
+
+        let args = string_vec!("argv[0]", "--format", "JSON", "--level=Warn");
+        Parser::from_strings(args)
+            .arg_lowercase('f', "format", "output format", &mut format, None, false)
+                .expect("failed to parse format argument")
+            .arg_uppercase('l', "level", "log level", &mut level, None, false)
+                .expect("failed to parse level argument")
+        ;
+
+        assert_eq!(format, "json", "did not lowercase value");
+        assert_eq!(level, "WARN", "did not uppercase value");
+    }
+
+    #[test]
+    fn defaults() {
+        let mut output: String = "".to_string();
+        let mut jobs: usize = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-o", "given.txt", "--jobs=8"));
+        parser
+            .arg_default('o', "output", "output file", &mut output, None, "out.txt".to_string())
+                .expect("bad output arg")
+            .arg_default('j', "jobs", "parallel jobs", &mut jobs, None, 4)
+                .expect("bad jobs arg")
+        ;
+        assert_eq!((output.as_str(), jobs), ("given.txt", 8), "expected given values to override");
+
+        let mut output: String = "".to_string();
+        let mut jobs: usize = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]"));
+        parser
+            .arg_default('o', "output", "output file", &mut output, None, "out.txt".to_string())
+                .expect("bad output arg")
+            .arg_default('j', "jobs", "parallel jobs", &mut jobs, None, 4)
+                .expect("bad jobs arg")
+        ;
+        assert_eq!((output.as_str(), jobs), ("out.txt", 4), "expected defaults to be applied");
+        assert!(parser.matched_options().is_empty(), "did not expect defaults to be matched");
+
+        let mut output: String = "".to_string();
+        let mut jobs: usize = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .arg_default('o', "output", "output file", &mut output, None, "out.txt".to_string())
+                .expect("bad output arg")
+            .arg_default('j', "jobs", "parallel jobs", &mut jobs, None, 4)
+                .expect("bad jobs arg")
+        ;
+        let help = parser.printer.render();
+        assert!(help.contains("output file [default: out.txt]\n"), "unexpected help:\n{}", help);
+        assert!(help.contains("parallel jobs [default: 4]\n"), "unexpected help:\n{}", help);
+    }
+
+    #[test]
+    fn optional() {
+        let mut timeout: Option<u32> = None;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--timeout", "0"));
+        parser
+            .opt_arg('t', "timeout", "timeout in seconds", &mut timeout, None, false)
+                .expect("bad timeout arg")
+        ;
+        assert_eq!(timeout, Some(0));
+
+        let mut timeout: Option<u32> = None;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]"));
+        parser
+            .opt_arg('t', "timeout", "timeout in seconds", &mut timeout, None, false)
+                .expect("bad timeout arg")
+        ;
+        assert_eq!(timeout, None);
+
+        let mut timeout: Option<u32> = None;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-t", "soon"));
+        let result = parser
+            .opt_arg('t', "timeout", "timeout in seconds", &mut timeout, None, false)
+        ;
+        match result {
+            Err(Error::ConstructionError('t', "timeout", _)) => {}
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected construction error"); }
+        }
+
+        let mut timeout: Option<u32> = None;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .opt_arg('t', "timeout", "timeout in seconds", &mut timeout, None, false)
+                .expect("bad timeout arg")
+        ;
+        let help = parser.printer.render();
+        assert!(help.contains("timeout in seconds [default: none]\n"), "unexpected help:\n{}", help);
+    }
+
+    #[test]
+    fn custom_parser() {
+        let duration = |v: &str| {
+            let (num, unit) = v.split_at(v.find(|c: char| !c.is_ascii_digit()).unwrap_or(v.len()));
+            let num = num.parse::<u64>().map_err(|_| format!("expected a duration like 90s, got '{}'", v))?;
+            match unit {
+                "s" => { Ok(std::time::Duration::from_secs(num)) }
+                "m" => { Ok(std::time::Duration::from_secs(num * 60)) }
+                _ => { Err(format!("unknown unit in '{}'", v)) }
+            }
+        };
+
+        let mut timeout = std::time::Duration::from_secs(30);
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--timeout", "2m"));
+        parser
+            .arg_with('t', "timeout", "how long to wait", &mut timeout, None, false, duration)
+                .expect("bad timeout")
+        ;
+        assert_eq!(timeout, std::time::Duration::from_secs(120));
+        assert_eq!(parser.matched_options()["timeout"], string_vec!("2m"));
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--timeout", "2h"));
+        match parser.arg_with('t', "timeout", "how long to wait", &mut timeout, None, false, duration) {
+            Err(Error::ConstructionError('t', "timeout", msg)) => {
+                assert_eq!(msg, "unknown unit in '2h'");
+            }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected construction error"); }
+        }
+    }
+
+    #[test]
+    fn choices() {
+        const LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error"];
+
+        let mut level: String = "info".to_string();
+        Parser::from_strings(string_vec!("argv[0]", "--log-level", "warn"))
+            .arg_choices('l', "log-level", "log level", &mut level, None, false, LEVELS)
+                .expect("bad level")
+        ;
+        assert_eq!(level, "warn");
+
+        for bad in &["WARN", "warning", ""] {
+            let mut level: String = "info".to_string();
+            let mut parser = Parser::from_strings(string_vec!("argv[0]", "--log-level", bad));
+            match parser.arg_choices('l', "log-level", "log level", &mut level, None, false, LEVELS) {
+                Err(Error::InvalidChoice('l', "log-level", given, choices)) => {
+                    assert_eq!(given, *bad);
+                    assert_eq!(choices, LEVELS);
+                }
+                Err(e) => { panic!("unexpected error for {}: {:?}", bad, e); }
+                Ok(_) => { panic!("expected invalid choice error for {}", bad); }
+            }
+            assert_eq!(level, "info", "modified level on error");
+        }
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .arg_choices('l', "log-level", "log level", &mut level, None, false, LEVELS)
+                .expect("bad level")
+        ;
+        let help = parser.printer.render();
+        assert!(help.contains("log level [choices: trace|debug|info|warn|error] [default: warn]\n"),
+            "unexpected help:\n{}", help);
+    }
+
+    #[test]
+    fn range() {
+        let mut threads: usize = 4;
+        Parser::from_strings(string_vec!("argv[0]", "-t", "1"))
+            .arg_range('t', "threads", "worker threads", &mut threads, None, false, 1, 64)
+                .expect("bad threads")
+        ;
+        assert_eq!(threads, 1);
+
+        let mut threads: usize = 4;
+        Parser::from_strings(string_vec!("argv[0]", "-t", "64"))
+            .arg_range('t', "threads", "worker threads", &mut threads, None, false, 1, 64)
+                .expect("bad threads")
+        ;
+        assert_eq!(threads, 64);
+
+        let mut threads: usize = 4;
+        Parser::from_strings(string_vec!("argv[0]"))
+            .arg_range('t', "threads", "worker threads", &mut threads, None, false, 1, 64)
+                .expect("bad threads")
+        ;
+        assert_eq!(threads, 4);
+
+        let mut threads: usize = 4;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--threads", "0"));
+        let result = parser
+            .arg_range('t', "threads", "worker threads", &mut threads, None, false, 1, 64)
+        ;
+        match result {
+            Err(Error::OutOfRange('t', "threads", msg)) => {
+                assert_eq!(msg, "'0' is not within 1..=64");
+            }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected out of range error"); }
+        }
+
+        let mut threads: usize = 4;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--threads", "65"));
+        let result = parser
+            .arg_range('t', "threads", "worker threads", &mut threads, None, false, 1, 64)
+        ;
+        match result {
+            Err(Error::OutOfRange('t', "threads", msg)) => {
+                assert_eq!(msg, "'65' is not within 1..=64");
+            }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected out of range error"); }
+        }
+
+        let mut ratio: f64 = 0.5;
+        Parser::from_strings(string_vec!("argv[0]", "--ratio", "0.25"))
+            .arg_range('r', "ratio", "split ratio", &mut ratio, None, false, 0.0, 1.0)
+                .expect("bad ratio")
+        ;
+        assert_eq!(ratio, 0.25);
+
+        let mut ratio: f64 = 0.5;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--ratio", "-0.1"));
+        let result = parser
+            .arg_range('r', "ratio", "split ratio", &mut ratio, None, false, 0.0, 1.0)
+        ;
+        match result {
+            Err(Error::OutOfRange(_, _, _)) => {}
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected out of range error for -0.1"); }
+        }
+
+        let mut ratio: f64 = 0.5;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--ratio", "1.5"));
+        let result = parser
+            .arg_range('r', "ratio", "split ratio", &mut ratio, None, false, 0.0, 1.0)
+        ;
+        match result {
+            Err(Error::OutOfRange(_, _, _)) => {}
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected out of range error for 1.5"); }
+        }
+
+        let mut ratio: f64 = 0.5;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--ratio", "NaN"));
+        let result = parser
+            .arg_range('r', "ratio", "split ratio", &mut ratio, None, false, 0.0, 1.0)
+        ;
+        match result {
+            Err(Error::OutOfRange(_, _, _)) => {}
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected out of range error for NaN"); }
+        }
+
+        let mut threads: usize = 4;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .arg_range('t', "threads", "worker threads", &mut threads, None, false, 1, 64)
+                .expect("bad threads")
+        ;
+        let help = parser.printer.render();
+        assert!(help.contains("worker threads [range: 1..=64] [default: 4]\n"), "unexpected help:\n{}", help);
+    }
+
+    #[test]
+    fn strict_values() {
+        let mut output: String = "".to_string();
+        let mut verbose: bool = false;
+
+        let args = string_vec!("argv[0]", "--output", "--verbose");
+        Parser::from_strings(args.clone())
+            .arg('o', "output", "output file", &mut output, None, false)
+                .expect("failed to parse output argument")
+        ;
+        assert_eq!(output, "--verbose", "expected lax mode to take the flag as a value");
+
+        let mut output: String = "".to_string();
+        let mut parser = Parser::from_strings(args);
+        let result = parser
+            .strict_values()
+            .arg('o', "output", "output file", &mut output, None, false)
+        ;
+        match result {
+            Ok(_) => { panic!("expected missing value error in strict mode"); }
+            Err(Error::MissingArgValue('o', "output")) => {}
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+        }
+
+        let mut output: String = "".to_string();
+        Parser::from_strings(string_vec!("argv[0]", "--output=-", "--verbose"))
+            .strict_values()
+            .arg('o', "output", "output file", &mut output, None, false)
+                .expect("failed to parse output argument")
+            .flag('v', "verbose", "verbose output", &mut verbose, false)
+                .expect("failed to parse verbose flag")
+        ;
+        assert_eq!(output, "-", "expected equal-form value to be accepted");
+        assert!(verbose, "expected verbose to be set");
+    }
+
+    #[test]
+    fn array() {
+        let mut rgb: [u8; 3] = [0, 0, 0];
+
+        Parser::from_strings(string_vec!("argv[0]", "--rgb", "255,128,0"))
+            .array_arg('c', "rgb", "color", &mut rgb, ',', Some("R,G,B"), false)
+                .expect("failed to parse rgb argument")
+        ;
+        assert_eq!(rgb, [255, 128, 0], "unexpected rgb value");
+
+        let mut rgb: [u8; 3] = [0, 0, 0];
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--rgb=255,128"));
+        let result = parser
+            .array_arg('c', "rgb", "color", &mut rgb, ',', Some("R,G,B"), false)
+        ;
+        match result {
+            Ok(_) => { panic!("expected wrong arity error"); }
+            Err(Error::WrongArity("rgb", 3, 2)) => {}
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+        }
+        assert_eq!(rgb, [0, 0, 0], "modified rgb on error");
+    }
+
+    #[test]
+    fn exact_long_precedence() {
+        let mut verbose: String = "".to_string();
+        let mut verb: String = "".to_string();
+
+        // declare the longer name first so it would be the first candidate for --verb
+        let args = string_vec!("argv[0]", "--verb", "short", "--verbose=long");
+        let mut parser = Parser::from_strings(args);
+        parser
+            .long_arg("verbose", "the longer name", &mut verbose, None, false)
+                .expect("failed to parse verbose argument")
+            .long_arg("verb", "the shorter name", &mut verb, None, false)
+                .expect("failed to parse verb argument")
+        ;
+
+        assert_eq!(verbose, "long", "longer name did not get its exact match");
+        assert_eq!(verb, "short", "shorter name did not get its exact match");
+        assert!(parser.unused().is_empty(), "expected all args to be consumed");
+    }
+
     #[test]
     fn runs_at_end() {
         let expect_count: usize = 8;
@@ -82,14 +458,823 @@ mod args {
         ;
 
         match result {
-            Ok(_) => { assert!(false, "expected error about args being at end of runs"); }
+            Ok(_) => { panic!("expected error about args being at end of runs"); }
             Err(e) => { match e {
                 Error::ValuedArgInRun(_, _) => { /* did what we expect */ }
                 _ => {
-                    assert!(false, "unexpected error: {:?}", e);
+                    panic!("unexpected error: {:?}", e);
                 }
             }}
         }
     }
-}
 
+    #[test]
+    fn symbolic_short_codes() {
+        let mut best: bool = false;
+        let mut numbered: bool = false;
+        let mut first: String = "".to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-#9", "-1", "foo"));
+        parser
+            .flag('9', "best", "compress better", &mut best, false)
+                .expect("bad best flag")
+            .short_flag('#', "number the lines", &mut numbered, false)
+                .expect("bad numbered flag")
+            .arg('1', "first", "first value", &mut first, Some("VAL"), false)
+                .expect("bad first arg")
+        ;
+        assert!(best && numbered, "expected both flags within the run");
+        assert_eq!(first, "foo");
+        assert!(parser.unused().is_empty(), "expected no unused args");
+
+        let mut best: bool = false;
+        let mut numbered: bool = false;
+        let mut first: String = "".to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-9", "-1=bar"));
+        parser
+            .flag('9', "best", "compress better", &mut best, false)
+                .expect("bad best flag")
+            .short_flag('#', "number the lines", &mut numbered, false)
+                .expect("bad numbered flag")
+            .arg('1', "first", "first value", &mut first, Some("VAL"), false)
+                .expect("bad first arg")
+        ;
+        assert!(best, "expected -9 to be matched");
+        assert_eq!(first, "bar");
+
+        let mut best: bool = false;
+        let mut numbered: bool = false;
+        let mut first: String = "".to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .flag('9', "best", "compress better", &mut best, false)
+                .expect("bad best flag")
+            .short_flag('#', "number the lines", &mut numbered, false)
+                .expect("bad numbered flag")
+            .arg('1', "first", "first value", &mut first, Some("VAL"), false)
+                .expect("bad first arg")
+        ;
+        let help = parser.printer.render();
+        assert!(help.contains("-9, --best"), "expected digit short in help:\n{}", help);
+        assert!(help.contains("-#  "), "expected symbol short in help:\n{}", help);
+        assert!(help.contains("-1, --first"), "expected digit short in help:\n{}", help);
+        assert!(help.contains("[-9# -1 VAL]"), "unexpected usage:\n{}", help);
+    }
+
+    #[test]
+    fn auto_short_codes() {
+        let mut verbose: bool = false;
+        let mut value: String = "".to_string();
+        let mut alpha: bool = false;
+        let mut none: usize = 0;
+
+        let args = string_vec!("argv[0]", "-a", "foo", "-l", "-v");
+        let mut parser = Parser::from_strings(args);
+        parser
+            .auto_short_codes()
+            .flag('v', "verbose", "be verbose", &mut verbose, false)
+                .expect("bad flag")
+            .long_arg("value", "takes 'a' since 'v' is taken", &mut value, None, false)
+                .expect("bad arg")
+            .long_flag("alpha", "takes 'l' since 'a' is taken", &mut alpha, false)
+                .expect("bad flag")
+            .long_count("hav", "no short since all chars are taken", &mut none, 1)
+                .expect("bad count")
+        ;
+
+        assert!(verbose, "expected verbose to be true");
+        assert!(value == "foo", "expected value to be 'foo' but got '{}'", value);
+        assert!(alpha, "expected alpha to be true");
+        assert!(parser.unused().is_empty(), "expected no unused args");
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .auto_short_codes()
+            .flag('v', "verbose", "be verbose", &mut verbose, false)
+                .expect("bad flag")
+            .long_arg("value", "takes 'a' since 'v' is taken", &mut value, None, false)
+                .expect("bad arg")
+            .long_count("hav", "no short since all chars are taken", &mut none, 1)
+                .expect("bad count")
+        ;
+
+        let help = parser.printer.render();
+        assert!(help.contains("-a, --value"), "expected auto short in help:\n{}", help);
+        assert!(help.contains("    --hav"), "expected no short in help:\n{}", help);
+    }
+
+    #[test]
+    fn net_addrs() {
+        let mut addr: std::net::SocketAddr = "0.0.0.0:80".parse().expect("bad default addr");
+        let mut ip: std::net::IpAddr = "0.0.0.0".parse().expect("bad default ip");
+
+        let args = string_vec!("argv[0]", "--listen", "127.0.0.1:8080", "--bind", "::1");
+        let mut parser = Parser::from_strings(args);
+        parser
+            .addr_arg('l', "listen", "address to listen on", &mut addr, None, false)
+                .expect("bad addr arg")
+            .ip_arg('b', "bind", "ip to bind", &mut ip, None, false)
+                .expect("bad ip arg")
+        ;
+
+        assert_eq!(addr, "127.0.0.1:8080".parse::<std::net::SocketAddr>().unwrap());
+        assert_eq!(ip, "::1".parse::<std::net::IpAddr>().unwrap());
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--listen", "localhost"));
+        match parser.addr_arg('l', "listen", "address to listen on", &mut addr, None, false) {
+            Err(Error::ConstructionError(_, long, msg)) => {
+                assert!(long == "listen", "unexpected arg in error: {}", long);
+                assert!(msg.contains("expected IP:PORT like 127.0.0.1:8080"),
+                    "unexpected error message: {}", msg);
+                assert!(msg.contains("'localhost'"), "expected value in message: {}", msg);
+            }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected construction error"); }
+        }
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--bind", "1.2.3"));
+        match parser.ip_arg('b', "bind", "ip to bind", &mut ip, None, false) {
+            Err(Error::ConstructionError(_, _, msg)) => {
+                assert!(msg.contains("expected an IP address"), "unexpected error message: {}", msg);
+            }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected construction error"); }
+        }
+    }
+
+    #[test]
+    fn percent() {
+        let mut opacity: f64 = 1.0;
+        let mut ratio: f64 = 0.0;
+
+        let args = string_vec!("argv[0]", "--opacity", "50%", "--ratio", "0.75");
+        let mut parser = Parser::from_strings(args);
+        parser
+            .percent_arg('o', "opacity", "opacity of the window", &mut opacity, None, false)
+                .expect("bad opacity")
+            .percent_arg('r', "ratio", "split ratio", &mut ratio, None, false)
+                .expect("bad ratio")
+        ;
+
+        assert!(opacity == 0.5, "expected opacity to be 0.5 but got {}", opacity);
+        assert!(ratio == 0.75, "expected ratio to be 0.75 but got {}", ratio);
+
+        for bad in &["150%", "-0.1", "half"] {
+            let mut parser = Parser::from_strings(string_vec!("argv[0]", "--opacity", bad));
+            match parser.percent_arg('o', "opacity", "opacity", &mut opacity, None, false) {
+                Err(Error::ConstructionError(_, _, msg)) => {
+                    assert!(msg.contains(bad), "expected value in message: {}", msg);
+                }
+                Err(e) => { panic!("unexpected error for {}: {:?}", bad, e); }
+                Ok(_) => { panic!("expected construction error for {}", bad); }
+            }
+        }
+    }
+
+    #[test]
+    fn collect_required() {
+        let mut file: String = "".to_string();
+        let mut libs: Vec<String> = vec!();
+        let mut debug: bool = false;
+        let mut target: String = "".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-D"));
+        parser
+            .collect_required_errors()
+            .arg('f', "file", "a file", &mut file, None, true)
+                .expect("missing arg should be deferred")
+            .list('l', "lib", "libs", &mut libs, None, true)
+                .expect("missing list should be deferred")
+            .flag('D', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .positional("target", "what to build", &mut target, true)
+                .expect("missing positional should be deferred")
+        ;
+        assert!(debug, "expected parsing to continue past missing args");
+
+        match parser.finish() {
+            Err(Error::MissingArguments(names)) => {
+                assert_eq!(names, string_vec!("-f, --file", "-l, --lib", "target"));
+            }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected missing arguments error"); }
+        }
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-f", "foo"));
+        parser
+            .collect_required_errors()
+            .arg('f', "file", "a file", &mut file, None, true)
+                .expect("bad arg")
+            .finish().expect("nothing should be missing")
+        ;
+    }
+
+    #[test]
+    fn accumulate_errors() {
+        let mut threads: usize = 4;
+        let mut file: String = "default.txt".to_string();
+        let mut ports: Vec<u16> = vec!();
+        let mut debug: bool = false;
+        let mut count: u8 = 1;
+
+        let args = string_vec!("argv[0]", "-t", "many", "-p", "80", "-p", "http", "-D", "lots");
+        let mut parser = Parser::from_strings(args);
+        parser
+            .accumulate_errors()
+            .arg('t', "threads", "thread count", &mut threads, None, false)
+                .expect("construction failure should be recorded")
+            .arg('f', "file", "a file", &mut file, None, true)
+                .expect("missing arg should be recorded")
+            .list('p', "port", "ports", &mut ports, None, false)
+                .expect("construction failure should be recorded")
+            .flag('D', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .positional("count", "how many", &mut count, true)
+                .expect("construction failure should be recorded")
+        ;
+
+        assert!(debug, "expected parsing to continue past errors");
+        assert_eq!(threads, 4, "expected the default to be kept");
+        assert_eq!(file, "default.txt", "expected the default to be kept");
+        assert_eq!(ports, vec!(80), "expected the valid port to be kept");
+        assert_eq!(count, 1, "expected the default to be kept");
+
+        let errors = parser.errors();
+        assert_eq!(errors.len(), 4, "unexpected errors: {:?}", errors);
+        assert!(matches!(errors[0], Error::ConstructionError('t', "threads", _)),
+            "unexpected error: {}", errors[0]);
+        assert!(matches!(errors[1], Error::MissingArgument(_)),
+            "unexpected error: {}", errors[1]);
+        assert!(matches!(errors[2], Error::ConstructionError('p', "port", _)),
+            "unexpected error: {}", errors[2]);
+        assert!(matches!(errors[3], Error::PositionalConstructionError("count", _)),
+            "unexpected error: {}", errors[3]);
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-t", "many"));
+        let res = parser
+            .arg('t', "threads", "thread count", &mut threads, None, false)
+            .map(|_| ());
+        assert!(res.is_err(), "expected failing fast without accumulate_errors");
+        assert!(parser.errors().is_empty(), "did not expect errors to be recorded");
+    }
+
+    #[test]
+    fn accumulate_matched() {
+        let mut threads: u8 = 4;
+        let mut level: u8 = 1;
+        let mut ports: Vec<u16> = vec!();
+        let mut debug: bool = false;
+
+        let args = string_vec!("argv[0]", "-t", "many", "-l", "high", "-p", "80", "-p", "http", "-D");
+        let mut parser = Parser::from_strings(args);
+        parser
+            .accumulate_errors()
+            .arg('t', "threads", "thread count", &mut threads, None, false)
+                .expect("construction failure should be recorded")
+            .arg_default('l', "level", "a level", &mut level, None, 3)
+                .expect("construction failure should be recorded")
+            .list('p', "port", "ports", &mut ports, None, false)
+                .expect("construction failure should be recorded")
+            .flag('D', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+        ;
+
+        assert_eq!(parser.errors().len(), 3, "unexpected errors: {:?}", parser.errors());
+        assert_eq!(level, 1, "did not expect the default to replace the failed value");
+
+        let matched = parser.matched_options();
+        assert!(!matched.contains_key("threads"), "did not expect failed arg: {:?}", matched);
+        assert!(!matched.contains_key("level"), "did not expect failed arg: {:?}", matched);
+        assert_eq!(matched.get("port"), Some(&string_vec!("80")));
+        assert_eq!(matched.get("debug"), Some(&vec!()));
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn dates() {
+        let mut since = chrono::NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        let mut until = chrono::NaiveDateTime::default();
+
+        let args = string_vec!("argv[0]",
+            "--since", "2023-01-15", "--until", "2023-02-01T08:30:00"
+        );
+        let mut parser = Parser::from_strings(args);
+        parser
+            .date_arg('s', "since", "start date", &mut since, "%Y-%m-%d", None, false)
+                .expect("bad date arg")
+            .datetime_arg('u', "until", "end time", &mut until, "%Y-%m-%dT%H:%M:%S", None, false)
+                .expect("bad datetime arg")
+        ;
+
+        assert_eq!(since, chrono::NaiveDate::from_ymd_opt(2023, 1, 15).unwrap());
+        assert_eq!(until, chrono::NaiveDate::from_ymd_opt(2023, 2, 1).unwrap()
+            .and_hms_opt(8, 30, 0).unwrap());
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--since", "01/15/2023"));
+        match parser.date_arg('s', "since", "start date", &mut since, "%Y-%m-%d", None, false) {
+            Err(Error::ConstructionError(_, _, msg)) => {
+                assert!(msg.contains("expected a date like YYYY-MM-DD"),
+                    "unexpected error message: {}", msg);
+            }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected construction error"); }
+        }
+    }
+
+    #[test]
+    fn all_or_none() {
+        let mut cert: String = "".to_string();
+        let mut key: String = "".to_string();
+        let mut debug: bool = false;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--tls-cert", "a.pem", "-d"));
+        let result = parser
+            .arg('c', "tls-cert", "certificate", &mut cert, None, false)
+                .expect("bad cert arg")
+            .arg('k', "tls-key", "private key", &mut key, None, false)
+                .expect("bad key arg")
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .all_or_none(&["tls-cert", "tls-key"])
+        ;
+        match result {
+            Err(Error::IncompleteGroup(missing)) => {
+                assert_eq!(missing, string_vec!("--tls-key"));
+            }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected incomplete group error"); }
+        }
+
+        let mut cert: String = "".to_string();
+        let mut key: String = "".to_string();
+        let mut debug: bool = false;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--tls-cert", "a.pem", "--tls-key", "a.key"));
+        let result = parser
+            .arg('c', "tls-cert", "certificate", &mut cert, None, false)
+                .expect("bad cert arg")
+            .arg('k', "tls-key", "private key", &mut key, None, false)
+                .expect("bad key arg")
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .all_or_none(&["tls-cert", "tls-key"])
+        ;
+        result.expect("both given should succeed");
+
+        let mut cert: String = "".to_string();
+        let mut key: String = "".to_string();
+        let mut debug: bool = false;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-d"));
+        let result = parser
+            .arg('c', "tls-cert", "certificate", &mut cert, None, false)
+                .expect("bad cert arg")
+            .arg('k', "tls-key", "private key", &mut key, None, false)
+                .expect("bad key arg")
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .all_or_none(&["tls-cert", "tls-key"])
+        ;
+        result.expect("neither given should succeed");
+    }
+
+    #[test]
+    fn abbreviations() {
+        let mut vers: String = "".to_string();
+        let mut verbose: bool = false;
+        let mut level: usize = 0;
+        Parser::from_strings(string_vec!("argv[0]", "--verb", "--lev=3"))
+            .allow_abbreviations(true)
+            .arg('\0', "vers", "version to use", &mut vers, None, false)
+                .expect("bad vers arg")
+            .flag('\0', "verbose", "verbose output", &mut verbose, false)
+                .expect("bad verbose flag")
+            .arg('\0', "level", "verbosity level", &mut level, None, false)
+                .expect("bad level arg")
+        ;
+        assert_eq!((vers, verbose, level), ("".to_string(), true, 3));
+
+        // exact matches win over prefixes
+        let mut vers: String = "".to_string();
+        let mut verbose: bool = false;
+        let mut level: usize = 0;
+        Parser::from_strings(string_vec!("argv[0]", "--vers", "1.2", "--l", "2"))
+            .allow_abbreviations(true)
+            .arg('\0', "vers", "version to use", &mut vers, None, false)
+                .expect("bad vers arg")
+            .flag('\0', "verbose", "verbose output", &mut verbose, false)
+                .expect("bad verbose flag")
+            .arg('\0', "level", "verbosity level", &mut level, None, false)
+                .expect("bad level arg")
+        ;
+        assert_eq!((vers, verbose, level), ("1.2".to_string(), false, 2));
+
+        let mut vers: String = "".to_string();
+        let mut verbose: bool = false;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--ver", "1.2"));
+        let result = parser
+            .allow_abbreviations(true)
+            .arg('\0', "vers", "version to use", &mut vers, None, false)
+                .expect("bad vers arg")
+            .flag('\0', "verbose", "verbose output", &mut verbose, false)
+        ;
+        match result {
+            Err(Error::AmbiguousLong(token, longs)) => {
+                assert_eq!(token, "ver");
+                assert_eq!(longs, vec!("vers", "verbose"));
+            }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected ambiguous abbreviation"); }
+        }
+
+        // the ambiguity is only known once the later long is declared
+        let mut verbose = false;
+        let mut verbosity: usize = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--verbo"));
+        parser.allow_abbreviations(true);
+        parser.flag('\0', "verbose", "verbose output", &mut verbose, false).expect("bad flag");
+        assert!(verbose);
+        match parser.arg('\0', "verbosity", "verbosity level", &mut verbosity, None, false) {
+            Err(Error::AmbiguousLong(token, longs)) => {
+                assert_eq!(token, "verbo");
+                assert_eq!(longs, vec!("verbose", "verbosity"));
+            }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected ambiguous abbreviation"); }
+        }
+
+        let mut verbose = false;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--verb"));
+        parser.flag('\0', "verbose", "verbose output", &mut verbose, false).expect("bad flag");
+        assert!(!verbose, "abbreviations should be disabled by default");
+    }
+
+    #[test]
+    fn exclusive_group() {
+        let mut json: bool = false;
+        let mut yaml: bool = false;
+        let mut toml: bool = false;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--yaml", "--toml", "--json"));
+        let result = parser
+            .long_flag("json", "json output", &mut json, false)
+                .expect("bad json flag")
+            .long_flag("yaml", "yaml output", &mut yaml, false)
+                .expect("bad yaml flag")
+            .long_flag("toml", "toml output", &mut toml, false)
+                .expect("bad toml flag")
+            .exclusive_group(&["json", "yaml", "toml"])
+        ;
+        match result {
+            Err(Error::MutuallyExclusive(given)) => {
+                assert_eq!(given, vec!("json", "yaml", "toml"));
+            }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected mutually exclusive error"); }
+        }
+
+        let mut json: bool = false;
+        let mut yaml: bool = false;
+        let mut toml: bool = false;
+        let mut file: String = "".to_string();
+        Parser::from_strings(string_vec!("argv[0]", "--yaml", "-f", "a"))
+            .long_flag("json", "json output", &mut json, false)
+                .expect("bad json flag")
+            .long_flag("yaml", "yaml output", &mut yaml, false)
+                .expect("bad yaml flag")
+            .long_flag("toml", "toml output", &mut toml, false)
+                .expect("bad toml flag")
+            .exclusive_group(&["json", "yaml", "toml"])
+                .expect("one given should succeed")
+            .arg('f', "file", "a file", &mut file, None, true)
+                .expect("one given should succeed")
+        ;
+
+        let mut json: bool = false;
+        let mut yaml: bool = false;
+        let mut toml: bool = false;
+        let mut file: String = "".to_string();
+        Parser::from_strings(string_vec!("argv[0]", "-f", "a"))
+            .long_flag("json", "json output", &mut json, false)
+                .expect("bad json flag")
+            .long_flag("yaml", "yaml output", &mut yaml, false)
+                .expect("bad yaml flag")
+            .long_flag("toml", "toml output", &mut toml, false)
+                .expect("bad toml flag")
+            .exclusive_group(&["json", "yaml", "toml"])
+                .expect("none given should succeed")
+            .arg('f', "file", "a file", &mut file, None, true)
+                .expect("none given should succeed")
+        ;
+
+        let mut json: bool = false;
+        let mut yaml: bool = false;
+        let mut toml: bool = false;
+        let mut file: String = "".to_string();
+        Parser::from_strings(string_vec!("argv[0]", "--json", "--yaml", "--help"))
+            .long_flag("json", "json output", &mut json, false)
+                .expect("bad json flag")
+            .long_flag("yaml", "yaml output", &mut yaml, false)
+                .expect("bad yaml flag")
+            .long_flag("toml", "toml output", &mut toml, false)
+                .expect("bad toml flag")
+            .exclusive_group(&["json", "yaml", "toml"])
+                .expect("help should succeed")
+            .arg('f', "file", "a file", &mut file, None, true)
+                .expect("help should succeed")
+        ;
+    }
+
+    #[test]
+    fn required_one_of() {
+        let mut stdin: bool = false;
+        let mut file: String = "".to_string();
+        let mut url: String = "".to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--file", "a"));
+        parser
+            .app_name("rags")
+            .long_flag("stdin", "read from stdin", &mut stdin, false)
+                .expect("bad stdin flag")
+            .long_arg("file", "read from a file", &mut file, Some("FILE"), false)
+                .expect("bad file arg")
+            .long_arg("url", "read from a url", &mut url, Some("URL"), false)
+                .expect("bad url arg")
+            .required_one_of(&["stdin", "file", "url"])
+                .expect("one given should succeed")
+        ;
+
+        let mut stdin: bool = false;
+        let mut file: String = "".to_string();
+        let mut url: String = "".to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]"));
+        let result = parser
+            .app_name("rags")
+            .long_flag("stdin", "read from stdin", &mut stdin, false)
+                .expect("bad stdin flag")
+            .long_arg("file", "read from a file", &mut file, Some("FILE"), false)
+                .expect("bad file arg")
+            .long_arg("url", "read from a url", &mut url, Some("URL"), false)
+                .expect("bad url arg")
+            .required_one_of(&["stdin", "file", "url"])
+        ;
+        match result {
+            Err(Error::MissingOneOf(members)) => {
+                assert_eq!(members, vec!("stdin", "file", "url"));
+            }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected missing one-of error"); }
+        }
+
+        let mut stdin: bool = false;
+        let mut file: String = "".to_string();
+        let mut url: String = "".to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--url", "b", "--stdin"));
+        let result = parser
+            .app_name("rags")
+            .long_flag("stdin", "read from stdin", &mut stdin, false)
+                .expect("bad stdin flag")
+            .long_arg("file", "read from a file", &mut file, Some("FILE"), false)
+                .expect("bad file arg")
+            .long_arg("url", "read from a url", &mut url, Some("URL"), false)
+                .expect("bad url arg")
+            .required_one_of(&["stdin", "file", "url"])
+        ;
+        match result {
+            Err(Error::MutuallyExclusive(given)) => {
+                assert_eq!(given, vec!("stdin", "url"));
+            }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected mutually exclusive error"); }
+        }
+
+        let mut stdin: bool = false;
+        let mut file: String = "".to_string();
+        let mut url: String = "".to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .app_name("rags")
+            .long_flag("stdin", "read from stdin", &mut stdin, false)
+                .expect("bad stdin flag")
+            .long_arg("file", "read from a file", &mut file, Some("FILE"), false)
+                .expect("bad file arg")
+            .long_arg("url", "read from a url", &mut url, Some("URL"), false)
+                .expect("bad url arg")
+            .required_one_of(&["stdin", "file", "url"])
+                .expect("help should succeed")
+        ;
+        let help = parser.help_string();
+        assert!(help.contains("usage: rags ( --stdin | --file FILE | --url URL )\n"),
+            "unexpected usage:\n{}", help);
+    }
+
+    #[test]
+    fn env_fallback() {
+        let mut url: String = "sqlite://default".to_string();
+        let mut threads: usize = 1;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--database-url", "pg://cli", "-t", "2"));
+        parser.env_source(|key| match key {
+            "DATABASE_URL" => { Some("pg://env".to_string()) }
+            "THREADS" => { Some("four".to_string()) }
+            _ => { None }
+        });
+        parser
+            .arg_env('d', "database-url", "database to use", &mut url, None, true, "DATABASE_URL")
+                .expect("bad url arg")
+            .arg_env('t', "threads", "thread count", &mut threads, None, false, "THREADS")
+                .expect("bad threads arg")
+        ;
+        assert_eq!(url, "pg://cli");
+
+        let mut url: String = "sqlite://default".to_string();
+        let mut threads: usize = 1;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--database-url", "pg://cli", "-t", "2"));
+        parser.env_source(|_| None);
+        parser
+            .arg_env('d', "database-url", "database to use", &mut url, None, true, "DATABASE_URL")
+                .expect("bad url arg")
+            .arg_env('t', "threads", "thread count", &mut threads, None, false, "THREADS")
+                .expect("bad threads arg")
+        ;
+        assert_eq!(url, "pg://cli");
+
+        let mut url: String = "sqlite://default".to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]"));
+        parser.env_source(|_| None);
+        let result = parser
+            .arg_env('d', "database-url", "database to use", &mut url, None, true, "DATABASE_URL")
+        ;
+        match result {
+            Err(Error::MissingArgument(_)) => {}
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected missing argument without the env var"); }
+        }
+
+        let mut url: String = "sqlite://default".to_string();
+        let mut threads: usize = 1;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]"));
+        parser.env_source(|key| match key {
+            "DATABASE_URL" => { Some("pg://env".to_string()) }
+            "THREADS" => { Some("four".to_string()) }
+            _ => { None }
+        });
+        let result = parser
+            .arg_env('d', "database-url", "database to use", &mut url, None, true, "DATABASE_URL")
+                .expect("bad url arg")
+            .arg_env('t', "threads", "thread count", &mut threads, None, false, "THREADS")
+        ;
+        match result {
+            Err(Error::ConstructionError('t', "threads", _)) => {}
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected construction error from the env var"); }
+        }
+
+        let mut url: String = "sqlite://default".to_string();
+        let mut threads: usize = 1;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-t", "2"));
+        parser.env_source(|key| match key {
+            "DATABASE_URL" => { Some("pg://env".to_string()) }
+            "THREADS" => { Some("four".to_string()) }
+            _ => { None }
+        });
+        parser
+            .arg_env('d', "database-url", "database to use", &mut url, None, true, "DATABASE_URL")
+                .expect("bad url arg")
+            .arg_env('t', "threads", "thread count", &mut threads, None, false, "THREADS")
+                .expect("bad threads arg")
+        ;
+        assert_eq!(url, "pg://env");
+
+        let mut url: String = "".to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .arg_env('d', "database-url", "database to use", &mut url, None, false, "DATABASE_URL")
+                .expect("bad arg")
+        ;
+        let help = parser.printer.render();
+        assert!(help.contains("database to use [env: DATABASE_URL]\n"), "unexpected help:\n{}", help);
+    }
+
+    #[test]
+    fn response_files() {
+        let dir = std::env::temp_dir();
+        let outer = dir.join(format!("rags-response-{}.txt", std::process::id()));
+        let inner = dir.join(format!("rags-response-nested-{}.txt", std::process::id()));
+        let outer_ref = format!("@{}", outer.to_string_lossy());
+        let inner_ref = format!("@{}", inner.to_string_lossy());
+        std::fs::write(&outer, format!("--level warn\n{}\n", inner_ref)).expect("could not write file");
+        std::fs::write(&inner, "-t 4\n-D").expect("could not write file");
+
+        let mut level: String = "info".to_string();
+        let mut threads: usize = 1;
+        let mut debug: bool = false;
+        let mut rest: Vec<String> = vec!();
+        Parser::from_strings_expanding_response_files(
+            string_vec!("argv[0]", outer_ref.clone(), "--", inner_ref.clone()))
+            .expect("bad response file")
+            .arg('l', "level", "log level", &mut level, None, false)
+                .expect("bad level arg")
+            .arg('t', "threads", "thread count", &mut threads, None, false)
+                .expect("bad threads arg")
+            .flag('D', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .positional_list("rest", "the rest", &mut rest, false)
+                .expect("bad positional list")
+        ;
+        assert_eq!((level, threads, debug, rest), ("warn".to_string(), 4, true, vec!(inner_ref.clone())));
+
+        let result = Parser::from_strings_expanding_response_files(
+            string_vec!("argv[0]", "@rags-no-such-response-file"));
+        match result {
+            Err(Error::ResponseFile(path, _)) => { assert_eq!(path, "rags-no-such-response-file"); }
+            Err(e) => { panic!("unexpected error: {}", e); }
+            Ok(_) => { panic!("expected response file error"); }
+        }
+
+        std::fs::write(&inner, format!("-D {}", outer_ref)).expect("could not write file");
+        let result = Parser::from_strings_expanding_response_files(string_vec!("argv[0]", outer_ref.clone()));
+        std::fs::remove_file(&outer).expect("could not remove file");
+        std::fs::remove_file(&inner).expect("could not remove file");
+        match result {
+            Err(Error::ResponseFile(path, reason)) => {
+                assert_eq!(path, outer.to_string_lossy());
+                assert_eq!(reason, "file references itself");
+            }
+            Err(e) => { panic!("unexpected error: {}", e); }
+            Ok(_) => { panic!("expected response file cycle error"); }
+        }
+    }
+
+    #[test]
+    fn config_from_env() {
+        let home = std::env::temp_dir();
+        let name = format!("rags-config-{}.conf", std::process::id());
+        std::fs::write(home.join(&name),
+            "# defaults\nlevel = \"debug\"\n\nthreads=4\n").expect("could not write config");
+
+        let mut level: String = "info".to_string();
+        let mut threads: usize = 1;
+        let home_dir = home.to_string_lossy().to_string();
+        let config = format!("~/{}", name);
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--threads", "8"));
+        parser.env_source(move |key| match key {
+            "HOME" => { Some(home_dir.clone()) }
+            "MYAPP_CONFIG" => { Some(config.clone()) }
+            _ => { None }
+        });
+        parser
+            .config_from_env("MYAPP_CONFIG", ConfigFormat::KeyValue).expect("bad config")
+            .arg('l', "level", "log level", &mut level, None, false).expect("bad level arg")
+            .arg('t', "threads", "thread count", &mut threads, None, true).expect("bad threads arg")
+        ;
+        assert_eq!((level, threads), ("debug".to_string(), 8));
+
+        let mut level: String = "info".to_string();
+        let mut threads: usize = 1;
+        let home_dir = home.to_string_lossy().to_string();
+        let config = format!("${{HOME}}/{}", name);
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-l", "warn"));
+        parser.env_source(move |key| match key {
+            "HOME" => { Some(home_dir.clone()) }
+            "MYAPP_CONFIG" => { Some(config.clone()) }
+            _ => { None }
+        });
+        parser
+            .config_from_env("MYAPP_CONFIG", ConfigFormat::KeyValue).expect("bad config")
+            .arg('l', "level", "log level", &mut level, None, false).expect("bad level arg")
+            .arg('t', "threads", "thread count", &mut threads, None, true).expect("bad threads arg")
+        ;
+        assert_eq!((level, threads), ("warn".to_string(), 4));
+
+        let mut level: String = "info".to_string();
+        let mut threads: usize = 1;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]"));
+        parser.env_source(|_| None);
+        let result = parser
+            .config_from_env("MYAPP_CONFIG", ConfigFormat::KeyValue).expect("config is optional")
+            .arg('l', "level", "log level", &mut level, None, false).expect("bad level arg")
+            .arg('t', "threads", "thread count", &mut threads, None, true)
+            .map(|_| ())
+        ;
+        match result {
+            Err(Error::MissingArgument(_)) => {}
+            Err(e) => { panic!("unexpected error: {}", e); }
+            Ok(_) => { panic!("expected missing argument without a config"); }
+        }
+
+        let home_dir = home.to_string_lossy().to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]"));
+        parser.env_source(move |key| match key {
+            "HOME" => { Some(home_dir.clone()) }
+            "MYAPP_CONFIG" => { Some("$HOME/rags-no-such.conf".to_string()) }
+            _ => { None }
+        });
+        let result = parser
+            .config_from_env("MYAPP_CONFIG", ConfigFormat::KeyValue)
+            .map(|_| ())
+        ;
+        match result {
+            Err(Error::ConfigFile(path, _)) => {
+                assert_eq!(path, home.join("rags-no-such.conf").to_string_lossy());
+            }
+            Err(e) => { panic!("unexpected error: {}", e); }
+            Ok(_) => { panic!("expected config file error"); }
+        }
+
+        std::fs::remove_file(home.join(&name)).expect("could not remove config");
+    }
+}
@@ -82,5 +82,196 @@ mod count {
         assert!(count == expect, "unexpected count value {}, wanted {}", count, expect);
         assert!(other == expect, "unexpected other value {}, wanted {}", other, expect);
     }
-}
 
+    #[test]
+    fn numeric() {
+        let mut verbosity: usize = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-vvv"));
+        parser
+            .count_numeric('v', "verbose", "verbosity", &mut verbosity, 1)
+                .expect("bad count")
+        ;
+        assert_eq!(verbosity, 3);
+        assert!(parser.unused().is_empty(), "expected no unused args");
+
+        let mut verbosity: usize = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-v3"));
+        parser
+            .count_numeric('v', "verbose", "verbosity", &mut verbosity, 1)
+                .expect("bad count")
+        ;
+        assert_eq!(verbosity, 3);
+        assert!(parser.unused().is_empty(), "expected no unused args");
+
+        let mut verbosity: usize = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-v=3"));
+        parser
+            .count_numeric('v', "verbose", "verbosity", &mut verbosity, 1)
+                .expect("bad count")
+        ;
+        assert_eq!(verbosity, 3);
+        assert!(parser.unused().is_empty(), "expected no unused args");
+
+        let mut verbosity: usize = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--verbose=3"));
+        parser
+            .count_numeric('v', "verbose", "verbosity", &mut verbosity, 1)
+                .expect("bad count")
+        ;
+        assert_eq!(verbosity, 3);
+        assert!(parser.unused().is_empty(), "expected no unused args");
+
+        let mut verbosity: usize = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-v2", "-v"));
+        parser
+            .count_numeric('v', "verbose", "verbosity", &mut verbosity, 1)
+                .expect("bad count")
+        ;
+        assert_eq!(verbosity, 3);
+        assert!(parser.unused().is_empty(), "expected no unused args");
+    }
+
+    #[test]
+    fn numeric_with_digit_short() {
+        let mut verbosity: usize = 0;
+        let mut three: bool = false;
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-v3"));
+        parser
+            .short_flag('3', "use version 3", &mut three, false)
+                .expect("bad flag")
+            .count_numeric('v', "verbose", "verbosity", &mut verbosity, 1)
+                .expect("bad count")
+        ;
+
+        assert!(three, "expected 3 to be a flag in the run");
+        assert!(verbosity == 1, "expected verbosity to be 1 but got {}", verbosity);
+    }
+
+    #[test]
+    fn numeric_ending_run() {
+        let mut verbosity: usize = 0;
+        let mut n: usize = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-vvn5"));
+        parser
+            .count('v', "verbose", "verbosity", &mut verbosity, 1)
+                .expect("bad count")
+            .arg('n', "num", "a number", &mut n, None, false)
+                .expect("bad n arg")
+        ;
+        assert_eq!((verbosity, n), (2, 5));
+        assert!(parser.unused().is_empty(), "expected no unused args");
+
+        let mut verbosity: usize = 0;
+        let mut n: usize = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-vvn5"));
+        parser
+            .count('v', "verbose", "verbosity", &mut verbosity, 1)
+                .expect("bad count")
+            .count_numeric('n', "num", "a numeric count", &mut n, 1)
+                .expect("bad n count")
+        ;
+        assert_eq!((verbosity, n), (2, 5));
+        assert!(parser.unused().is_empty(), "expected no unused args");
+
+        // the number belongs to n regardless of which is declared first
+        let mut verbosity: usize = 0;
+        let mut n: usize = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-vvn5"));
+        parser
+            .arg('n', "num", "a number", &mut n, None, false)
+                .expect("bad n arg")
+            .count('v', "verbose", "verbosity", &mut verbosity, 1)
+                .expect("bad count")
+        ;
+        assert_eq!((verbosity, n), (2, 5));
+        assert!(parser.unused().is_empty(), "expected no unused args");
+
+        let mut verbosity: usize = 0;
+        let mut n: usize = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-vvn5"));
+        parser
+            .count_numeric('n', "num", "a numeric count", &mut n, 1)
+                .expect("bad n count")
+            .count('v', "verbose", "verbosity", &mut verbosity, 1)
+                .expect("bad count")
+        ;
+        assert_eq!((verbosity, n), (2, 5));
+        assert!(parser.unused().is_empty(), "expected no unused args");
+
+        let mut verbosity: usize = 0;
+        let mut n: usize = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-vn12", "-v"));
+        parser
+            .count('v', "verbose", "verbosity", &mut verbosity, 1)
+                .expect("bad count")
+            .arg('n', "num", "a number", &mut n, None, false)
+                .expect("bad n arg")
+        ;
+        assert_eq!((verbosity, n), (2, 12));
+        assert!(parser.unused().is_empty(), "expected no unused args");
+
+        let mut verbosity: usize = 0;
+        let mut n: usize = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-n5"));
+        parser
+            .count('v', "verbose", "verbosity", &mut verbosity, 1)
+                .expect("bad count")
+            .arg('n', "num", "a number", &mut n, None, false)
+                .expect("bad n arg")
+        ;
+        assert_eq!((verbosity, n), (0, 5));
+        assert!(parser.unused().is_empty(), "expected no unused args");
+    }
+
+    #[test]
+    fn max() {
+        let mut verbosity: u8 = 0;
+        Parser::from_strings(string_vec!("argv[0]", "-vvvvv"))
+            .count_max('v', "verbose", "verbosity", &mut verbosity, 1, 3)
+                .expect("bad count")
+        ;
+        assert_eq!(verbosity, 3, "expected the count to stop at the max");
+
+        // the occurrences recorded are those counted
+        let mut verbosity: u8 = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-vvvvv"));
+        parser
+            .count_max('v', "verbose", "verbosity", &mut verbosity, 1, 3)
+                .expect("bad count")
+        ;
+        assert_eq!(verbosity, 3);
+        assert_eq!(parser.matched_options().get("verbose"), Some(&string_vec!("3")));
+
+        // a step overshooting the max stops at it
+        let mut level: u8 = 0;
+        Parser::from_strings(string_vec!("argv[0]", "-ll"))
+            .count_max('l', "level", "level", &mut level, 2, 3)
+                .expect("bad count")
+        ;
+        assert_eq!(level, 3, "expected the step to stop at the max");
+
+        // the preset value is kept when the arg is not given
+        let mut verbosity: u8 = 7;
+        Parser::from_strings(string_vec!("argv[0]"))
+            .count_max('v', "verbose", "verbosity", &mut verbosity, 1, 3)
+                .expect("bad count")
+        ;
+        assert_eq!(verbosity, 7, "expected the value to be left alone");
+
+        let mut verbosity: u8 = 0;
+        Parser::from_strings(string_vec!("argv[0]", "-vv"))
+            .count_max('v', "verbose", "verbosity", &mut verbosity, 1, 3)
+                .expect("bad count")
+        ;
+        assert_eq!(verbosity, 2, "expected the count below the max to be kept");
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .count_max('v', "verbose", "verbosity", &mut verbosity, 1, 3)
+                .expect("bad count")
+        ;
+        let help = parser.help_string();
+        assert!(help.contains("verbosity [default: 2, max: 3]\n"), "unexpected help:\n{}", help);
+    }
+}
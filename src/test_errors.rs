@@ -0,0 +1,189 @@
+#[cfg(test)]
+mod errors {
+    use crate::*;
+
+    #[test]
+    fn exit_codes() {
+        assert_eq!(Error::MissingArgument("--file".to_string()).exit_code(), 2);
+        assert_eq!(Error::MissingArgValue('f', "file").exit_code(), 2);
+        assert_eq!(Error::MissingSubcommand(vec!("build")).exit_code(), 2);
+
+        assert_eq!(Error::ConstructionError('f', "file", "bad".to_string()).exit_code(), 1);
+        assert_eq!(Error::PathNotFound("/nope".to_string()).exit_code(), 1);
+        assert_eq!(Error::InvalidState("bad state").exit_code(), 1);
+    }
+
+    #[test]
+    fn exit_code_from_parse() {
+        let mut file: String = "".to_string();
+        let mut count: usize = 0;
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]"));
+        match parser.arg('f', "file", "a file", &mut file, None, true) {
+            Err(e) => { assert_eq!(e.exit_code(), 2, "unexpected code for: {}", e); }
+            Ok(_) => { panic!("expected missing argument error"); }
+        }
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-c", "x"));
+        match parser.arg('c', "count", "a count", &mut count, None, false) {
+            Err(e) => { assert_eq!(e.exit_code(), 1, "unexpected code for: {}", e); }
+            Ok(_) => { panic!("expected construction error"); }
+        }
+    }
+
+    #[test]
+    fn construction_source() {
+        use std::error::Error as StdError;
+
+        let mut port: u16 = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--port", "abc"));
+        let err = match parser.arg_typed('p', "port", "a port", &mut port, None, false) {
+            Err(e) => { e }
+            Ok(_) => { panic!("expected construction error"); }
+        };
+        match err.source().and_then(|s| s.downcast_ref::<std::num::ParseIntError>()) {
+            Some(e) => { assert_eq!(e.kind(), &std::num::IntErrorKind::InvalidDigit); }
+            None => { panic!("expected a ParseIntError source for: {}", err); }
+        }
+        assert!(matches!(err.inner(), Error::ConstructionError('p', "port", _)),
+            "expected a construction error within: {:?}", err);
+        assert_eq!(err.exit_code(), 1);
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--port", "abc"));
+        match parser.arg('p', "port", "a port", &mut port, None, false) {
+            Err(e @ Error::ConstructionError('p', "port", _)) => {
+                assert!(e.source().is_none(), "did not expect a source for: {}", e);
+            }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected construction error"); }
+        }
+
+        let mut level: u8 = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "300"));
+        let err = match parser.positional_typed("level", "a level", &mut level, false) {
+            Err(e) => { e }
+            Ok(_) => { panic!("expected positional construction error"); }
+        };
+        assert!(err.source().is_some_and(|s| s.is::<std::num::ParseIntError>()),
+            "expected a ParseIntError source for: {}", err);
+
+        assert!(Error::MissingArgValue('f', "file").source().is_none(),
+            "did not expect a source for a missing value");
+    }
+
+    #[test]
+    fn help_hint() {
+        let mut file: String = "".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]"));
+        let err = match parser.arg('f', "file", "a file", &mut file, None, true) {
+            Err(e) => { e }
+            Ok(_) => { panic!("expected missing argument error"); }
+        };
+
+        assert_eq!(parser.format_error(&err), err.to_string());
+
+        parser.error_help_hint(true);
+        assert_eq!(parser.format_error(&err), format!("{}\nrun with --help for usage", err));
+    }
+
+    #[test]
+    fn validate_tree() {
+        let mut debug: bool = false;
+        let mut dry: bool = false;
+        let mut verbose: bool = false;
+        let mut subs: Vec<String> = vec!();
+        let mut target: String = "".to_string();
+        let mut config: String = "".to_string();
+        let mut files: Vec<String> = vec!();
+        let mut extra: String = "".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]"));
+        parser
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad debug flag")
+            .flag('d', "dry-run", "do nothing", &mut dry, false)
+                .expect("bad dry-run flag")
+            .subcommand("build", "do a build", &mut subs, None)
+                .expect("bad sub(build)")
+                .flag('d', "debug", "debug build", &mut debug, false)
+                    .expect("bad nested debug flag")
+                .positional("config", "config file", &mut config, false)
+                    .expect("bad config positional")
+                .positional("target", "build target", &mut target, true)
+                    .expect("bad target positional")
+                .positional_list("files", "files to build", &mut files, false)
+                    .expect("bad files positional")
+                .positional("extra", "an extra", &mut extra, false)
+                    .expect("bad extra positional")
+                .done().expect("no done on build")
+            .subcommand("test", "test a target", &mut subs, None)
+                .expect("bad sub(test)")
+                .group("output", "output options")
+                    .expect("bad group")
+                .flag('v', "verbose", "verbose output", &mut verbose, false)
+                    .expect("bad verbose flag")
+        ;
+
+        match parser.validate_tree() {
+            Err(Error::InvalidTree(problems)) => {
+                assert_eq!(problems, string_vec!(
+                    "top-level: duplicate short code -d",
+                    "build: required positional target declared after optional config",
+                    "build: positional extra declared after variadic files",
+                    "test: subcommand scope not closed with done()",
+                    "group output not closed with done()"
+                ));
+            }
+            Err(e) => { panic!("unexpected error: {}", e); }
+            Ok(_) => { panic!("expected an invalid tree"); }
+        }
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]"));
+        parser
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad debug flag")
+            .subcommand("build", "do a build", &mut subs, None)
+                .expect("bad sub(build)")
+                .flag('d', "debug", "debug build", &mut debug, false)
+                    .expect("bad nested debug flag")
+                .done().expect("no done on build")
+        ;
+        if let Err(e) = parser.validate_tree() {
+            panic!("unexpected error for a valid tree: {}", e);
+        }
+    }
+
+    #[test]
+    fn suggest_on_error() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("verbsoe", "verbose"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+
+        let error_for = |arg: &str, suggest: bool| {
+            let mut verbose: bool = false;
+            let mut output: String = "".to_string();
+
+            let mut parser = Parser::from_strings(string_vec!("argv[0]", arg));
+            parser
+                .suggest_on_error(suggest)
+                .on_unknown(|_| UnknownAction::Error)
+                .flag('v', "verbose", "verbose output", &mut verbose, false)
+                    .expect("bad flag")
+                .arg('o', "output", "output file", &mut output, None, false)
+                    .expect("bad arg")
+            ;
+            match parser.finish() {
+                Err(e) => { e.to_string() }
+                Ok(_) => { panic!("expected unexpected argument error for {}", arg); }
+            }
+        };
+
+        assert_eq!(error_for("--verbsoe", true),
+            "unexpected argument: --verbsoe; did you mean --verbose?");
+        assert_eq!(error_for("--ouptut=foo", true),
+            "unexpected argument: --ouptut=foo; did you mean --output?");
+        assert_eq!(error_for("--zzz", true), "unexpected argument: --zzz");
+        assert_eq!(error_for("--verbsoe", false), "unexpected argument: --verbsoe");
+    }
+}
@@ -19,6 +19,7 @@ mod flags {
     }
 
     #[test]
+    #[allow(clippy::bool_comparison)]
     fn inverted() {
         let mut debug_mode_short: bool = true;
         let mut debug_mode_long: bool = true;
@@ -32,6 +33,30 @@ mod flags {
         assert!(debug_mode_long == false, "did not invert with long flag");
     }
 
+    #[test]
+    fn linked() {
+        let mut verbose: bool = false;
+        Parser::from_strings(string_vec!("argv[0]", "--verbose", "--quiet"))
+            .linked_flags(('v', "verbose"), ('q', "quiet"), "toggle verbose output", &mut verbose)
+                .expect("bad linked flags")
+        ;
+        assert!(!verbose, "expected last given (--quiet) to win");
+
+        let mut verbose: bool = false;
+        Parser::from_strings(string_vec!("argv[0]", "-q", "--verbose"))
+            .linked_flags(('v', "verbose"), ('q', "quiet"), "toggle verbose output", &mut verbose)
+                .expect("bad linked flags")
+        ;
+        assert!(verbose, "expected last given (--verbose) to win");
+
+        let mut verbose: bool = true;
+        Parser::from_strings(string_vec!("argv[0]"))
+            .linked_flags(('v', "verbose"), ('q', "quiet"), "toggle verbose output", &mut verbose)
+                .expect("bad linked flags")
+        ;
+        assert!(verbose, "expected value to be untouched when neither is given");
+    }
+
     #[test]
     fn within_run() {
         let expect_count: usize = 8;
@@ -53,5 +78,299 @@ mod flags {
             "unexpected other value {}, wanted {}", other, expect_count);
         assert!(debug, "expected flag to be caugh within the run, got {}", debug);
     }
-}
 
+    #[test]
+    fn implies() {
+        let mut debug: bool = false;
+        let mut verbose: bool = false;
+        let mut trace: bool = false;
+        Parser::from_strings(string_vec!("argv[0]", "--debug"))
+            .implies("debug", "verbose")
+            .implies("verbose", "trace")
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad debug flag")
+            .linked_flags(('v', "verbose"), ('q', "quiet"), "verbosity", &mut verbose)
+                .expect("bad linked flags")
+            .flag('t', "trace", "trace mode", &mut trace, false)
+                .expect("bad trace flag")
+        ;
+        assert_eq!((debug, verbose, trace), (true, true, true));
+
+        let mut debug: bool = false;
+        let mut verbose: bool = false;
+        let mut trace: bool = false;
+        Parser::from_strings(string_vec!("argv[0]", "--verbose"))
+            .implies("debug", "verbose")
+            .implies("verbose", "trace")
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad debug flag")
+            .linked_flags(('v', "verbose"), ('q', "quiet"), "verbosity", &mut verbose)
+                .expect("bad linked flags")
+            .flag('t', "trace", "trace mode", &mut trace, false)
+                .expect("bad trace flag")
+        ;
+        assert_eq!((debug, verbose, trace), (false, true, true));
+
+        // flags given explicitly take precedence
+        let mut debug: bool = false;
+        let mut verbose: bool = false;
+        let mut trace: bool = false;
+        Parser::from_strings(string_vec!("argv[0]", "--debug", "--quiet"))
+            .implies("debug", "verbose")
+            .implies("verbose", "trace")
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad debug flag")
+            .linked_flags(('v', "verbose"), ('q', "quiet"), "verbosity", &mut verbose)
+                .expect("bad linked flags")
+            .flag('t', "trace", "trace mode", &mut trace, false)
+                .expect("bad trace flag")
+        ;
+        assert_eq!((debug, verbose, trace), (true, false, false));
+
+        // a trigger swallowed as the value of another arg was never given as a flag
+        let mut output: String = "".to_string();
+        let mut debug: bool = false;
+        let mut verbose: bool = false;
+        Parser::from_strings(string_vec!("argv[0]", "--output", "--debug"))
+            .implies("debug", "verbose")
+            .arg('o', "output", "output file", &mut output, None, false)
+                .expect("bad output arg")
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad debug flag")
+            .flag('v', "verbose", "verbose mode", &mut verbose, false)
+                .expect("bad verbose flag")
+        ;
+        assert_eq!(output, "--debug");
+        assert!(!debug, "expected --debug to be taken as a value");
+        assert!(!verbose, "expected a swallowed trigger to imply nothing");
+
+        // nor is a trigger given within a subcommand which was not taken
+        let mut subs: Vec<String> = vec!();
+        let mut debug: bool = false;
+        let mut verbose: bool = false;
+        Parser::from_strings(string_vec!("argv[0]", "build", "--debug"))
+            .implies("debug", "verbose")
+            .subcommand("test", "run tests", &mut subs, None)
+                .expect("bad subcommand")
+                .flag('d', "debug", "debug mode", &mut debug, false)
+                    .expect("bad debug flag")
+                .done().expect("failed to close subcommand")
+            .flag('v', "verbose", "verbose mode", &mut verbose, false)
+                .expect("bad verbose flag")
+        ;
+        assert!(!debug, "did not expect --debug outside of its subcommand");
+        assert!(!verbose, "expected a trigger in another subcommand to imply nothing");
+    }
+
+    #[test]
+    fn take_flag() {
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--verbose", "-xd", "--color=no"));
+
+        assert!(parser.take_flag('v', "verbose"), "expected --verbose to be present");
+        assert!(!parser.take_flag('v', "verbose"), "expected --verbose to be consumed");
+        assert!(parser.take_flag('d', "debug"), "expected -d within the run to be present");
+        assert!(!parser.take_flag('q', "quiet"), "did not expect --quiet to be present");
+        assert!(!parser.take_flag('\0', "color"), "did not expect a valued flag to be present");
+
+        let unused = parser.unused().into_iter().map(|u| u.arg).collect::<Vec<String>>();
+        assert_eq!(unused, string_vec!("-x", "--color=no"));
+        assert!(parser.matched_options().contains_key("verbose"), "expected verbose to be matched");
+    }
+
+    #[test]
+    fn version() {
+        let mut file: String = "".to_string();
+        let mut subs: Vec<String> = vec!();
+
+        for arg in ["-V", "--version"] {
+            let mut parser = Parser::from_strings(string_vec!("argv[0]", arg));
+            parser
+                .app_name("rags")
+                .app_version("1.0")
+                .arg('f', "file", "a file", &mut file, None, true)
+                    .expect("required arg should not be enforced")
+                .subcommand("build", "do a build", &mut subs, None)
+                    .expect("bad sub(build)")
+                    .done().expect("no done on build")
+                .require_subcommand()
+                    .expect("subcommand should not be required")
+            ;
+            assert!(parser.wants_version(), "expected {} to request the version", arg);
+            assert!(!parser.wants_help(), "did not expect {} to request help", arg);
+            assert_eq!(parser.printer.version_line(), "rags 1.0");
+            assert!(parser.unused().is_empty(), "expected {} to be consumed", arg);
+        }
+
+        let parser = Parser::from_strings(string_vec!("argv[0]"));
+        assert!(!parser.wants_version(), "did not expect the version to be requested");
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-V", "--ver"));
+        parser.version_flag('\0', "ver").expect("bad version flag");
+        assert!(parser.wants_version(), "expected --ver to request the version");
+        assert_eq!(parser.unused().len(), 1, "expected -V to be left unused");
+
+        let mut verify: bool = false;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-V"));
+        parser
+            .no_version_flag()
+                .expect("bad version flag")
+            .flag('V', "verify", "verify the output", &mut verify, false)
+                .expect("bad flag")
+        ;
+        assert!(!parser.wants_version(), "did not expect the version to be requested");
+        assert!(verify, "expected -V to be available when the version flag is disabled");
+
+        // the version flag gives up a code the application declares itself
+        let mut verbose: bool = false;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-xV"));
+        parser
+            .flag('x', "extract", "extract files", &mut verify, false)
+                .expect("bad flag")
+            .flag('V', "verbose", "verbose output", &mut verbose, false)
+                .expect("bad flag")
+        ;
+        assert!(!parser.wants_version(), "expected -V to be given up by the version flag");
+        assert!(verbose, "expected -V to be matched as --verbose");
+        assert!(!parser.matched_options().contains_key("version"), "unexpected version match");
+        assert!(parser.validate_tree().is_ok(), "expected no duplicate -V");
+
+        let mut verbose: bool = false;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--version"));
+        parser
+            .flag('V', "verbose", "verbose output", &mut verbose, false)
+                .expect("bad flag")
+        ;
+        assert!(parser.wants_version(), "expected --version to still request the version");
+        assert!(!verbose, "did not expect --version to be matched as --verbose");
+    }
+
+    #[test]
+    fn help_flags() {
+        let mut host: String = "".to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-?"));
+        parser
+            .help_flags('?', "usage")
+                .expect("bad help flags")
+            .arg('h', "host", "host to connect to", &mut host, None, false)
+                .expect("bad host arg")
+        ;
+        assert!(parser.wants_help(), "expected -? to request help");
+        assert!(parser.unused().is_empty(), "expected -? to be consumed");
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--usage"));
+        parser.help_flags('?', "usage").expect("bad help flags");
+        assert!(parser.wants_help(), "expected --usage to request help");
+
+        let mut host: String = "".to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-h", "example.com"));
+        parser
+            .help_flags('?', "usage")
+                .expect("bad help flags")
+            .arg('h', "host", "host to connect to", &mut host, None, false)
+                .expect("bad host arg")
+        ;
+        assert!(!parser.wants_help(), "did not expect -h to request help");
+        assert_eq!(host, "example.com");
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser.help_flags('?', "usage").expect("bad help flags");
+        assert!(!parser.wants_help(), "did not expect --help to request help");
+        assert_eq!(parser.unused().len(), 1, "expected --help to be left unused");
+
+        // help on empty is kept when the help flags are changed afterwards
+        let mut parser = Parser::from_strings(string_vec!("argv[0]"));
+        parser
+            .help_on_empty()
+            .help_flags('?', "usage")
+                .expect("bad help flags")
+        ;
+        assert!(parser.wants_help(), "expected help with no args");
+
+        // declarations would be lost by matching the help flag again
+        let mut host: String = "".to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-h", "example.com"));
+        parser.arg('h', "host", "host to connect to", &mut host, None, false)
+            .expect("bad host arg");
+        match parser.help_flags('?', "usage") {
+            Err(Error::InvalidState(_)) => {}
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected changing the help flags late to fail"); }
+        }
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]"));
+        parser.help_flags('?', "usage").expect("bad help flags").error_help_hint(true);
+        let formatted = parser.format_error(&Error::MissingArgument("--host".to_string()));
+        assert!(formatted.ends_with("run with --usage for usage"), "unexpected hint: {}", formatted);
+    }
+
+    #[test]
+    fn defaults() {
+        let mut color: bool = false;
+        Parser::from_strings(string_vec!("argv[0]"))
+            .flag_default('n', "no-color", "disable color", &mut color, true)
+                .expect("bad flag")
+        ;
+        assert!(color, "expected default to be applied");
+
+        let mut color: bool = false;
+        Parser::from_strings(string_vec!("argv[0]", "--no-color"))
+            .flag_default('n', "no-color", "disable color", &mut color, true)
+                .expect("bad flag")
+        ;
+        assert!(!color, "expected flag to override");
+    }
+
+    #[test]
+    fn bool_flag() {
+        let mut debug: bool = true;
+        Parser::from_strings(string_vec!("argv[0]"))
+            .bool_flag('d', "debug", "debug mode", &mut debug)
+                .expect("bad bool flag")
+        ;
+        assert!(debug, "expected default to be kept");
+
+        let mut debug: bool = true;
+        Parser::from_strings(string_vec!("argv[0]", "--debug"))
+            .bool_flag('d', "debug", "debug mode", &mut debug)
+                .expect("bad bool flag")
+        ;
+        assert!(debug, "expected bare flag to set true");
+
+        let mut debug: bool = true;
+        Parser::from_strings(string_vec!("argv[0]", "--debug=false"))
+            .bool_flag('d', "debug", "debug mode", &mut debug)
+                .expect("bad bool flag")
+        ;
+        assert!(!debug, "expected false to unset");
+
+        let mut debug: bool = true;
+        Parser::from_strings(string_vec!("argv[0]", "--debug=No"))
+            .bool_flag('d', "debug", "debug mode", &mut debug)
+                .expect("bad bool flag")
+        ;
+        assert!(!debug, "expected No to unset");
+
+        let mut debug: bool = true;
+        Parser::from_strings(string_vec!("argv[0]", "-d=0"))
+            .bool_flag('d', "debug", "debug mode", &mut debug)
+                .expect("bad bool flag")
+        ;
+        assert!(!debug, "expected 0 to unset");
+
+        let mut debug: bool = true;
+        Parser::from_strings(string_vec!("argv[0]", "--debug=yes"))
+            .bool_flag('d', "debug", "debug mode", &mut debug)
+                .expect("bad bool flag")
+        ;
+        assert!(debug, "expected yes to set");
+
+        let mut debug: bool = true;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--debug=maybe"));
+        let result = parser.bool_flag('d', "debug", "debug mode", &mut debug);
+        match result {
+            Err(Error::InvalidChoice('d', "debug", given, _)) => { assert_eq!(given, "maybe"); }
+            Err(e) => { panic!("unexpected error: {}", e); }
+            Ok(_) => { panic!("expected an invalid choice"); }
+        }
+    }
+}
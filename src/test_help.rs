@@ -0,0 +1,1133 @@
+#[cfg(test)]
+mod help {
+    use crate::*;
+
+    #[test]
+    fn usage_name() {
+        let mut debug: bool = false;
+
+        let mut parser = Parser::from_strings(string_vec!("/usr/bin/rags-link", "--help"));
+        parser
+            .app_name("rags")
+            .app_version("1.0")
+            .usage_name("my-rags")
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+        ;
+
+        let help = parser.printer.render();
+        assert!(help.starts_with("rags - 1.0\n"), "unexpected header:\n{}", help);
+        assert!(help.contains("usage: my-rags [-d]"), "unexpected usage:\n{}", help);
+
+        parser.usage_name_from_args();
+        let help = parser.printer.render();
+        assert!(help.starts_with("rags - 1.0\n"), "unexpected header:\n{}", help);
+        assert!(help.contains("usage: rags-link [-d]"), "unexpected usage:\n{}", help);
+    }
+
+    #[test]
+    fn print_to_writer() {
+        let mut debug: bool = false;
+        let mut file: String = "".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .app_name("rags")
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .positional("file", "file to use", &mut file, false)
+                .expect("bad positional")
+        ;
+
+        let mut out: Vec<u8> = vec!();
+        parser.print_help_to(&mut out).expect("failed to print help");
+        let help = String::from_utf8(out).expect("help is not utf8");
+        assert_eq!(help, parser.printer.render());
+        assert!(help.starts_with("rags\n"), "unexpected header:\n{}", help);
+        assert!(help.contains("usage: rags [-d] [file]\n"), "unexpected usage:\n{}", help);
+    }
+
+    #[test]
+    fn help_string() {
+        let mut subs: Vec<String> = vec!();
+        let mut debug: bool = false;
+        let mut release: bool = false;
+        let mut filter: String = "".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "build", "--help"));
+        parser
+            .app_name("rags")
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .subcommand("build", "build a target", &mut subs, None)
+                .expect("bad sub(build)")
+                .flag('r', "release", "release build", &mut release, false)
+                    .expect("bad release flag")
+                .done().expect("no done on build")
+            .subcommand("test", "test a target", &mut subs, None)
+                .expect("bad sub(test)")
+                .arg('f', "filter", "test filter", &mut filter, None, false)
+                    .expect("bad filter arg")
+                .done().expect("no done on test")
+        ;
+
+        let mut out: Vec<u8> = vec!();
+        parser.print_help_to(&mut out).expect("failed to print help");
+        let help = parser.help_string();
+        assert_eq!(help, String::from_utf8(out).expect("help is not utf8"));
+        assert!(help.contains("--release"), "expected build options:\n{}", help);
+        assert!(!help.contains("--filter"), "did not expect test options:\n{}", help);
+    }
+
+    #[test]
+    fn nested_subcommand() {
+        let mut subs: Vec<String> = vec!();
+        let mut build_subs: Vec<String> = vec!();
+        let mut debug: bool = false;
+        let mut release: bool = false;
+        let mut deep: bool = false;
+        let mut other: bool = false;
+        let mut after: bool = false;
+        let mut filter: String = "".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "build", "sub", "--help"));
+        parser
+            .app_name("rags")
+            .app_version("1.0")
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .subcommand("an-especially-long-name", "another top-level subcommand", &mut subs, None)
+                .expect("bad sub(an-especially-long-name)")
+                .done().expect("no done on an-especially-long-name")
+            .subcommand("build", "build a target", &mut subs, Some("long build description"))
+                .expect("bad sub(build)")
+                .flag('r', "release", "release build", &mut release, false)
+                    .expect("bad release flag")
+                .subcommand("other", "other nested", &mut build_subs, None)
+                    .expect("bad sub(other)")
+                    .flag('o', "other", "other nested flag", &mut other, false)
+                        .expect("bad other flag")
+                    .done().expect("no done on other")
+                .subcommand("sub", "nested subcommand", &mut build_subs, Some("long sub description"))
+                    .expect("bad sub(sub)")
+                    .flag('x', "deep", "nested flag", &mut deep, false)
+                        .expect("bad deep flag")
+                    .done().expect("no done on sub")
+                .flag('a', "after", "declared after the nested subcommands", &mut after, false)
+                    .expect("bad after flag")
+                .done().expect("no done on build")
+            .subcommand("test", "test a target", &mut subs, None)
+                .expect("bad sub(test)")
+                .arg('f', "filter", "test filter", &mut filter, None, false)
+                    .expect("bad filter arg")
+                .done().expect("no done on test")
+        ;
+
+        let expect = "\
+rags build sub - 1.0 - nested subcommand
+
+usage: rags build sub [-drx]
+
+long sub description
+
+options:
+    -d, --debug          debug mode [default: false]
+    -r, --release        release build [default: false]
+    -x, --deep           nested flag [default: false]
+
+";
+        assert_eq!(parser.help_string(), expect);
+    }
+
+    #[test]
+    fn hidden() {
+        let mut debug: bool = false;
+        let mut trace: bool = false;
+        let mut dump: String = "".to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .app_name("rags")
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .flag('\0', "trace-internals", "dump internal state", &mut trace, false)
+                .expect("bad hidden flag")
+                .hidden()
+            .group("internal", "internal options")
+                .expect("bad group")
+                .arg('\0', "dump-everything-to", "dump state to a file", &mut dump, None, false)
+                    .expect("bad hidden arg")
+                    .hidden()
+                .done().expect("failed to close group")
+        ;
+        let help = parser.help_string();
+        assert!(!help.contains("trace-internals"), "did not expect hidden flag:\n{}", help);
+        assert!(!help.contains("dump-everything-to"), "did not expect hidden arg:\n{}", help);
+        assert!(!help.contains("internal:"), "did not expect empty group:\n{}", help);
+        assert!(help.contains("usage: rags [-d]\n"), "unexpected usage:\n{}", help);
+        assert!(help.contains("    -d, --debug        debug mode [default: false]\n"),
+            "expected alignment to ignore hidden options:\n{}", help);
+
+        let mut debug: bool = false;
+        let mut trace: bool = false;
+        let mut dump: String = "".to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--trace-internals", "--dump-everything-to", "x"));
+        parser
+            .app_name("rags")
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .flag('\0', "trace-internals", "dump internal state", &mut trace, false)
+                .expect("bad hidden flag")
+                .hidden()
+            .group("internal", "internal options")
+                .expect("bad group")
+                .arg('\0', "dump-everything-to", "dump state to a file", &mut dump, None, false)
+                    .expect("bad hidden arg")
+                    .hidden()
+                .done().expect("failed to close group")
+        ;
+        assert!(trace, "expected hidden flag to be parsed");
+        assert_eq!(dump, "x", "expected hidden arg to be parsed");
+    }
+
+    #[test]
+    fn hidden_excluded() {
+        let mut debug: bool = false;
+        let mut trace: bool = false;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--trace-internalz"));
+        parser
+            .suggest_on_error(true)
+            .on_unknown(|_| UnknownAction::Error)
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .flag('\0', "trace-internals", "dump internal state", &mut trace, false)
+                .expect("bad hidden flag")
+                .hidden()
+        ;
+        let script = parser.generate_zsh_completion("my-app");
+        assert!(script.contains("--debug"), "expected visible flag:\n{}", script);
+        assert!(!script.contains("trace-internals"), "did not expect hidden flag:\n{}", script);
+        match parser.finish() {
+            Err(e) => {
+                assert_eq!(e.to_string(), "unexpected argument: --trace-internalz");
+            }
+            Ok(_) => { panic!("expected unexpected argument error"); }
+        }
+    }
+
+    #[test]
+    fn hidden_only_options() {
+        let mut debug: bool = false;
+        let mut input: String = "".to_string();
+        let mut build: Vec<String> = vec!();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .app_name("rags")
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .subcommand("build", "build the project", &mut build, None)
+                .expect("bad subcommand")
+                .hidden()
+            .done().expect("failed to close subcommand")
+            .positional("input", "input file", &mut input, false)
+                .expect("bad positional")
+                .hidden()
+        ;
+        let help = parser.help_string();
+        assert!(help.contains("-d, --debug"), "expected flag to remain visible:\n{}", help);
+        assert!(help.contains("[-d]"), "unexpected usage:\n{}", help);
+        assert!(help.contains("build"), "expected subcommand to remain visible:\n{}", help);
+        assert!(help.contains("input"), "expected positional to remain visible:\n{}", help);
+    }
+
+    #[test]
+    fn positional_usage() {
+        let mut input: String = "".to_string();
+        let mut output: String = "".to_string();
+        let mut rest: Vec<String> = vec!();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .app_name("rags")
+            .positional("input", "file to read", &mut input, true)
+                .expect("bad input positional")
+            .positional("output", "file to write", &mut output, false)
+                .expect("bad output positional")
+            .positional_list("rest", "more files", &mut rest, false)
+                .expect("bad positional list")
+        ;
+
+        let help = parser.printer.render();
+        assert!(help.contains("usage: rags input [output] [rest...]\n"), "unexpected usage:\n{}", help);
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .app_name("rags")
+            .positional_list("files", "files to read", &mut rest, true)
+                .expect("bad positional list")
+        ;
+        let help = parser.printer.render();
+        assert!(help.contains("usage: rags files...\n"), "unexpected usage:\n{}", help);
+    }
+
+    #[test]
+    fn required_only_usage() {
+        let mut package: String = "".to_string();
+        let mut force: bool = false;
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .app_name("rags")
+            .arg('p', "package", "package to build", &mut package, Some("PKG"), true)
+                .expect("bad package arg")
+        ;
+        let help = parser.printer.render();
+        assert!(help.contains("usage: rags -p PKG\n"), "unexpected usage:\n{}", help);
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .app_name("rags")
+            .arg('p', "package", "package to build", &mut package, Some("PKG"), true)
+                .expect("bad package arg")
+            .flag('f', "force", "force the build", &mut force, false)
+                .expect("bad flag")
+        ;
+        let help = parser.printer.render();
+        assert!(help.contains("usage: rags [-f] -p PKG\n"), "unexpected usage:\n{}", help);
+    }
+
+    #[test]
+    fn usage_wrapping() {
+        let mut debug: bool = false;
+        let mut package: String = "".to_string();
+        let mut lib: String = "".to_string();
+        let mut file: String = "".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .app_name("rags")
+            .help_width(28)
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .arg('p', "package", "package to build", &mut package, Some("PKG"), true)
+                .expect("bad package arg")
+            .arg('l', "lib", "library to link", &mut lib, Some("LIB"), true)
+                .expect("bad lib arg")
+            .positional("file", "file to build", &mut file, true)
+                .expect("bad positional")
+        ;
+
+        let help = parser.printer.render();
+        let expected = "usage: rags [-d] -p PKG\n            -l LIB file\n";
+        assert!(help.contains(expected), "unexpected usage:\n{}", help);
+
+        parser.help_width(80);
+        let help = parser.printer.render();
+        assert!(help.contains("usage: rags [-d] -p PKG -l LIB file\n"), "unexpected usage:\n{}", help);
+    }
+
+    #[test]
+    fn markdown() {
+        let mut subs: Vec<String> = vec!();
+        let mut debug: bool = false;
+        let mut package: String = "main".to_string();
+        let mut file: String = "".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .app_name("rags")
+            .app_desc("an example")
+            .flag('d', "debug", "debug mode | verbose", &mut debug, false)
+                .expect("bad flag")
+            .arg('p', "package", "package name", &mut package, Some("PKG"), true)
+                .expect("bad arg")
+            .subcommand("build", "build a target", &mut subs, None)
+                .expect("bad subcommand")
+                .done().expect("failed to close subcommand")
+            .positional("file", "file to use", &mut file, false)
+                .expect("bad positional")
+        ;
+
+        let md = parser.help_markdown();
+        assert!(md.starts_with("# rags\n\nan example\n\n"), "unexpected markdown:\n{}", md);
+        assert!(md.contains("```text\nrags {subcommand} [-d] -p PKG [file]\n```"),
+            "missing usage block:\n{}", md);
+        assert!(md.contains("## Subcommands\n"), "missing subcommands:\n{}", md);
+        assert!(md.contains("| `build` | build a target |\n"), "missing subcommand row:\n{}", md);
+        assert!(md.contains("## Options\n"), "missing options:\n{}", md);
+        assert!(md.contains("| `-d, --debug` | debug mode \\| verbose | `false` |\n"),
+            "missing debug row:\n{}", md);
+        assert!(md.contains("| `-p, --package PKG` | package name *(required)* | `main` |\n"),
+            "missing package row:\n{}", md);
+        assert!(md.contains("## Positionals\n"), "missing positionals:\n{}", md);
+        assert!(md.contains("| `file` | file to use |  |\n"), "missing file row:\n{}", md);
+    }
+
+    #[test]
+    fn markdown_accessories() {
+        let mut level: String = "info".to_string();
+        let mut token: String = "".to_string();
+        let mut jobs: u8 = 4;
+        let mut mode: String = "fast".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .app_name("rags")
+            .arg_choices('l', "level", "log level", &mut level, None, false, &["info", "debug"])
+                .expect("bad arg")
+            .arg_env('t', "token", "api token", &mut token, None, true, "RAGS_TOKEN")
+                .expect("bad arg")
+            .arg_range('j', "jobs", "parallel jobs", &mut jobs, None, false, 1, 64)
+                .expect("bad arg")
+            .positional_choices("mode", "run mode", &mut mode, false, &["fast", "slow"])
+                .expect("bad positional")
+        ;
+
+        let md = parser.help_markdown();
+        assert!(md.contains("| `-l, --level` | log level [choices: info\\|debug] | `info` |\n"),
+            "missing choices:\n{}", md);
+        assert!(md.contains("| `-t, --token` | api token [env: RAGS_TOKEN] *(required)* |  |\n"),
+            "missing env:\n{}", md);
+        assert!(md.contains("| `-j, --jobs` | parallel jobs [range: 1..=64] | `4` |\n"),
+            "missing range:\n{}", md);
+        assert!(md.contains("| `mode` | run mode [choices: fast\\|slow] | `fast` |\n"),
+            "missing positional choices:\n{}", md);
+    }
+
+    #[test]
+    fn markdown_multiline_desc() {
+        let mut subs: Vec<String> = vec!();
+        let mut debug: bool = false;
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .app_name("rags")
+            .flag('d', "debug", "debug mode\nalso traces", &mut debug, false)
+                .expect("bad flag")
+            .subcommand("build", "build a target\nin release mode", &mut subs, None)
+                .expect("bad subcommand")
+                .done().expect("failed to close subcommand")
+        ;
+
+        let md = parser.help_markdown();
+        assert!(md.contains("| `build` | build a target<br>in release mode |\n"),
+            "expected subcommand row to stay on one line:\n{}", md);
+        assert!(md.contains("| `-d, --debug` | debug mode<br>also traces | `false` |\n"),
+            "expected option row to stay on one line:\n{}", md);
+    }
+
+    #[test]
+    fn bash_completion() {
+        let mut subs: Vec<String> = vec!();
+        let mut debug: bool = false;
+        let mut file: String = "".to_string();
+        let mut out: String = "".to_string();
+        let mut level: String = "info".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .arg_completed('f', "file", "input file", &mut file, None, false, Completion::File)
+                .expect("bad file arg")
+            .arg_completed('\0', "out-dir", "output dir", &mut out, None, false, Completion::Dir)
+                .expect("bad dir arg")
+            .arg_completed('l', "level", "log level", &mut level, None, false,
+                Completion::Words(&["debug", "info"]))
+                .expect("bad level arg")
+            .subcommand("build", "build a target", &mut subs, None)
+                .expect("bad subcommand")
+                .done().expect("failed to close subcommand")
+        ;
+
+        let script = parser.generate_bash_completion("my-app");
+        assert!(script.starts_with("_my_app() {\n"), "unexpected script:\n{}", script);
+        assert!(script.contains(
+            "        -f|--file)\n            COMPREPLY=($(compgen -f -- \"$cur\"))\n"),
+            "missing file completion:\n{}", script);
+        assert!(script.contains(
+            "        --out-dir)\n            COMPREPLY=($(compgen -d -- \"$cur\"))\n"),
+            "missing dir completion:\n{}", script);
+        assert!(script.contains(
+            "        -l|--level)\n            COMPREPLY=($(compgen -W \"debug info\" -- \"$cur\"))\n"),
+            "missing word completion:\n{}", script);
+        assert!(script.contains(
+            "    COMPREPLY=($(compgen -W \"-d --debug -f --file --out-dir -l --level build\" -- \"$cur\"))\n"),
+            "missing option completion:\n{}", script);
+        assert!(script.ends_with("}\ncomplete -F _my_app my-app\n"), "unexpected script:\n{}", script);
+
+        Parser::from_strings(string_vec!("argv[0]", "--file", "foo.txt"))
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .arg_completed('f', "file", "input file", &mut file, None, false, Completion::File)
+                .expect("bad file arg")
+            .arg_completed('\0', "out-dir", "output dir", &mut out, None, false, Completion::Dir)
+                .expect("bad dir arg")
+            .arg_completed('l', "level", "log level", &mut level, None, false,
+                Completion::Words(&["debug", "info"]))
+                .expect("bad level arg")
+            .subcommand("build", "build a target", &mut subs, None)
+                .expect("bad subcommand")
+                .done().expect("failed to close subcommand")
+        ;
+        assert_eq!(file, "foo.txt", "expected completed arg to parse as usual");
+    }
+
+    #[test]
+    fn zsh_completion() {
+        let mut subs: Vec<String> = vec!();
+        let mut debug: bool = false;
+        let mut file: String = "".to_string();
+        let mut level: String = "info".to_string();
+        let mut jobs: usize = 1;
+        let mut target: String = "".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]"));
+        parser
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .arg_completed('f', "file", "input file", &mut file, None, false, Completion::File)
+                .expect("bad file arg")
+            .arg_choices('l', "level", "log [level]", &mut level, None, false, &["debug", "info"])
+                .expect("bad level arg")
+            .subcommand("build", "build a target", &mut subs, None)
+                .expect("bad subcommand")
+                .long_arg("jobs", "parallel jobs", &mut jobs, None, false)
+                    .expect("bad jobs arg")
+                .positional("target", "what to build", &mut target, true)
+                    .expect("bad positional")
+                .done().expect("failed to close subcommand")
+        ;
+
+        let script = parser.generate_zsh_completion("my-app");
+        assert!(script.starts_with("#compdef my-app\n\n_my-app() {\n"), "unexpected script:\n{}", script);
+        assert!(script.contains(
+            "        '(-h --help)'{-h,--help}'[print this help dialog]' \\\n        '(-d --debug)'{-d,--debug}'[debug mode]' \\\n"),
+            "missing flag spec:\n{}", script);
+        assert!(script.contains("        '(-f --file)'{-f,--file=}'[input file]:file:_files' \\\n"),
+            "missing file spec:\n{}", script);
+        assert!(script.contains("        '(-l --level)'{-l,--level=}'[log \\[level\\]]:level:(debug info)' \\\n"),
+            "missing level spec:\n{}", script);
+        assert!(script.contains("                'build:build a target'\n"),
+            "missing subcommand description:\n{}", script);
+        assert!(script.contains("                'build') _my-app_build ;;\n"),
+            "missing subcommand dispatch:\n{}", script);
+        assert!(script.contains(
+            "_my-app_build() {\n    _arguments -s -S \\\n        --jobs='[parallel jobs]:jobs: ' \\\n        ':target:_default'\n}\n"),
+            "missing subcommand function:\n{}", script);
+        assert!(script.ends_with("    compdef _my-app my-app\nfi\n"), "unexpected script:\n{}", script);
+    }
+
+    #[test]
+    fn manpage() {
+        let mut subs: Vec<String> = vec!();
+        let mut debug: bool = false;
+        let mut package: String = "main".to_string();
+        let mut file: String = "".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .app_name("rags")
+            .app_version("1.0")
+            .app_desc("an example")
+            .app_long_desc("longer description\n\n.starts with a dot")
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .arg('p', "package", "package name", &mut package, Some("PKG"), true)
+                .expect("bad arg")
+            .subcommand("build", "build a target", &mut subs, None)
+                .expect("bad subcommand")
+                .done().expect("failed to close subcommand")
+            .positional("file", "file to use", &mut file, false)
+                .expect("bad positional")
+        ;
+
+        let man = parser.generate_manpage(1);
+        assert!(man.starts_with(".TH \"RAGS\" \"1\" \"\" \"rags 1.0\" \"\"\n"),
+            "unexpected title:\n{}", man);
+        assert!(man.contains(".SH NAME\nrags \\- an example\n"), "missing name:\n{}", man);
+        assert!(man.contains(".SH SYNOPSIS\n.B rags\n{subcommand} [\\-d] \\-p PKG [file]\n"),
+            "missing synopsis:\n{}", man);
+        assert!(man.contains(".SH DESCRIPTION\nlonger description\n.PP\n\\&.starts with a dot\n"),
+            "missing description:\n{}", man);
+        assert!(man.contains(".SH COMMANDS\n.TP\n.B build\nbuild a target\n"),
+            "missing commands:\n{}", man);
+        assert!(man.contains(".SH OPTIONS\n"), "missing options:\n{}", man);
+        assert!(man.contains(".TP\n.B \\-p, \\-\\-package PKG\npackage name [required, default: main]\n"),
+            "missing option entry:\n{}", man);
+        assert!(man.contains(".SH ARGUMENTS\n.TP\n.B file\nfile to use\n"),
+            "missing arguments:\n{}", man);
+    }
+
+    #[test]
+    fn manpage_accessories() {
+        let mut level: String = "info".to_string();
+        let mut token: String = "".to_string();
+        let mut jobs: u8 = 4;
+        let mut mode: String = "fast".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .app_name("rags")
+            .arg_choices('l', "level", "log level", &mut level, None, false, &["info", "debug"])
+                .expect("bad arg")
+            .arg_env('t', "token", "api token", &mut token, None, true, "RAGS_TOKEN")
+                .expect("bad arg")
+            .arg_range('j', "jobs", "parallel jobs", &mut jobs, None, false, 1, 64)
+                .expect("bad arg")
+            .positional_choices("mode", "run mode", &mut mode, false, &["fast", "slow"])
+                .expect("bad positional")
+        ;
+
+        let man = parser.generate_manpage(1);
+        assert!(man.contains(".B \\-l, \\-\\-level\nlog level [choices: info|debug] [default: info]\n"),
+            "missing choices:\n{}", man);
+        assert!(man.contains(".B \\-t, \\-\\-token\napi token [required] [env: RAGS_TOKEN]\n"),
+            "missing env:\n{}", man);
+        assert!(man.contains(".B \\-j, \\-\\-jobs\nparallel jobs [range: 1..=64] [default: 4]\n"),
+            "missing range:\n{}", man);
+        assert!(man.contains(".B mode\nrun mode [choices: fast|slow] [default: fast]\n"),
+            "missing positional choices:\n{}", man);
+    }
+
+    #[test]
+    fn manpage_multiline_desc() {
+        let mut debug: bool = false;
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .app_name("rags")
+            .flag('d', "debug", "debug mode\n.also traces\n'quoted", &mut debug, false)
+                .expect("bad flag")
+        ;
+
+        let man = parser.generate_manpage(1);
+        assert!(man.contains(".TP\n.B \\-d, \\-\\-debug\ndebug mode\n\\&.also traces\n\\&'quoted [default: false]\n"),
+            "expected every line to be escaped:\n{}", man);
+    }
+
+    #[test]
+    fn positional_groups() {
+        let mut src: String = "".to_string();
+        let mut dst: String = "".to_string();
+        let mut mode: String = "".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .positional("mode", "copy mode", &mut mode, false)
+                .expect("bad positional")
+            .positional_group("paths", "where to copy")
+                .expect("bad positional group")
+                .positional("src", "copy from", &mut src, true)
+                    .expect("bad positional")
+                .positional("dst", "copy to", &mut dst, true)
+                    .expect("bad positional")
+                .done().expect("failed to close positional group")
+        ;
+
+        let help = parser.printer.render();
+        let expected = "\
+positionals:
+    mode        copy mode
+
+paths:          where to copy
+    src         copy from [required]
+    dst         copy to [required]
+
+";
+        assert!(help.ends_with(expected), "unexpected help dialog:\n{}", help);
+    }
+
+    #[test]
+    fn positional_group_nesting() {
+        let mut parser = Parser::from_strings(string_vec!("argv[0]"));
+        let result = parser
+            .group("logging", "adjust logging")
+                .expect("bad group")
+            .positional_group("paths", "where to copy")
+        ;
+
+        match result {
+            Ok(_) => { panic!("expected nested group error"); }
+            Err(Error::NestedGroup("logging", "paths")) => {}
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+        }
+    }
+
+    #[test]
+    fn flatten_groups() {
+        let build = |flatten: bool| {
+            let mut debug: bool = false;
+            let mut verbose: bool = false;
+
+            let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+            if flatten {
+                parser.flatten_groups_in_help();
+            }
+            parser
+                .flag('d', "debug", "debug mode", &mut debug, false)
+                    .expect("bad flag")
+                .group("logging", "adjust logging")
+                    .expect("bad group")
+                    .flag('v', "verbose", "more output", &mut verbose, false)
+                        .expect("bad flag")
+                    .done().expect("failed to close group")
+            ;
+            parser.printer.render()
+        };
+
+        let grouped = build(false);
+        assert!(grouped.contains("logging:"), "expected logging group:\n{}", grouped);
+
+        let flat = build(true);
+        assert!(!flat.contains("logging:"), "unexpected logging group:\n{}", flat);
+        let expected = "\
+options:
+    -d, --debug          debug mode [default: false]
+    -v, --verbose        more output [default: false]
+
+";
+        assert!(flat.ends_with(expected), "unexpected help dialog:\n{}", flat);
+    }
+
+    #[test]
+    fn gap() {
+        let mut debug: bool = false;
+        let mut file: String = "".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .help_gap(2)
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .positional("file", "file to use", &mut file, false)
+                .expect("bad positional")
+        ;
+
+        let help = parser.printer.render();
+        let expected = "\
+options:
+    -d, --debug  debug mode [default: false]
+
+positionals:
+    file         file to use
+
+";
+        assert!(help.ends_with(expected), "unexpected help dialog:\n{}", help);
+    }
+
+    #[test]
+    fn layout() {
+        let mut debug: bool = false;
+        let mut level: String = "info".to_string();
+        let mut subs: Vec<String> = vec!();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .help_layout(2, 3)
+            .subcommand("build", "build a target", &mut subs, None)
+                .expect("bad subcommand")
+                .done().expect("failed to close subcommand")
+            .group("logging", "control the log output")
+                .expect("bad group")
+                .arg('l', "level", "log level", &mut level, None, false)
+                    .expect("bad level arg")
+                .done().expect("failed to close group")
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+        ;
+
+        let help = parser.printer.render();
+        let expected = "\
+subcommands:
+  build         build a target
+
+logging:        control the log output
+  -l, --level   log level [default: info]
+
+options:
+  -d, --debug   debug mode [default: false]
+
+";
+        assert!(help.ends_with(expected), "unexpected help dialog:\n{}", help);
+    }
+
+    #[test]
+    fn pager_fallback() {
+        let mut debug: bool = false;
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+        ;
+        let expected = parser.printer.render();
+
+        let cases = vec!(
+            (None, true),
+            (Some("".to_string()), true),
+            (Some("less -R".to_string()), false),
+            (Some("rags-no-such-pager --flag".to_string()), true),
+        );
+        for (pager, interactive) in cases {
+            let mut out: Vec<u8> = vec!();
+            parser.page_help(pager.clone(), interactive, &mut out)
+                .expect("failed to page help");
+            assert!(String::from_utf8(out).unwrap() == expected,
+                "expected direct help output for pager {:?}", pager);
+        }
+
+        parser.color(ColorMode::Auto);
+        let mut colored: Vec<u8> = vec!();
+        parser.printer.print(&mut colored, true).expect("failed to print help");
+        for (interactive, expected) in [(true, colored), (false, expected.into_bytes())] {
+            let mut out: Vec<u8> = vec!();
+            parser.page_help(None, interactive, &mut out).expect("failed to page help");
+            assert!(out == expected, "unexpected color when interactive is {}", interactive);
+        }
+    }
+
+    #[test]
+    fn help_on_empty() {
+        let mut debug: bool = false;
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]"));
+        assert!(!parser.wants_help(), "did not expect help without the toggle");
+        parser
+            .help_on_empty()
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+        ;
+        assert!(parser.wants_help(), "expected help with no args");
+        let help = parser.printer.render();
+        assert!(help.contains("-d, --debug"), "expected debug in help:\n{}", help);
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-d"));
+        parser
+            .help_on_empty()
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+        ;
+        assert!(!parser.wants_help(), "did not expect help with args given");
+        assert!(debug, "expected debug to be true");
+    }
+
+    #[test]
+    fn ansi() {
+        let mut debug: bool = false;
+        let mut file: String = "".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .descriptions_contain_ansi(true)
+            .flag('d', "debug", "\x1b[1mdebug\x1b[0m mode", &mut debug, false)
+                .expect("bad flag")
+            .arg('f', "file", "file to use", &mut file, Some("\x1b[4mFILE\x1b[0m"), false)
+                .expect("bad arg")
+            .positional("\x1b[32mpath\x1b[0m", "where to go", &mut file, false)
+                .expect("bad positional")
+        ;
+
+        let help = parser.printer.render();
+        let expected = "\
+options:
+    -d, --debug            \x1b[1mdebug\x1b[0m mode [default: false]
+    -f, --file \x1b[4mFILE\x1b[0m        file to use
+
+positionals:
+    \x1b[32mpath\x1b[0m                   where to go
+
+";
+        assert!(help.ends_with(expected), "unexpected help dialog:\n{}", help);
+    }
+
+    #[test]
+    fn option_sort() {
+        let render = |sort: OptionSort| {
+            let mut zeta: bool = false;
+            let mut alpha: bool = false;
+            let mut mid: bool = false;
+            let mut beta: bool = false;
+
+            let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+            parser
+                .option_sort(sort)
+                .long_flag("zeta", "last letter", &mut zeta, false)
+                    .expect("bad flag")
+                .flag('a', "alpha", "first letter", &mut alpha, false)
+                    .expect("bad flag")
+                .long_flag("Mid", "middle letter", &mut mid, false)
+                    .expect("bad flag")
+                .flag('b', "beta", "second letter", &mut beta, false)
+                    .expect("bad flag")
+            ;
+            let help = parser.printer.render();
+            help.lines()
+                .filter(|l| l.starts_with("    "))
+                .filter_map(|l| l.split_whitespace().find(|t| t.starts_with("--")))
+                .map(|t| t.to_string())
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(render(OptionSort::Declared),
+            string_vec!("--zeta", "--alpha", "--Mid", "--beta"));
+        assert_eq!(render(OptionSort::Alphabetical),
+            string_vec!("--alpha", "--beta", "--Mid", "--zeta"));
+        assert_eq!(render(OptionSort::ShortsFirst),
+            string_vec!("--alpha", "--beta", "--zeta", "--Mid"));
+    }
+
+    #[test]
+    fn option_sort_documents() {
+        let mut zeta: bool = false;
+        let mut alpha: bool = false;
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .app_name("rags")
+            .option_sort(OptionSort::Alphabetical)
+            .long_flag("zeta", "last letter", &mut zeta, false)
+                .expect("bad flag")
+            .flag('a', "alpha", "first letter", &mut alpha, false)
+                .expect("bad flag")
+        ;
+
+        let md = parser.help_markdown();
+        let first = md.find("| `-a, --alpha`").expect("missing alpha in markdown");
+        assert!(first < md.find("| `--zeta`").expect("missing zeta in markdown"),
+            "expected sorted markdown:\n{}", md);
+
+        let man = parser.generate_manpage(1);
+        let first = man.find(".B \\-a, \\-\\-alpha").expect("missing alpha in man page");
+        assert!(first < man.find(".B \\-\\-zeta").expect("missing zeta in man page"),
+            "expected sorted man page:\n{}", man);
+
+        let longs = parser.help_model().options.iter().map(|o| o.long).collect::<Vec<&str>>();
+        assert_eq!(longs, vec!("alpha", "zeta"));
+    }
+
+    #[test]
+    fn pager_from_env_source() {
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser.env_source(|key| {
+            if key == "PAGER" { Some("most -s".to_string()) } else { None }
+        });
+        assert_eq!(parser.env_var("PAGER"), Some("most -s".to_string()));
+        assert_eq!(parser.env_var("HOME"), None);
+        assert_eq!(parser.pager(), Some("most -s".to_string()));
+
+        parser.env_source(|_| None);
+        assert_eq!(parser.pager(), Some("less -R".to_string()));
+
+        parser.env_source(|key| {
+            if key == "PAGER" { Some("".to_string()) } else { None }
+        });
+        assert_eq!(parser.pager(), None);
+    }
+
+    #[test]
+    fn color() {
+        let render = |mode: ColorMode| {
+            let mut debug: bool = false;
+            let mut package: String = "".to_string();
+            let mut file: String = "".to_string();
+            let mut sync: String = "".to_string();
+            let mut check: String = "".to_string();
+
+            let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+            parser
+                .app_name("rags")
+                .color(mode)
+                .flag('d', "debug", "debug mode", &mut debug, false)
+                    .expect("bad flag")
+                .arg_choices('s', "sync", "sync mode", &mut sync, None, true,
+                    &["optional", "required"])
+                    .expect("bad choices")
+                .arg_default('c', "check", "check mode", &mut check, None,
+                    "not-required".to_string())
+                    .expect("bad default")
+                .group("package", "package options")
+                    .expect("bad group")
+                    .arg('p', "package", "package name", &mut package, Some("PKG"), true)
+                        .expect("bad arg")
+                    .done().expect("failed to close group")
+                .positional("file", "file to use", &mut file, true)
+                    .expect("bad positional")
+            ;
+            let mut help: Vec<u8> = vec!();
+            parser.print_help_to(&mut help).expect("could not print help");
+            (String::from_utf8(help).expect("help is not utf-8"), parser.help_string())
+        };
+
+        let (plain, _) = render(ColorMode::Never);
+        assert!(!plain.contains('\x1b'), "did not expect color:\n{}", plain);
+
+        // only a terminal is colored automatically, and a string never is
+        let (auto, _) = render(ColorMode::Auto);
+        assert_eq!(auto, plain);
+
+        let (colored, string) = render(ColorMode::Always);
+        assert_eq!(string, plain);
+        assert!(colored.contains("\x1b[1moptions:\x1b[0m\n"), "missing bold header:\n{}", colored);
+        assert!(colored.contains("\x1b[1mpositionals:\x1b[0m\n"), "missing bold header:\n{}", colored);
+        assert!(colored.contains("\x1b[1mpackage:\x1b[0m"), "missing bold group:\n{}", colored);
+        assert!(colored.contains("    \x1b[36m-d, --debug\x1b[0m"), "missing colored codes:\n{}", colored);
+        assert!(colored.contains("[\x1b[31mrequired\x1b[0m]"), "missing red required:\n{}", colored);
+        assert!(colored.contains("sync mode [choices: optional|required] [\x1b[31mrequired\x1b[0m]"),
+            "only the required marker should be red:\n{}", colored);
+        assert!(colored.contains("check mode [default: not-required]"),
+            "only the required marker should be red:\n{}", colored);
+
+        // color does not change the alignment of the dialog
+        let mut stripped = colored.clone();
+        for code in ["\x1b[1m", "\x1b[36m", "\x1b[31m", "\x1b[0m"] {
+            stripped = stripped.replace(code, "");
+        }
+        assert_eq!(stripped, plain);
+    }
+
+    #[test]
+    fn accessory_format() {
+        let mut debug: bool = false;
+        let mut package: String = "".to_string();
+        let mut level: String = "info".to_string();
+        let mut mode: String = "fast".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .accessory_format(|a| {
+                let mut parts = vec!();
+                if a.required { parts.push("required".to_string()); }
+                if let Some(d) = a.default { parts.push(format!("default {}", d)); }
+                if let Some(c) = a.choices { parts.push(format!("one of {}", c.join(", "))); }
+                if parts.is_empty() { "".to_string() } else { format!(" <{}>", parts.join("; ")) }
+            })
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .arg('p', "package", "package name", &mut package, None, true)
+                .expect("bad package arg")
+            .arg('l', "level", "log level", &mut level, None, false)
+                .expect("bad level arg")
+            .positional_choices("mode", "build mode", &mut mode, true, &["fast", "small"])
+                .expect("bad positional")
+        ;
+
+        let help = parser.help_string();
+        assert!(help.contains("debug mode <default false>\n"), "unexpected flag:\n{}", help);
+        assert!(help.contains("package name <required>\n"), "unexpected arg:\n{}", help);
+        assert!(help.contains("log level <default info>\n"), "unexpected arg:\n{}", help);
+        assert!(help.contains("build mode <required; default fast; one of fast, small>\n"),
+            "unexpected positional:\n{}", help);
+        assert!(!help.contains("[required") && !help.contains("[default"),
+            "did not expect the default format:\n{}", help);
+    }
+
+    #[test]
+    fn required_marker() {
+        let mut debug: bool = false;
+        let mut package: String = "".to_string();
+        let mut target: String = "".to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .required_marker(RequiredMarker::TrailingBracket)
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .arg('p', "package", "package name", &mut package, None, true)
+                .expect("bad package arg")
+            .positional("target", "what to build", &mut target, true)
+                .expect("bad positional")
+        ;
+        let help = parser.help_string();
+        assert!(help.contains("\n    -p, --package        package name [required]\n"),
+            "unexpected arg:\n{}", help);
+
+        let mut debug: bool = false;
+        let mut package: String = "".to_string();
+        let mut target: String = "".to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .required_marker(RequiredMarker::LeadingAsterisk)
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .arg('p', "package", "package name", &mut package, None, true)
+                .expect("bad package arg")
+            .positional("target", "what to build", &mut target, true)
+                .expect("bad positional")
+        ;
+        let help = parser.help_string();
+        assert!(help.contains("\n  * -p, --package        package name\n"),
+            "unexpected arg:\n{}", help);
+        assert!(help.contains("\n  * target               what to build\n"),
+            "unexpected positional:\n{}", help);
+        assert!(help.contains("\n    -d, --debug          debug mode [default: false]\n"),
+            "unexpected flag:\n{}", help);
+        assert!(!help.contains("[required"), "did not expect a trailing marker:\n{}", help);
+
+        let mut debug: bool = false;
+        let mut package: String = "".to_string();
+        let mut target: String = "".to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .required_marker(RequiredMarker::None)
+            .flag('d', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .arg('p', "package", "package name", &mut package, None, true)
+                .expect("bad package arg")
+            .positional("target", "what to build", &mut target, true)
+                .expect("bad positional")
+        ;
+        let help = parser.help_string();
+        assert!(!help.contains("[required") && !help.contains('*'), "unexpected marker:\n{}", help);
+    }
+
+    #[test]
+    fn help_renderer() {
+        let mut verbose: bool = true;
+        let mut package: String = "".to_string();
+        let mut subs: Vec<String> = vec!();
+        let mut target: String = "".to_string();
+
+        let rendered: std::rc::Rc<std::cell::RefCell<Option<HelpModel>>> = Default::default();
+        let into = rendered.clone();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "build", "--help"));
+        parser
+            .app_name("rags")
+            .app_version("1.0")
+            .set_help_renderer(move |model| { *into.borrow_mut() = Some(model.clone()); })
+            .linked_flags(('v', "verbose"), ('q', "quiet"), "toggle verbose output", &mut verbose)
+                .expect("bad linked flags")
+            .subcommand("build", "do a build", &mut subs, None)
+                .expect("bad sub(build)")
+                .group("package", "package selection")
+                    .expect("bad group")
+                    .arg('p', "package", "package to build", &mut package, Some("PKG"), true)
+                        .expect("bad package arg")
+                    .done().expect("no done on group")
+                .positional("target", "what to build", &mut target, false)
+                    .expect("bad positional")
+                .done().expect("no done on build")
+        ;
+
+        parser.print_help();
+        let model = rendered.borrow_mut().take().expect("renderer was not invoked");
+        assert_eq!(model, parser.help_model());
+        assert_eq!(model.name, "rags build");
+        assert_eq!(model.version, "1.0");
+        assert_eq!(model.desc, "do a build");
+        assert_eq!(model.usage.as_deref(), Some("rags build [-vq] -p PKG [target]"));
+        assert!(model.subcommands.is_empty(), "unexpected subcommands: {:?}", model.subcommands);
+
+        assert_eq!(model.options.len(), 1, "unexpected options: {:?}", model.options);
+        assert_eq!((model.options[0].short, model.options[0].long), ('v', "verbose"));
+        assert_eq!(model.options[0].linked, Some(('q', "quiet")));
+
+        assert_eq!(model.groups.len(), 1, "unexpected groups: {:?}", model.groups);
+        assert_eq!(model.groups[0].name, "package");
+        assert_eq!(model.groups[0].options[0].label, Some("PKG"));
+        assert!(model.groups[0].options[0].required, "expected package to be required");
+
+        assert_eq!(model.positionals.len(), 1, "unexpected positionals: {:?}", model.positionals);
+        assert_eq!(model.positionals[0].name, "target");
+        assert!(!model.positionals[0].required, "did not expect target to be required");
+    }
+}
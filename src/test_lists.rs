@@ -80,14 +80,201 @@ mod lists {
         ;
 
         match result {
-            Ok(_) => { assert!(false, "expected error about args being at end of runs"); }
+            Ok(_) => { panic!("expected error about args being at end of runs"); }
             Err(e) => { match e {
                 Error::ValuedArgInRun(_, _) => { /* did what we expect */ }
                 _ => {
-                    assert!(false, "unexpected error: {:?}", e);
+                    panic!("unexpected error: {:?}", e);
                 }
             }}
         }
     }
-}
 
+    #[test]
+    fn empty_string() {
+        let mut libs: Vec<String> = vec!();
+
+        let args = string_vec!("argv[0]", "-l", "", "--lib", "b", "--lib=");
+        let mut parser = Parser::from_strings(args);
+        parser
+            .list('l', "lib", "libs", &mut libs, None, false)
+                .expect("bad list")
+        ;
+
+        assert_eq!(libs, string_vec!("", "b", ""));
+        assert!(parser.unused().is_empty(), "expected no unused args");
+    }
+
+    #[test]
+    fn ordered_kv() {
+        let mut headers: Vec<(String, String)> = vec!();
+
+        let args = string_vec!("argv[0]", "-H", "a:1", "-H", "b:2", "--header=a:3", "-H", "c:d:4");
+        let mut parser = Parser::from_strings(args);
+        parser
+            .ordered_kv_arg('H', "header", "headers to send", &mut headers, ':', None, false)
+                .expect("bad ordered kv arg")
+        ;
+
+        let expected = vec!(("a", "1"), ("b", "2"), ("a", "3"), ("c", "d:4")).into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<Vec<(String, String)>>();
+        assert_eq!(headers, expected);
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-H", "novalue"));
+        match parser.ordered_kv_arg('H', "header", "headers", &mut headers, ':', None, false) {
+            Err(Error::ConstructionError(_, _, msg)) => {
+                assert!(msg.contains("novalue"), "expected value in message: {}", msg);
+            }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected construction error"); }
+        }
+    }
+
+    #[test]
+    fn dotted_set() {
+        let mut settings: BTreeMap<String, String> = BTreeMap::new();
+
+        let args = string_vec!("argv[0]",
+            "--set", "server.port=8080", "-s", "server.host=x", "--set=log=a=b", "-s", "server.port=9090"
+        );
+        let mut parser = Parser::from_strings(args);
+        parser
+            .dotted_set_arg('s', "set", "settings to override", &mut settings, Some("KEY=VALUE"), false)
+                .expect("bad dotted set arg")
+        ;
+
+        let expected = vec!(("log", "a=b"), ("server.host", "x"), ("server.port", "9090")).into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<BTreeMap<String, String>>();
+        assert_eq!(settings, expected);
+
+        for bad in &["novalue", "=value"] {
+            let mut parser = Parser::from_strings(string_vec!("argv[0]", "--set", bad));
+            match parser.dotted_set_arg('s', "set", "settings", &mut settings, None, false) {
+                Err(Error::ConstructionError(_, _, msg)) => {
+                    assert!(msg.contains(bad), "expected value in message: {}", msg);
+                }
+                Err(e) => { panic!("unexpected error: {:?}", e); }
+                Ok(_) => { panic!("expected construction error for {}", bad); }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn expand_dotted() {
+        let pairs = vec!(("server.port", "8080"), ("server.host", "x"), ("server", "y"), ("log", "debug"))
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<BTreeMap<String, String>>();
+
+        let expected = serde_json::json!({
+            "log": "debug",
+            "server": { "host": "x", "port": "8080" },
+        });
+        assert_eq!(crate::expand_dotted(&pairs), expected);
+    }
+
+    #[test]
+    fn defaults() {
+        let default = || string_vec!("a", "b");
+
+        let mut libs: Vec<String> = string_vec!("initial");
+        Parser::from_strings(string_vec!("argv[0]", "-l", "x", "--lib=y"))
+            .list_default('l', "lib", "libs", &mut libs, None, default())
+                .expect("bad list")
+        ;
+        assert_eq!(libs, string_vec!("x", "y"), "expected given values to override");
+
+        let mut libs: Vec<String> = string_vec!("initial");
+        Parser::from_strings(string_vec!("argv[0]"))
+            .list_default('l', "lib", "libs", &mut libs, None, default())
+                .expect("bad list")
+        ;
+        assert_eq!(libs, default(), "expected defaults to be applied");
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .list_default('l', "lib", "libs to link", &mut libs, None, default())
+                .expect("bad list")
+        ;
+        let help = parser.printer.render();
+        assert!(help.contains("libs to link [default: a,b]\n"), "unexpected help:\n{}", help);
+    }
+
+    #[test]
+    fn validated() {
+        let in_range = |p: &u32| {
+            if (1..=65535).contains(p) { Ok(()) } else { Err("must be 1-65535".to_string()) }
+        };
+
+        let mut ports: Vec<u32> = vec!();
+        Parser::from_strings(string_vec!("argv[0]", "-p", "80", "--port=443"))
+            .list_validated('p', "port", "ports to bind", &mut ports, None, false, in_range)
+                .expect("bad validated list")
+        ;
+        assert_eq!(ports, vec!(80, 443));
+
+        let mut ports: Vec<u32> = vec!();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-p", "80", "-p", "70000"));
+        match parser.list_validated('p', "port", "ports to bind", &mut ports, None, false, in_range) {
+            Err(Error::ValidationFailed(short, long, value, reason)) => {
+                assert_eq!((short, long), ('p', "port"));
+                assert_eq!(value, "70000");
+                assert_eq!(reason, "must be 1-65535");
+            }
+            Err(e) => { panic!("unexpected error: {}", e); }
+            Ok(_) => { panic!("expected validation error"); }
+        }
+        assert!(ports.is_empty(), "did not expect values to be appended: {:?}", ports);
+    }
+
+    #[test]
+    fn multi() {
+        let mut tags: Vec<String> = vec!();
+        let mut file: String = "".to_string();
+        let mut rest: Vec<String> = vec!();
+
+        let args = string_vec!("argv[0]", "--tags", "foo", "bar", "-f", "x.txt", "-t=baz", "qux",
+            "--", "not-a-tag");
+        let mut parser = Parser::from_strings(args);
+        parser
+            .arg('f', "file", "a file", &mut file, None, false)
+                .expect("bad arg")
+            .multi_list('t', "tags", "tags to apply", &mut tags, None, false)
+                .expect("bad multi list")
+            .positional_list("rest", "the rest", &mut rest, false)
+                .expect("bad positional list")
+        ;
+        assert_eq!(tags, string_vec!("foo", "bar", "baz", "qux"));
+        assert_eq!(file, "x.txt");
+        assert_eq!(rest, string_vec!("not-a-tag"));
+    }
+
+    #[test]
+    fn delimited() {
+        let mut libs: Vec<String> = vec!();
+        let mut ports: Vec<u16> = vec!();
+
+        let args = string_vec!("argv[0]", "-l", "a,b", "--lib=c,,d,", "-l", "e", "--port", "80,443");
+        Parser::from_strings(args)
+            .delimited_list('l', "lib", "libs to link", &mut libs, None, false, ',')
+                .expect("bad delimited list")
+            .delimited_list('p', "port", "ports to open", &mut ports, None, false, ',')
+                .expect("bad delimited list")
+        ;
+        assert_eq!(libs, string_vec!("a", "b", "c", "d", "e"));
+        assert_eq!(ports, vec!(80, 443));
+
+        let mut ports: Vec<u16> = vec!();
+        let res = Parser::from_strings(string_vec!("argv[0]", "--port=80,http"))
+            .delimited_list('p', "port", "ports to open", &mut ports, None, false, ',')
+            .map(|_| ());
+        match res {
+            Err(Error::ConstructionError('p', "port", _)) => {}
+            Err(e) => { panic!("unexpected error: {}", e); }
+            Ok(_) => { panic!("expected a construction error for http"); }
+        }
+    }
+}
@@ -0,0 +1,291 @@
+#[cfg(test)]
+mod matched {
+    use crate::*;
+
+    #[test]
+    fn all_kinds() {
+        let mut debug: bool = false;
+        let mut verbosity: usize = 0;
+        let mut file: String = "".to_string();
+        let mut libs: Vec<String> = vec!();
+        let mut short_only: String = "".to_string();
+
+        let args = string_vec!("argv[0]",
+            "-D", "-vvv", "--file", "foo.txt", "-l", "a", "--lib=b", "-s", "short"
+        );
+        let mut parser = Parser::from_strings(args);
+        parser
+            .flag('D', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .count('v', "verbose", "verbosity", &mut verbosity, 1)
+                .expect("bad count")
+            .arg('f', "file", "a file", &mut file, None, false)
+                .expect("bad arg")
+            .list('l', "lib", "libs", &mut libs, None, false)
+                .expect("bad list")
+            .short_arg('s', "short only", &mut short_only, None, false)
+                .expect("bad short arg")
+        ;
+
+        let matched = parser.matched_options();
+        assert_eq!(matched.len(), 5, "unexpected matched options: {:?}", matched);
+        assert_eq!(matched["debug"], Vec::<String>::new());
+        assert_eq!(matched["verbose"], string_vec!("3"));
+        assert_eq!(matched["file"], string_vec!("foo.txt"));
+        assert_eq!(matched["lib"], string_vec!("a", "b"));
+        assert_eq!(matched["s"], string_vec!("short"));
+    }
+
+    #[test]
+    fn values_of() {
+        let mut libs: Vec<String> = vec!();
+        let mut file: String = "".to_string();
+
+        let args = string_vec!("argv[0]", "-l", "a", "--lib=b", "-l", "c");
+        let mut parser = Parser::from_strings(args);
+        parser
+            .list('l', "lib", "libs", &mut libs, None, false)
+                .expect("bad list")
+            .arg('f', "file", "a file", &mut file, None, false)
+                .expect("bad arg")
+        ;
+
+        assert_eq!(parser.values_of('l', "lib"), vec!("a", "b", "c"));
+        assert!(parser.values_of('f', "file").is_empty(), "expected no values for file");
+
+        // looked up by long name regardless of the short code assigned
+        let mut tags: Vec<String> = vec!();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-t", "x", "--tag=y"));
+        parser
+            .auto_short_codes()
+            .list('\0', "tag", "tags", &mut tags, None, false)
+                .expect("bad list")
+        ;
+        assert_eq!(tags, string_vec!("x", "y"));
+        assert_eq!(parser.values_of('\0', "tag"), vec!("x", "y"));
+        assert_eq!(parser.values_of('t', "tag"), vec!("x", "y"));
+
+        // an option declared in two scopes with different short codes is one record
+        let mut subs: Vec<String> = vec!();
+        let mut tags: Vec<String> = vec!();
+        let mut sub_tags: Vec<String> = vec!();
+        let args = string_vec!("argv[0]", "-t", "x", "build", "-g", "y");
+        let mut parser = Parser::from_strings(args);
+        parser
+            .list('t', "tag", "tags", &mut tags, None, false)
+                .expect("bad list")
+            .subcommand("build", "do a build", &mut subs, None)
+                .expect("bad subcommand")
+                .list('g', "tag", "tags", &mut sub_tags, None, false)
+                    .expect("bad list")
+                .done().expect("failed to close subcommand")
+        ;
+        assert_eq!(parser.values_of('t', "tag"), vec!("x", "y"));
+        assert_eq!(parser.matched_options().get("tag"), Some(&string_vec!("x", "y")));
+    }
+
+    #[test]
+    fn unmatched_absent() {
+        let mut debug: bool = false;
+        let mut file: String = "".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--debug"));
+        parser
+            .flag('D', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .arg('f', "file", "a file", &mut file, None, false)
+                .expect("bad arg")
+        ;
+
+        let matched = parser.matched_options();
+        assert!(matched.contains_key("debug"), "expected debug to be matched");
+        assert!(!matched.contains_key("file"), "did not expect file to be matched");
+    }
+
+    #[test]
+    fn outcome() {
+        let mut subs: Vec<String> = vec!();
+        let mut verbosity: usize = 0;
+        let mut file: String = "".to_string();
+        let mut debug: bool = false;
+
+        let args = string_vec!("argv[0]", "build", "-vv", "--file", "foo.txt", "--bogus", "extra");
+        let mut parser = Parser::from_strings(args);
+        parser
+            .subcommand("build", "do a build", &mut subs, None)
+                .expect("bad sub(build)")
+                .count('v', "verbose", "verbosity", &mut verbosity, 1)
+                    .expect("bad count")
+                .arg('f', "file", "a file", &mut file, None, false)
+                    .expect("bad arg")
+                .done().expect("no done on build")
+            .subcommand("test", "test a target", &mut subs, None)
+                .expect("bad sub(test)")
+                .flag('D', "debug", "debug mode", &mut debug, false)
+                    .expect("bad flag")
+                .done().expect("no done on test")
+        ;
+
+        let mut matched = BTreeMap::new();
+        matched.insert("verbose".to_string(), string_vec!("2"));
+        matched.insert("file".to_string(), string_vec!("foo.txt"));
+
+        assert_eq!(parser.outcome(), ParseOutcome{
+            matched,
+            subcommands: string_vec!("build"),
+            unused: string_vec!("--bogus", "extra"),
+            help: false,
+            version: false,
+        });
+
+        let mut debug: bool = false;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--version"));
+        parser
+            .flag('D', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+        ;
+        let outcome = parser.outcome();
+        assert!(outcome.version, "expected the version to be requested");
+        assert!(!outcome.help, "did not expect help to be requested");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serialize_outcome() {
+        let mut debug: bool = false;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-D", "extra"));
+        parser
+            .flag('D', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+        ;
+
+        let json = serde_json::to_value(parser.outcome()).expect("failed to serialize");
+        assert_eq!(json, serde_json::json!({
+            "matched": { "debug": [] },
+            "subcommands": [],
+            "unused": ["extra"],
+            "help": false,
+            "version": false,
+        }));
+    }
+
+    #[test]
+    fn diff() {
+        let home = std::env::temp_dir();
+        let name = format!("rags-diff-{}.conf", std::process::id());
+        std::fs::write(home.join(&name), "level=debug\nthreads=4\n").expect("could not write config");
+
+        let mut level: String = "info".to_string();
+        let mut threads: usize = 1;
+        let mut debug: bool = false;
+        let config = home.join(&name).to_string_lossy().to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]"));
+        parser.env_source(move |key| match key {
+            "MYAPP_CONFIG" => { Some(config.clone()) }
+            _ => { None }
+        });
+        parser
+            .config_from_env("MYAPP_CONFIG", ConfigFormat::KeyValue)
+                .expect("bad config")
+            .arg('l', "level", "log level", &mut level, None, false)
+                .expect("bad level arg")
+            .arg('t', "threads", "thread count", &mut threads, None, false)
+                .expect("bad threads arg")
+            .flag('D', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+        ;
+        let config_only = parser.outcome();
+
+        let mut level: String = "info".to_string();
+        let mut threads: usize = 1;
+        let mut debug: bool = false;
+        let config = home.join(&name).to_string_lossy().to_string();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--level", "warn", "-D"));
+        parser.env_source(move |key| match key {
+            "MYAPP_CONFIG" => { Some(config.clone()) }
+            _ => { None }
+        });
+        parser
+            .config_from_env("MYAPP_CONFIG", ConfigFormat::KeyValue)
+                .expect("bad config")
+            .arg('l', "level", "log level", &mut level, None, false)
+                .expect("bad level arg")
+            .arg('t', "threads", "thread count", &mut threads, None, false)
+                .expect("bad threads arg")
+            .flag('D', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+        ;
+        let with_cli = parser.outcome();
+        std::fs::remove_file(home.join(&name)).expect("could not remove config");
+
+        assert_eq!(config_only.diff(&with_cli), vec!(
+            ("debug".to_string(), None, Some("".to_string())),
+            ("level".to_string(), Some("debug".to_string()), Some("warn".to_string())),
+        ));
+        assert!(with_cli.diff(&with_cli).is_empty(), "expected no diff with itself");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn deserialize() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        #[serde(rename_all = "kebab-case")]
+        struct Options {
+            debug: bool,
+            #[serde(default)]
+            dry_run: bool,
+            verbose: u8,
+            port: Option<u16>,
+            lib: Vec<String>,
+            #[serde(default)]
+            file: String,
+        }
+
+        let mut debug: bool = false;
+        let mut dry_run: bool = false;
+        let mut verbosity: usize = 0;
+        let mut port: u16 = 0;
+        let mut libs: Vec<String> = vec!();
+        let mut file: String = "".to_string();
+
+        let args = string_vec!("argv[0]", "-D", "-vv", "--port", "8080", "-l", "a");
+        let mut parser = Parser::from_strings(args);
+        parser
+            .flag('D', "debug", "debug mode", &mut debug, false)
+                .expect("bad flag")
+            .flag('n', "dry-run", "do nothing", &mut dry_run, false)
+                .expect("bad flag")
+            .count('v', "verbose", "verbosity", &mut verbosity, 1)
+                .expect("bad count")
+            .arg('p', "port", "port", &mut port, None, false)
+                .expect("bad arg")
+            .list('l', "lib", "libs", &mut libs, None, false)
+                .expect("bad list")
+            .arg('f', "file", "a file", &mut file, None, false)
+                .expect("bad arg")
+        ;
+
+        let opts: Options = parser.deserialize().expect("failed to deserialize");
+        assert_eq!(opts, Options{
+            debug: true,
+            dry_run: false,
+            verbose: 2,
+            port: Some(8080),
+            lib: string_vec!("a"),
+            file: "".to_string(),
+        });
+
+        #[derive(serde::Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Strict {
+            port: u8,
+        }
+        match parser.deserialize::<Strict>() {
+            Err(Error::Deserialize(reason)) => {
+                assert!(reason.contains("8080"), "unexpected reason: {}", reason);
+            }
+            Err(e) => { panic!("unexpected error: {}", e); }
+            Ok(_) => { panic!("expected 8080 to overflow a u8"); }
+        }
+    }
+}
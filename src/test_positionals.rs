@@ -63,13 +63,13 @@ mod named {
 
         match &result {
             Ok(_) => {
-                assert!(false, "did not receive missing positional error");
+                panic!("did not receive missing positional error");
             }
             Err(e) => {
                 match e {
                     Error::MissingPositional(_) => {}
                     _ => {
-                        assert!(false, "got wrong error: {}", e);
+                        panic!("got wrong error: {}", e);
                     }
                 }
             }
@@ -122,11 +122,11 @@ mod lists {
         ;
 
         match result {
-            Ok(_) => { assert!(false, "did not receive errror"); }
+            Ok(_) => { panic!("did not receive errror"); }
             Err(e) => match e {
                 Error::MultipleVariadic(_) => {}
                 _ => {
-                    assert!(false, "received incorrect error: {}", e);
+                    panic!("received incorrect error: {}", e);
                 }
             }
         }
@@ -134,6 +134,29 @@ mod lists {
     }
 
 
+    #[test]
+    fn trailing_after_named() {
+        let mut first: String = "".to_string();
+        let mut second: String = "".to_string();
+        let mut rest: Vec<String> = vec!();
+
+        let argv = string_vec!("argv[0]", "a", "b", "c", "d", "e");
+        let mut parser = Parser::from_strings(argv);
+        parser
+            .positional("first", "", &mut first, true)
+                .expect("could not create first positional")
+            .positional("second", "", &mut second, true)
+                .expect("could not create second positional")
+            .positional_list("rest", "", &mut rest, false)
+                .expect("could not create positional list")
+        ;
+
+        assert_eq!(first, "a", "incorrect first positional");
+        assert_eq!(second, "b", "incorrect second positional");
+        assert_eq!(rest, string_vec!("c", "d", "e"), "incorrect trailing positionals");
+        assert!(parser.unused().is_empty(), "expected all positionals to be claimed");
+    }
+
     #[test]
     fn collects_after_argstop() {
         let mut flags: bool = false;
@@ -164,5 +187,270 @@ mod lists {
         assert_eq!(post_stop[0], "--long", "incorrect first post-stop arg");
         assert_eq!(post_stop[1], "foo", "incorrect second post-stop arg");
     }
-}
 
+    #[test]
+    fn skip_leading() {
+        let mut count: usize = 0;
+        let mut file: String = "".to_string();
+        let mut rest: Vec<String> = vec!();
+
+        // the leading tokens, including their arg-stop, were consumed by a dispatcher
+        let argv = string_vec!("argv[0]", "tool", "--", "-v", "in.txt", "--", "-v");
+        let mut parser = Parser::from_strings(crate::skip_leading(argv, 2));
+        parser
+            .count('v', "verbose", "increase verbosity", &mut count, 1)
+                .expect("bad count")
+            .positional("file", "input file", &mut file, true)
+                .expect("bad positional")
+            .positional_list("rest", "trailing args", &mut rest, false)
+                .expect("bad positional list")
+        ;
+
+        assert_eq!(count, 1, "expected only the -v before the arg-stop to count");
+        assert_eq!(file, "in.txt", "incorrect positional");
+        assert_eq!(rest, string_vec!("-v"), "incorrect post-argstop args");
+
+        assert_eq!(crate::skip_leading(string_vec!("argv[0]", "a"), 5), string_vec!("argv[0]"));
+        assert_eq!(crate::skip_leading(string_vec!("argv[0]", "a"), 0), string_vec!("argv[0]", "a"));
+    }
+
+    #[test]
+    fn custom_argstop() {
+        let mut count: usize = 0;
+        let mut files: Vec<String> = vec!();
+
+        let argv = string_vec!("argv[0]", "-v", "--", "file", "::", "-v", "--help");
+        let mut parser = Parser::from_strings(argv);
+        parser
+            .arg_stop_token("::")
+                .expect("bad arg-stop")
+            .count('v', "verbose", "increase verbosity", &mut count, 1)
+                .expect("bad count parse")
+            .positional_list("file", "", &mut files, false)
+                .expect("could not create positional list")
+        ;
+
+        assert!(!parser.wants_help(), "parsed help after the custom arg-stop");
+        assert_eq!(count, 1, "parsed count after the custom arg-stop");
+        assert_eq!(files, string_vec!("--", "file", "-v", "--help"), "incorrect positionals");
+    }
+
+    #[test]
+    fn empty_string() {
+        let mut first: String = "unset".to_string();
+        let mut second: String = "unset".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "", "second"));
+        parser
+            .positional("first", "first positional", &mut first, true)
+                .expect("bad first positional")
+            .positional("second", "second positional", &mut second, true)
+                .expect("bad second positional")
+        ;
+
+        assert!(first.is_empty(), "expected empty first positional, got '{}'", first);
+        assert!(second == "second", "expected second positional, got '{}'", second);
+        assert!(parser.unused().is_empty(), "expected no unused args");
+    }
+
+    #[test]
+    fn choices() {
+        let mut mode: String = "".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "stop"));
+        parser
+            .positional_choices("mode", "what to do", &mut mode, true,
+                &["start", "stop", "status"])
+                .expect("bad positional")
+        ;
+        assert!(mode == "stop", "expected mode to be 'stop' but got '{}'", mode);
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "restart"));
+        let result = parser
+            .positional_choices("mode", "what to do", &mut mode, true,
+                &["start", "stop", "status"])
+        ;
+        match result {
+            Err(Error::InvalidPositionalChoice(name, given, _)) => {
+                assert!(name == "mode", "unexpected positional in error: {}", name);
+                assert!(given == "restart", "unexpected value in error: {}", given);
+            }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected invalid choice error"); }
+        }
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--help"));
+        parser
+            .positional_choices("mode", "what to do", &mut mode, true,
+                &["start", "stop", "status"])
+                .expect("bad positional")
+        ;
+        let help = parser.printer.render();
+        assert!(help.contains("what to do [choices: start|stop|status] [required"),
+            "expected choices in help:\n{}", help);
+    }
+
+    #[test]
+    fn file() {
+        let manifest = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+        let missing = concat!(env!("CARGO_MANIFEST_DIR"), "/no-such-file.toml");
+        let mut path = std::path::PathBuf::new();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", manifest));
+        parser
+            .file_positional("manifest", "manifest to read", &mut path, true, true)
+                .expect("bad file positional")
+        ;
+        assert_eq!(path, std::path::PathBuf::from(manifest));
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", missing));
+        match parser.file_positional("manifest", "manifest to read", &mut path, true, true) {
+            Err(Error::PathNotFound(p)) => { assert_eq!(p, missing); }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected path not found error"); }
+        }
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", missing));
+        parser
+            .file_positional("output", "file to create", &mut path, true, false)
+                .expect("bad file positional")
+        ;
+        assert_eq!(path, std::path::PathBuf::from(missing));
+    }
+
+    #[test]
+    fn lone_dash() {
+        let mut input: String = "".to_string();
+        let mut rest: Vec<String> = vec!();
+        let mut file: String = "".to_string();
+
+        let args = string_vec!("argv[0]", "-f", "-", "-", "a", "-");
+        let mut parser = Parser::from_strings(args);
+        parser
+            .strict_values()
+            .arg('f', "file", "file to read", &mut file, None, false)
+                .expect("bad arg")
+            .positional("input", "input to read, - for stdin", &mut input, true)
+                .expect("bad positional")
+            .positional_list("rest", "more inputs", &mut rest, false)
+                .expect("bad positional list")
+        ;
+
+        assert!(file == "-", "expected file to be '-' but got '{}'", file);
+        assert!(input == "-", "expected input to be '-' but got '{}'", input);
+        assert_eq!(rest, string_vec!("a", "-"));
+    }
+
+    #[test]
+    fn posix_strict() {
+        let mut verbose: bool = false;
+        let mut extra: bool = false;
+        let mut output: String = "".to_string();
+        let mut files: Vec<String> = vec!();
+        Parser::from_strings(string_vec!("argv[0]", "-v", "-o", "out", "input", "-x", "file2"))
+            .flag('v', "verbose", "verbose output", &mut verbose, false)
+                .expect("bad verbose flag")
+            .flag('x', "extra", "extra output", &mut extra, false)
+                .expect("bad extra flag")
+            .arg('o', "output", "output file", &mut output, None, false)
+                .expect("bad output arg")
+            .positional_list("files", "files to read", &mut files, false)
+                .expect("bad positional list")
+        ;
+        assert!(verbose && extra, "expected both flags in permuting mode");
+        assert_eq!(output, "out");
+        assert_eq!(files, string_vec!("input", "file2"));
+
+        let mut verbose: bool = false;
+        let mut extra: bool = false;
+        let mut output: String = "".to_string();
+        let mut files: Vec<String> = vec!();
+        Parser::from_strings(string_vec!("argv[0]", "-v", "-o", "out", "input", "-x", "file2"))
+            .posix_strict()
+            .flag('v', "verbose", "verbose output", &mut verbose, false)
+                .expect("bad verbose flag")
+            .flag('x', "extra", "extra output", &mut extra, false)
+                .expect("bad extra flag")
+            .arg('o', "output", "output file", &mut output, None, false)
+                .expect("bad output arg")
+            .positional_list("files", "files to read", &mut files, false)
+                .expect("bad positional list")
+        ;
+        assert!(verbose, "expected -v before the first positional");
+        assert!(!extra, "did not expect -x after the first positional");
+        assert_eq!(output, "out");
+        assert_eq!(files, string_vec!("input", "-x", "file2"));
+
+        let mut verbose: bool = false;
+        let mut extra: bool = false;
+        let mut subs: Vec<String> = vec!();
+        let mut srcs: Vec<String> = vec!();
+        Parser::from_strings(string_vec!("argv[0]", "-v", "build", "-x", "src", "-v"))
+            .posix_strict()
+            .flag('v', "verbose", "verbose output", &mut verbose, false)
+                .expect("bad verbose flag")
+            .subcommand("build", "do a build", &mut subs, None)
+                .expect("bad sub(build)")
+                .flag('x', "extra", "extra output", &mut extra, false)
+                    .expect("bad extra flag")
+                .positional_list("srcs", "sources", &mut srcs, false)
+                    .expect("bad positional list")
+                .done().expect("no done on build")
+        ;
+        assert!(verbose, "expected -v before the subcommand");
+        assert!(extra, "expected -x to match after the subcommand");
+        assert_eq!(srcs, string_vec!("src", "-v"));
+    }
+
+    #[test]
+    fn negative_numbers() {
+        let mut verbosity: usize = 0;
+        let mut offset: i32 = 0;
+        let mut x: f64 = 0.0;
+        let mut y: f64 = 0.0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-5", "-v", "--offset", "-3", "-2.5"));
+        parser
+            .allow_negative_numbers(true)
+            .strict_values()
+            .count('v', "verbose", "verbosity", &mut verbosity, 1)
+                .expect("bad count")
+            .arg('o', "offset", "offset", &mut offset, None, false)
+                .expect("bad offset arg")
+            .positional("x", "x coordinate", &mut x, true)
+                .expect("bad x positional")
+            .positional("y", "y coordinate", &mut y, true)
+                .expect("bad y positional")
+        ;
+        assert_eq!((verbosity, offset, x, y), (1, -3, -5.0, -2.5));
+        assert!(parser.unused().is_empty(), "expected no unused args");
+
+        let mut verbosity: usize = 0;
+        let mut offset: i32 = 0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-5", "-v", "--offset", "-3", "-2.5"));
+        let result = parser
+            .allow_negative_numbers(false)
+            .strict_values()
+            .count('v', "verbose", "verbosity", &mut verbosity, 1)
+                .expect("bad count")
+            .arg('o', "offset", "offset", &mut offset, None, false)
+        ;
+        match result {
+            Err(Error::MissingArgValue(_, "offset")) => {}
+            Err(e) => { panic!("unexpected error: {}", e); }
+            Ok(_) => { panic!("expected -3 to look like an option"); }
+        }
+
+        let mut five: bool = false;
+        let mut x: f64 = 0.0;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-5", "-2.5"));
+        parser
+            .allow_negative_numbers(true)
+            .flag('5', "five", "a numeric short code", &mut five, false)
+                .expect("bad flag")
+            .positional("x", "x coordinate", &mut x, true)
+                .expect("bad positional")
+        ;
+        assert!(five, "expected -5 to match the declared short code");
+        assert_eq!(x, -2.5);
+    }
+}
@@ -51,4 +51,270 @@ mod subcmds {
         assert!(build_file == "hahaha.txt", "did not set build-file: {}", build_file);
         assert!(test_file == "test", "overwrote test-file: {}", test_file);
     }
+
+    #[test]
+    fn required() {
+        let mut subs: Vec<String> = vec!();
+
+        let result = Parser::from_strings(string_vec!("argv[0]", "-v"))
+            .subcommand("build", "do a build", &mut subs, None).expect("bad sub(build)")
+                .subcommand("all", "build everything", &mut subs, None)
+                    .expect("bad sub-sub(all)")
+                    .done().expect("no done on build-all")
+                .done().expect("no done on build")
+            .subcommand("test", "test a target", &mut subs, None).expect("bad sub(test)")
+                .done().expect("no done on test")
+            .require_subcommand()
+            .map(|_| ())
+        ;
+
+        match result {
+            Err(Error::MissingSubcommand(names)) => {
+                assert_eq!(names, vec!("build", "test"));
+            }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected missing subcommand error"); }
+        }
+
+        Parser::from_strings(string_vec!("argv[0]", "test"))
+            .subcommand("build", "do a build", &mut subs, None).expect("bad sub(build)")
+                .done().expect("no done on build")
+            .subcommand("test", "test a target", &mut subs, None).expect("bad sub(test)")
+                .done().expect("no done on test")
+            .require_subcommand().expect("subcommand was given")
+        ;
+    }
+
+    #[test]
+    fn required_with_help() {
+        let mut subs: Vec<String> = vec!();
+
+        Parser::from_strings(string_vec!("argv[0]", "--help"))
+            .subcommand("build", "do a build", &mut subs, None).expect("bad sub(build)")
+                .done().expect("no done on build")
+            .require_subcommand().expect("should not require subcommand with help")
+        ;
+    }
+
+    #[test]
+    fn unmatched() {
+        let mut subs: Vec<String> = vec!();
+        let mut verbose: bool = false;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-v", "--bogus", "biuld"));
+        parser
+            .flag('v', "verbose", "be verbose", &mut verbose, false)
+                .expect("bad flag")
+            .subcommand("build", "do a build", &mut subs, None).expect("bad sub(build)")
+                .subcommand("all", "build everything", &mut subs, None)
+                    .expect("bad sub-sub(all)")
+                    .done().expect("no done on build-all")
+                .done().expect("no done on build")
+            .subcommand("test", "test a target", &mut subs, None).expect("bad sub(test)")
+                .done().expect("no done on test")
+        ;
+        assert_eq!(parser.unmatched_subcommand(), Some("biuld"));
+
+        let mut subs: Vec<String> = vec!();
+        let mut verbose: bool = false;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "build", "everything"));
+        parser
+            .flag('v', "verbose", "be verbose", &mut verbose, false)
+                .expect("bad flag")
+            .subcommand("build", "do a build", &mut subs, None).expect("bad sub(build)")
+                .subcommand("all", "build everything", &mut subs, None)
+                    .expect("bad sub-sub(all)")
+                    .done().expect("no done on build-all")
+                .done().expect("no done on build")
+            .subcommand("test", "test a target", &mut subs, None).expect("bad sub(test)")
+                .done().expect("no done on test")
+        ;
+        assert_eq!(parser.unmatched_subcommand(), Some("everything"));
+
+        let mut subs: Vec<String> = vec!();
+        let mut verbose: bool = false;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "build", "all", "extra"));
+        parser
+            .flag('v', "verbose", "be verbose", &mut verbose, false)
+                .expect("bad flag")
+            .subcommand("build", "do a build", &mut subs, None).expect("bad sub(build)")
+                .subcommand("all", "build everything", &mut subs, None)
+                    .expect("bad sub-sub(all)")
+                    .done().expect("no done on build-all")
+                .done().expect("no done on build")
+            .subcommand("test", "test a target", &mut subs, None).expect("bad sub(test)")
+                .done().expect("no done on test")
+        ;
+        assert_eq!(parser.unmatched_subcommand(), None);
+
+        let mut subs: Vec<String> = vec!();
+        let mut verbose: bool = false;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "test", "extra"));
+        parser
+            .flag('v', "verbose", "be verbose", &mut verbose, false)
+                .expect("bad flag")
+            .subcommand("build", "do a build", &mut subs, None).expect("bad sub(build)")
+                .subcommand("all", "build everything", &mut subs, None)
+                    .expect("bad sub-sub(all)")
+                    .done().expect("no done on build-all")
+                .done().expect("no done on build")
+            .subcommand("test", "test a target", &mut subs, None).expect("bad sub(test)")
+                .done().expect("no done on test")
+        ;
+        assert_eq!(parser.unmatched_subcommand(), None);
+    }
+
+    #[test]
+    fn suggest() {
+        let mut subs: Vec<String> = vec!();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "biuld"));
+        parser
+            .subcommand("build", "do a build", &mut subs, None).expect("bad sub(build)")
+                .subcommand("all", "build everything", &mut subs, None)
+                    .expect("bad sub-sub(all)")
+                    .done().expect("no done on build-all")
+                .done().expect("no done on build")
+            .subcommand("test", "test a target", &mut subs, None).expect("bad sub(test)")
+                .done().expect("no done on test")
+        ;
+        assert_eq!(parser.unmatched_subcommand(), Some("biuld"));
+        assert_eq!(parser.suggest_subcommand("biuld"), Some("build"));
+        assert_eq!(parser.suggest_subcommand("tset"), Some("test"));
+        assert_eq!(parser.suggest_subcommand("build"), None, "exact match needs no suggestion");
+        assert_eq!(parser.suggest_subcommand("deploy"), None, "nothing close to deploy");
+        assert_eq!(parser.suggest_subcommand("al"), None, "all is not at the top-level");
+
+        let mut subs: Vec<String> = vec!();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "build", "al"));
+        parser
+            .subcommand("build", "do a build", &mut subs, None).expect("bad sub(build)")
+                .subcommand("all", "build everything", &mut subs, None)
+                    .expect("bad sub-sub(all)")
+                    .done().expect("no done on build-all")
+                .done().expect("no done on build")
+            .subcommand("test", "test a target", &mut subs, None).expect("bad sub(test)")
+                .done().expect("no done on test")
+        ;
+        assert_eq!(parser.suggest_subcommand("al"), Some("all"));
+        assert_eq!(parser.suggest_subcommand("biuld"), None, "build is not within build");
+    }
+
+    #[test]
+    fn repeated() {
+        let mut subs: Vec<String> = vec!();
+        let mut patterns: Vec<String> = vec!();
+        let mut verbose: bool = false;
+        let mut tests: Vec<String> = vec!();
+
+        let args = string_vec!("argv[0]", "filter", "foo", "filter", "-v", "bar");
+        Parser::from_strings(args)
+            .subcommand("test", "test a target", &mut tests, None).expect("bad sub(test)")
+                .done().expect("no done on test")
+            .repeated_subcommand("filter", "filter the input", &mut subs, None)
+                .expect("bad sub(filter)")
+                .flag('v', "invert", "invert the match", &mut verbose, false)
+                    .expect("bad flag")
+                .positional_list("patterns", "patterns to match", &mut patterns, true)
+                    .expect("bad positional list")
+                .done().expect("no done on filter")
+        ;
+
+        assert!(tests.is_empty(), "did not expect test to match: {:?}", tests);
+        assert_eq!(subs, string_vec!("filter", "filter"));
+        assert_eq!(patterns, string_vec!("foo", "bar"));
+        assert!(verbose, "expected flag within repeated scope to be set");
+
+        let mut subs: Vec<String> = vec!();
+        let mut patterns: Vec<String> = vec!();
+        let mut n: usize = 0;
+        Parser::from_strings(string_vec!("argv[0]", "filter", "--n", "1", "foo", "filter", "bar"))
+            .repeated_subcommand("filter", "filter the input", &mut subs, None)
+                .expect("bad sub(filter)")
+                .long_arg("n", "max matches", &mut n, None, false)
+                    .expect("bad n arg")
+                .positional_list("patterns", "patterns to match", &mut patterns, true)
+                    .expect("bad positional list")
+                .done().expect("no done on filter")
+        ;
+        assert_eq!(n, 1);
+        assert_eq!(patterns, string_vec!("foo", "bar"));
+
+        let mut subs: Vec<String> = vec!();
+        let mut n: usize = 0;
+        let mut parser = Parser::from_strings(
+            string_vec!("argv[0]", "filter", "--n", "1", "foo", "filter", "--n", "2", "bar"));
+        let result = parser
+            .repeated_subcommand("filter", "filter the input", &mut subs, None)
+                .expect("bad sub(filter)")
+                .long_arg("n", "max matches", &mut n, None, false)
+        ;
+        match result {
+            Err(Error::RepeatedValue(arg, sub)) => {
+                assert_eq!((arg.as_str(), sub), ("--n", "filter"));
+            }
+            Err(e) => { panic!("unexpected error: {:?}", e); }
+            Ok(_) => { panic!("expected repeated value error"); }
+        }
+    }
+
+    #[test]
+    fn shadowing_warnings() {
+        let mut subs: Vec<String> = vec!();
+        let mut verbose: bool = false;
+        let mut host: String = "".to_string();
+        let mut extra: bool = false;
+        let mut help: bool = false;
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "build"));
+        parser
+            .flag('v', "verbose", "verbose output", &mut verbose, false)
+                .expect("bad flag")
+            .subcommand("build", "do a build", &mut subs, None)
+                .expect("bad sub(build)")
+                .arg('h', "host", "build host", &mut host, None, false)
+                    .expect("bad host arg")
+                .flag('x', "verbose", "verbose build", &mut extra, false)
+                    .expect("bad verbose flag")
+                .done().expect("no done on build")
+            .subcommand("test", "test a target", &mut subs, None)
+                .expect("bad sub(test)")
+                .long_flag("help", "help for tests", &mut help, false)
+                    .expect("bad help flag")
+                .done().expect("no done on test")
+        ;
+
+        assert_eq!(parser.warnings(), &[
+            "build: -h, --host shadows the built-in help flag (-h, --help)".to_string(),
+            "build: -x, --verbose shadows a global option (-v, --verbose)".to_string(),
+            "test: --help shadows the built-in help flag (-h, --help)".to_string(),
+        ]);
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]"));
+        parser.flag('x', "extra", "extra output", &mut extra, false).expect("bad flag");
+        assert!(parser.warnings().is_empty(), "did not expect warnings at the top-level");
+    }
+
+    #[test]
+    fn help_header() {
+        let mut subs: Vec<String> = vec!();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "build", "--help"));
+        parser
+            .app_name("rags")
+            .app_version("1.0")
+            .app_desc("the root app")
+            .app_long_desc("describes the root app")
+            .subcommand("build", "do a build", &mut subs, Some("builds the given targets"))
+                .expect("bad sub(build)")
+                .done().expect("no done on build")
+            .subcommand("test", "test a target", &mut subs, Some("tests the given targets"))
+                .expect("bad sub(test)")
+                .done().expect("no done on test")
+        ;
+
+        let help = parser.printer.render();
+        assert!(help.starts_with("rags build - 1.0 - do a build\n"), "unexpected header:\n{}", help);
+        assert!(help.contains("builds the given targets"), "missing long desc:\n{}", help);
+        assert!(!help.contains("root app"), "unexpected root description:\n{}", help);
+        assert!(!help.contains("tests the given targets"), "unexpected sibling:\n{}", help);
+    }
 }
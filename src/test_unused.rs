@@ -16,7 +16,7 @@ mod unused {
                 .expect("count parse error")
         ;
 
-        assert!(flag == true, "expected flag to be true");
+        assert!(flag, "expected flag to be true");
         assert!(count == 4, "expected count to be 4 but got {}", count);
 
         let unused = parser.unused();
@@ -34,6 +34,88 @@ mod unused {
     }
 
 
+    #[test]
+    fn unknown_in_run() {
+        let mut count: usize = 0;
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-cxc"));
+        parser
+            .short_count('c', "count that does something", &mut count, 1)
+                .expect("count parse error")
+        ;
+
+        assert!(count == 2, "expected count to be 2 but got {}", count);
+        let unused = parser.unused();
+        assert!(unused.len() == 1, "expected only 1 unused, got {}", unused.len());
+        assert!(unused[0].arg == "-x", "got unexpected unused: {}", unused[0].arg);
+        assert!(unused[0].looks_like == LooksLike::ShortArg,
+            "exepcted unused[0] to look like short arg, got '{}'", unused[0].looks_like);
+    }
+
+    #[test]
+    fn dashes_in_run() {
+        let mut count: usize = 0;
+        let mut other: usize = 0;
+
+        let args = string_vec!("argv[0]", "-v--", "-v-x-");
+        let mut parser = Parser::from_strings(args);
+        parser
+            .short_count('v', "count that does something", &mut count, 1)
+                .expect("count parse error")
+            .short_count('x', "another count", &mut other, 1)
+                .expect("count parse error")
+        ;
+
+        assert!(count == 2, "expected count to be 2 but got {}", count);
+        assert!(other == 1, "expected other to be 1 but got {}", other);
+
+        let unused = parser.unused();
+        let args = unused.iter().map(|u| u.arg.as_str()).collect::<Vec<&str>>();
+        assert_eq!(args, vec!("-", "-", "-", "-"), "unexpected unused args");
+        assert!(unused.iter().all(|u| u.looks_like == LooksLike::ShortArg),
+            "expected embedded dashes to look like short args");
+    }
+
+    #[test]
+    fn valued_before_dashes_attached() {
+        let mut file: String = "".to_string();
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-f--file", "foo"));
+        parser
+            .short_arg('f', "a file", &mut file, None, false)
+                .expect("bad short arg")
+        ;
+
+        assert_eq!(file, "--file", "expected the rest of the arg to be the value");
+        let unused = parser.unused().into_iter().map(|u| u.arg).collect::<Vec<String>>();
+        assert_eq!(unused, string_vec!("foo"));
+    }
+
+    #[test]
+    fn consumption() {
+        let mut flag: bool = false;
+        let mut count: usize = 0;
+
+        let args = string_vec!("argv[0]", "-f", "-cca", "boo.berry", "--", "after");
+        let mut parser = Parser::from_strings(args);
+        parser
+            .short_flag('f', "flag that does something", &mut flag, false)
+                .expect("flag parse error")
+            .short_count('c', "count that does something", &mut count, 1)
+                .expect("count parse error")
+        ;
+
+        let mask = parser.consumption();
+        assert!(mask.contains(0), "expected program name to be consumed");
+        assert!(mask.contains(1), "expected flag to be consumed");
+        assert!(!mask.contains(2), "expected partially matched run to be unconsumed");
+        assert!(!mask.contains(3), "expected positional to be unconsumed");
+        assert!(mask.contains(4), "expected arg-stop to be consumed");
+        assert!(mask.contains(5), "expected trailing args to be consumed");
+        assert!(!mask.contains(6), "expected out of range index to be unconsumed");
+        assert_eq!(mask.iter_unconsumed().collect::<Vec<usize>>(), vec!(2, 3));
+    }
+
     #[test]
     fn unhandled_positional() {
         let mut flag: bool = false;
@@ -48,11 +130,151 @@ mod unused {
                 .expect("count parse error")
         ;
 
-        assert!(flag == true, "expected flag to be true");
+        assert!(flag, "expected flag to be true");
         assert!(count == 4, "expected count to be 4 but got {}", count);
 
         let unused = parser.unused();
         assert!(unused.len() == 1, "expected only 1 unused, got {}", unused.len());
         assert!(unused[0].arg == "boo.berry", "got unexpected unused: {}", unused[0].arg);
     }
+
+    #[test]
+    fn empty_string() {
+        let mut flag: bool = false;
+
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "", "-f", ""));
+        parser
+            .short_flag('f', "flag that does something", &mut flag, false)
+                .expect("flag parse error")
+        ;
+
+        assert!(flag, "expected flag to be true");
+        let unused = parser.unused();
+        assert!(unused.len() == 2, "expected 2 unused, got {}", unused.len());
+        for u in unused.iter() {
+            assert!(u.arg.is_empty(), "expected empty unused arg, got '{}'", u.arg);
+            assert!(u.looks_like == LooksLike::Positional,
+                "exepcted empty arg to look like positional, got '{}'", u.looks_like);
+        }
+
+        let blank = Unused::new("".to_string());
+        assert!(blank.looks_like == LooksLike::Positional,
+            "exepcted empty arg to look like positional, got '{}'", blank.looks_like);
+    }
+
+    #[test]
+    fn lone_dash() {
+        let dash = Unused::new("-".to_string());
+        assert!(dash.looks_like == LooksLike::Positional,
+            "exepcted '-' to look like positional, got '{}'", dash.looks_like);
+
+        let mut flag: bool = false;
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-", "-f"));
+        parser
+            .short_flag('f', "flag that does something", &mut flag, false)
+                .expect("flag parse error")
+        ;
+        let unused = parser.unused();
+        assert!(unused.len() == 1, "expected 1 unused, got {}", unused.len());
+        assert!(unused[0].arg == "-", "got unexpected unused: {}", unused[0].arg);
+        assert!(unused[0].looks_like == LooksLike::Positional,
+            "exepcted '-' to look like positional, got '{}'", unused[0].looks_like);
+    }
+
+    #[test]
+    fn long_only() {
+        let mut a: bool = false;
+        let mut count: usize = 0;
+        let mut verbose: bool = false;
+        let mut file: String = "".to_string();
+
+        let args = string_vec!("argv[0]", "-abc", "--verbose", "-h", "-c2");
+        let mut parser = Parser::from_strings(args);
+        parser
+            .long_only()
+                .expect("bad long-only")
+            .flag('a', "all", "all of them", &mut a, false)
+                .expect("flag parse error")
+            .count_numeric('c', "count", "count that does something", &mut count, 1)
+                .expect("count parse error")
+            .flag('v', "verbose", "verbose output", &mut verbose, false)
+                .expect("flag parse error")
+        ;
+
+        assert!(!parser.wants_help(), "did not expect -h to request help");
+        assert!(!a, "did not expect -a within -abc to match");
+        assert!(count == 0, "expected count to be 0 but got {}", count);
+        assert!(verbose, "expected long flag to match");
+
+        let unused = parser.unused();
+        assert!(unused.len() == 3, "expected 3 unused, got {}", unused.len());
+        assert!(unused[0].arg == "-abc", "got unexpected unused: {}", unused[0].arg);
+        for u in unused.iter() {
+            assert!(u.looks_like == LooksLike::Positional,
+                "exepcted '{}' to look like positional, got '{}'", u.arg, u.looks_like);
+        }
+
+        parser.positional("file", "a file", &mut file, false).expect("positional parse error");
+        assert_eq!(file, "-abc", "expected -abc to be taken as a positional");
+    }
+
+    #[test]
+    fn on_unknown() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut flag: bool = false;
+        let seen: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(vec!()));
+        let handler_seen = seen.clone();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "--plugin-x", "-fz", "extra"));
+        parser
+            .on_unknown(move |u| {
+                handler_seen.borrow_mut().push(u.arg.clone());
+                if u.arg.starts_with("--plugin") || (u.arg == "-z") {
+                    UnknownAction::Consume
+                } else if u.arg == "--bad" {
+                    UnknownAction::Error
+                } else {
+                    UnknownAction::Ignore
+                }
+            })
+            .short_flag('f', "flag that does something", &mut flag, false)
+                .expect("flag parse error")
+        ;
+        assert!(flag, "expected flag to be set");
+        if let Err(e) = parser.finish() {
+            panic!("unexpected error: {}", e);
+        }
+        assert_eq!(*seen.borrow(), string_vec!("--plugin-x", "-z", "extra"));
+
+        let unused = parser.unused();
+        assert!(unused.len() == 1, "expected 1 unused, got {}", unused.len());
+        assert!(unused[0].arg == "extra", "got unexpected unused: {}", unused[0].arg);
+
+        let mut flag: bool = false;
+        let seen: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(vec!()));
+        let handler_seen = seen.clone();
+        let mut parser = Parser::from_strings(string_vec!("argv[0]", "-f", "--plugin-y", "--bad", "extra"));
+        parser
+            .on_unknown(move |u| {
+                handler_seen.borrow_mut().push(u.arg.clone());
+                if u.arg.starts_with("--plugin") || (u.arg == "-z") {
+                    UnknownAction::Consume
+                } else if u.arg == "--bad" {
+                    UnknownAction::Error
+                } else {
+                    UnknownAction::Ignore
+                }
+            })
+            .short_flag('f', "flag that does something", &mut flag, false)
+                .expect("flag parse error")
+        ;
+        assert!(flag, "expected flag to be set");
+        match parser.finish() {
+            Err(Error::UnexpectedArgument(arg, _)) => { assert_eq!(arg, "--bad"); }
+            Err(e) => { panic!("unexpected error: {}", e); }
+            Ok(_) => { panic!("expected unexpected argument error"); }
+        }
+        assert_eq!(*seen.borrow(), string_vec!("--plugin-y", "--bad"));
+    }
 }